@@ -0,0 +1,77 @@
+use ascii_gen::art::ArtBuffer;
+use ascii_gen::charset::Charset;
+use ascii_gen::color::ColorMode;
+use ascii_gen::converter::{AsciiOptions, ImageConverter, ToAsciiArt};
+use ascii_gen::temporal::TemporalFilter;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use image::{DynamicImage, RgbImage};
+
+const SIZES: [(u32, u32); 3] = [(80, 24), (160, 48), (320, 96)];
+
+/// A 1080p diagonal gradient, so every cell samples different colors.
+fn source() -> ImageConverter {
+    let image = RgbImage::from_fn(1920, 1080, |x, y| {
+        image::Rgb([
+            (x * 255 / 1920) as u8,
+            (y * 255 / 1080) as u8,
+            ((x + y) % 256) as u8,
+        ])
+    });
+    ImageConverter::from_image(DynamicImage::ImageRgb8(image))
+}
+
+fn sizes(c: &mut Criterion) {
+    let source = source();
+    let mut group = c.benchmark_group("to_ascii_art/size");
+    for (width, height) in SIZES {
+        group.throughput(Throughput::Elements(u64::from(width * height)));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{width}x{height}")),
+            |b| {
+                let options = AsciiOptions::new(width, height, 1.0);
+                b.iter(|| source.to_ascii_art(Some(options.clone())).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+fn charsets(c: &mut Criterion) {
+    let source = source();
+    let mut group = c.benchmark_group("to_ascii_art/charset");
+    for charset in [
+        Charset::Standard,
+        Charset::Simple,
+        Charset::Blocks,
+        Charset::Detailed,
+    ] {
+        let options = AsciiOptions::new(160, 48, 1.0).with_charset(charset);
+        group.bench_function(BenchmarkId::from_parameter(charset.name()), |b| {
+            b.iter(|| source.to_ascii_art(Some(options.clone())).unwrap());
+        });
+    }
+    group.finish();
+}
+
+/// Converts into a reused buffer as videos do, so only the first iteration makes a plan.
+fn color_modes(c: &mut Criterion) {
+    let source = source();
+    let options = AsciiOptions::new(160, 48, 1.0);
+    let mut group = c.benchmark_group("convert/color_mode");
+    for (name, color_mode) in [
+        ("none", ColorMode::None),
+        ("truecolor", ColorMode::Truecolor),
+        ("ansi256", ColorMode::Ansi256),
+        ("halfblock", ColorMode::Halfblock),
+    ] {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            let mut art = ArtBuffer::default();
+            let mut temporal_filter = TemporalFilter::new(0);
+            b.iter(|| art.convert(&source, &options, color_mode, &mut temporal_filter));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, sizes, charsets, color_modes);
+criterion_main!(benches);
@@ -0,0 +1,161 @@
+use crate::color::{self, CellColor};
+
+/// Columns between tab stops.
+const TAB_WIDTH: usize = 8;
+
+/// Art read back from text, with the color its escape codes gave each cell.
+pub struct AnsiArt {
+    pub art: String,
+    /// Color of each cell, in the order of the art's characters without line breaks.
+    pub colors: Vec<CellColor>,
+}
+
+/// A foreground set by an escape code. The eight basic colors are kept apart, as bold
+/// brightens them.
+#[derive(Clone, Copy, Default)]
+enum Pen {
+    #[default]
+    Default,
+    Basic(u8),
+    Rgb([u8; 3]),
+}
+
+/// The graphics state escape codes change.
+#[derive(Clone, Copy, Default)]
+struct State {
+    fg: Pen,
+    bg: Option<[u8; 3]>,
+    bold: bool,
+    reverse: bool,
+}
+
+/// Parses text with ANSI color codes, such as the art `--format ansi` prints, into its
+/// characters and their colors. Cells without a color of their own get `foreground` and no
+/// background. Escape codes other than colors, such as cursor moves, are dropped.
+///
+/// ```
+/// use ascii_gen::ansi;
+///
+/// let parsed = ansi::parse("\x1b[38;2;255;0;0m#\x1b[0m.\n", [200; 3], [0; 3]);
+/// assert_eq!(parsed.art, "#.\n");
+/// assert_eq!(parsed.colors[0].fg, [255, 0, 0]);
+/// assert_eq!(parsed.colors[1].fg, [200; 3]);
+/// ```
+pub fn parse(text: &str, foreground: [u8; 3], background: [u8; 3]) -> AnsiArt {
+    let mut parsed = AnsiArt {
+        art: String::with_capacity(text.len()),
+        colors: Vec::new(),
+    };
+    let mut state = State::default();
+    let mut column = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            if c == 'm' {
+                                state.apply(&params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                // Titles and hyperlinks, ended by a bell or `ESC \`
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            // Lines may end with \r\n, as files saved on Windows do
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                parsed.art.push('\n');
+                column = 0;
+            }
+            '\t' => {
+                let cell = state.cell(foreground, background);
+                for _ in 0..TAB_WIDTH - column % TAB_WIDTH {
+                    parsed.art.push(' ');
+                    parsed.colors.push(cell);
+                }
+                column += TAB_WIDTH - column % TAB_WIDTH;
+            }
+            c if c.is_control() => {}
+            c => {
+                parsed.art.push(c);
+                parsed.colors.push(state.cell(foreground, background));
+                column += 1;
+            }
+        }
+    }
+    parsed
+}
+
+impl State {
+    /// Applies the parameters of a Select Graphic Rendition code, `ESC [ ... m`.
+    fn apply(&mut self, params: &str) {
+        let mut codes = params
+            .split([';', ':'])
+            .map(|code| code.parse::<u16>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                7 => self.reverse = true,
+                27 => self.reverse = false,
+                30..=37 => self.fg = Pen::Basic((code - 30) as u8),
+                90..=97 => self.fg = Pen::Rgb(color::ansi256_to_rgb((code - 90) as u8 + 8)),
+                39 => self.fg = Pen::Default,
+                40..=47 => self.bg = Some(color::ansi256_to_rgb((code - 40) as u8)),
+                100..=107 => self.bg = Some(color::ansi256_to_rgb((code - 100) as u8 + 8)),
+                49 => self.bg = None,
+                38 | 48 => {
+                    let rgb = match codes.next() {
+                        Some(5) => codes
+                            .next()
+                            .map(|index| color::ansi256_to_rgb(index.min(255) as u8)),
+                        Some(2) => {
+                            let mut channel = || codes.next().unwrap_or(0).min(255) as u8;
+                            Some([channel(), channel(), channel()])
+                        }
+                        _ => None,
+                    };
+                    match (code, rgb) {
+                        (38, Some(rgb)) => self.fg = Pen::Rgb(rgb),
+                        (_, Some(rgb)) => self.bg = Some(rgb),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The colors a character drawn now gets.
+    fn cell(&self, foreground: [u8; 3], background: [u8; 3]) -> CellColor {
+        let fg = match self.fg {
+            Pen::Default => foreground,
+            Pen::Basic(index) if self.bold => color::ansi256_to_rgb(index + 8),
+            Pen::Basic(index) => color::ansi256_to_rgb(index),
+            Pen::Rgb(rgb) => rgb,
+        };
+        if self.reverse {
+            CellColor {
+                fg: self.bg.unwrap_or(background),
+                bg: Some(fg),
+            }
+        } else {
+            CellColor { fg, bg: self.bg }
+        }
+    }
+}
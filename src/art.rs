@@ -0,0 +1,205 @@
+use crate::charset::CharMapper;
+use crate::color::{self, CellColor, ColorMode};
+use crate::converter::{self, AsciiOptions, PixelSource};
+use crate::error::AsciiGenError;
+use crate::histogram::Histogram;
+use crate::plan::ConversionPlan;
+use crate::progress::ConversionHandle;
+use crate::temporal::TemporalFilter;
+use crate::tone::ToneTable;
+
+/// Converted art together with the scratch buffers reused between conversions.
+#[derive(Default)]
+pub struct ArtBuffer {
+    pub art: String,
+    pub colors: Vec<CellColor>,
+    next_colors: Vec<CellColor>,
+    pixels: Vec<[u8; 3]>,
+    grid: Vec<u8>,
+    levels: Vec<u8>,
+    /// Luminance of the converted cells, only counted when requested.
+    pub histogram: Option<Histogram>,
+    /// Plan of the last conversion, reused while the size and settings stay the same.
+    plan: Option<ConversionPlan>,
+}
+
+impl ArtBuffer {
+    /// An empty buffer that also counts a luminance histogram on each conversion.
+    pub fn with_histogram() -> Self {
+        Self {
+            histogram: Some(Histogram::default()),
+            ..Self::default()
+        }
+    }
+
+    /// Converts a frame into the art, reusing the previous frame's colors for cells
+    /// that barely changed.
+    pub fn convert(
+        &mut self,
+        source: &impl PixelSource,
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+        temporal_filter: &mut TemporalFilter,
+    ) {
+        // A fresh handle is never cancelled
+        let handle = ConversionHandle::default();
+        let _ = self.convert_tracked(source, options, color_mode, temporal_filter, &handle);
+    }
+
+    /// Same as `convert`, reporting progress to `handle` and stopping once it is cancelled,
+    /// which leaves the previous art in place.
+    pub fn convert_tracked(
+        &mut self,
+        source: &impl PixelSource,
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+        temporal_filter: &mut TemporalFilter,
+        handle: &ConversionHandle,
+    ) -> Result<(), AsciiGenError> {
+        let dimensions = source.dimensions();
+        let plan = match self.plan.take() {
+            Some(plan) if plan.is_for(dimensions, options, color_mode) => plan,
+            _ => ConversionPlan::new(dimensions, options, color_mode),
+        };
+        let result = self.convert_planned(source, &plan, temporal_filter, handle);
+        self.plan = Some(plan);
+        result
+    }
+
+    /// Same as `convert_tracked`, with the plan made for the source's size and settings.
+    pub fn convert_planned(
+        &mut self,
+        source: &impl PixelSource,
+        plan: &ConversionPlan,
+        temporal_filter: &mut TemporalFilter,
+        handle: &ConversionHandle,
+    ) -> Result<(), AsciiGenError> {
+        tracing::trace_span!("sample")
+            .in_scope(|| plan.sample(source, &mut self.pixels, handle))?;
+        let _span = tracing::trace_span!("map").entered();
+        self.convert_art(
+            plan.options(),
+            plan.table(),
+            plan.color_mode(),
+            temporal_filter,
+        );
+
+        if let Some(histogram) = self.histogram.as_mut() {
+            let table = plan.table();
+            histogram.fill(self.pixels.iter().map(|&rgb| table.tone(rgb)));
+        }
+        Ok(())
+    }
+
+    /// Cell colors sampled by the last conversion, row by row, before any tone mapping.
+    pub fn sampled(&self) -> &[[u8; 3]] {
+        &self.pixels
+    }
+
+    /// Converts colors sampled earlier with the same size and color mode, skipping the
+    /// sampling pass when only the gamma, tone curve, charset or inversion changed.
+    pub fn convert_sampled(
+        &mut self,
+        sampled: &[[u8; 3]],
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+        temporal_filter: &mut TemporalFilter,
+    ) {
+        self.pixels.clear();
+        self.pixels.extend_from_slice(sampled);
+        let table = ToneTable::new(options);
+        self.convert_art(options, &table, color_mode, temporal_filter);
+
+        if let Some(histogram) = self.histogram.as_mut() {
+            histogram.fill(self.pixels.iter().map(|&rgb| table.tone(rgb)));
+        }
+    }
+
+    /// Builds the art from the sampled pixels.
+    fn convert_art(
+        &mut self,
+        options: &AsciiOptions,
+        table: &ToneTable,
+        color_mode: ColorMode,
+        temporal_filter: &mut TemporalFilter,
+    ) {
+        if !color_mode.is_enabled() {
+            self.colors.clear();
+            self.draw_cells(options, table, temporal_filter);
+            return;
+        }
+
+        let mut colors = std::mem::take(&mut self.next_colors);
+
+        if color_mode == ColorMode::Halfblock {
+            converter::pixels_to_half_blocks_into(
+                &mut self.art,
+                &mut colors,
+                &self.pixels,
+                options,
+            );
+        } else {
+            self.draw_cells(options, table, temporal_filter);
+
+            colors.clear();
+            colors.extend(self.pixels.iter().map(|&fg| CellColor { fg, bg: None }));
+        }
+
+        if let Some(palette) = options.palette() {
+            palette.quantize(&mut colors);
+        }
+        color::stabilize(&self.colors, &mut colors);
+        self.next_colors = std::mem::replace(&mut self.colors, colors);
+    }
+
+    /// Draws the sampled pixels as characters. Without a temporal filter to blend the tones
+    /// first, the row kernel finds each cell's charset index along with its tone.
+    fn draw_cells(
+        &mut self,
+        options: &AsciiOptions,
+        table: &ToneTable,
+        temporal_filter: &mut TemporalFilter,
+    ) {
+        if temporal_filter.is_enabled() {
+            table.tones_into(&self.pixels, &mut self.grid);
+            temporal_filter.apply(&mut self.grid, options.charset());
+            table.tone_art_into(&self.grid, options.width(), &mut self.art);
+        } else {
+            table.cells_into(&self.pixels, &mut self.grid, &mut self.levels);
+            table.art_into(&self.levels, options.width(), &mut self.art);
+        }
+    }
+
+    /// Redraws the converted cells with `mapper`, keeping half blocks as they are.
+    pub fn remap(
+        &mut self,
+        mapper: &dyn CharMapper,
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+    ) {
+        if color_mode == ColorMode::Halfblock {
+            return;
+        }
+        let _span = tracing::trace_span!("remap").entered();
+
+        self.art.clear();
+        for row in self.pixels.chunks(options.width().max(1) as usize) {
+            for &rgb in row {
+                self.art.push(mapper.glyph(options.tone(rgb), rgb));
+            }
+            self.art.push('\n');
+        }
+
+        for (cell, &rgb) in self.colors.iter_mut().zip(&self.pixels) {
+            if let Some(fg) = mapper.color(options.tone(rgb), rgb) {
+                cell.fg = fg;
+            }
+        }
+    }
+
+    /// Renders the source itself as colored half blocks.
+    pub fn convert_original(&mut self, source: &impl PixelSource, width: u32, height: u32) {
+        let options = AsciiOptions::new(width, height, 1.0);
+        source.to_half_blocks_into(&mut self.art, &mut self.colors, &mut self.pixels, &options);
+    }
+}
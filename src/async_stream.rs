@@ -0,0 +1,107 @@
+use crate::converter::AsciiOptions;
+use crate::error::AsciiGenError;
+use crate::stream::{AsciiFrame, VideoConverter};
+use futures_core::Stream;
+use std::{
+    path::PathBuf,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// Frames converted ahead of the consumer before decoding waits for it.
+pub const DEFAULT_CAPACITY: usize = 4;
+
+/// Converts a video on its own thread, yielding the frames as an async `Stream`.
+///
+/// Decoding pauses once `capacity` frames are waiting, and stops when the stream is
+/// cancelled or dropped. Frames come as fast as they are read, leaving pacing to the caller.
+///
+/// ```no_run
+/// use ascii_gen::{async_stream::AsyncVideoStream, converter::AsciiOptions};
+///
+/// # async fn play() -> Result<(), ascii_gen::error::AsciiGenError> {
+/// let mut frames = AsyncVideoStream::open("movie.mp4", AsciiOptions::default());
+/// while let Some(frame) = frames.next_frame().await {
+///     println!("{}", frame?.art);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncVideoStream {
+    frames: mpsc::Receiver<Result<AsciiFrame, AsciiGenError>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl AsyncVideoStream {
+    /// Opens a video with the default deinterlacing and rotation, as `VideoConverter::open`.
+    pub fn open(path: impl Into<PathBuf>, options: AsciiOptions) -> Self {
+        let path = path.into();
+        Self::spawn(DEFAULT_CAPACITY, move || {
+            VideoConverter::open(path, options)
+        })
+    }
+
+    /// Runs the converter made by `open` on a new thread, so it can be configured freely.
+    ///
+    /// The converter is created on the decoding thread so its ffmpeg contexts never move
+    /// between threads. An error opening it is the stream's only item.
+    pub fn spawn<F>(capacity: usize, open: F) -> Self
+    where
+        F: FnOnce() -> Result<VideoConverter, AsciiGenError> + Send + 'static,
+    {
+        let (sender, frames) = mpsc::channel(capacity.max(1));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let thread_cancelled = Arc::clone(&cancelled);
+
+        std::thread::spawn(move || {
+            let converter = match open() {
+                Ok(converter) => converter,
+                Err(e) => {
+                    let _ = sender.blocking_send(Err(e));
+                    return;
+                }
+            };
+            for frame in converter {
+                let failed = frame.is_err();
+                // Waits while the channel is full, and fails once the stream is dropped
+                if thread_cancelled.load(Ordering::Relaxed)
+                    || sender.blocking_send(frame).is_err()
+                    || failed
+                {
+                    break;
+                }
+            }
+        });
+
+        Self { frames, cancelled }
+    }
+
+    /// Waits for the next frame, or `None` once the video ends or the stream is cancelled.
+    pub async fn next_frame(&mut self) -> Option<Result<AsciiFrame, AsciiGenError>> {
+        self.frames.recv().await
+    }
+
+    /// Stops decoding after the current frame. Frames already converted are still yielded.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Stream for AsyncVideoStream {
+    type Item = Result<AsciiFrame, AsciiGenError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.frames.poll_recv(cx)
+    }
+}
+
+impl Drop for AsyncVideoStream {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
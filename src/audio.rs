@@ -0,0 +1,34 @@
+use ascii_gen::error::AsciiGenError;
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{OutputStream, OutputStreamHandle};
+
+/// Opens the default output device, or else the first device of any audio backend that
+/// plays. Windows may have no default device, e.g. over remote desktop, while WASAPI or
+/// ASIO still offer others.
+pub fn output() -> Result<(OutputStream, OutputStreamHandle), AsciiGenError> {
+    let default_error = match OutputStream::try_default() {
+        Ok(output) => return Ok(output),
+        Err(e) => e,
+    };
+    tracing::debug!(error = %default_error, "cannot open the default audio device");
+
+    for id in rodio::cpal::available_hosts() {
+        let Ok(host) = rodio::cpal::host_from_id(id) else {
+            continue;
+        };
+        let Ok(devices) = host.output_devices() else {
+            continue;
+        };
+        for device in devices {
+            let name = device.name().unwrap_or_default();
+            match OutputStream::try_from_device(&device) {
+                Ok(output) => {
+                    tracing::info!(backend = id.name(), device = name, "playing audio");
+                    return Ok(output);
+                }
+                Err(e) => tracing::debug!(backend = id.name(), device = name, error = %e),
+            }
+        }
+    }
+    Err(AsciiGenError::Audio(default_error.to_string()))
+}
@@ -0,0 +1,102 @@
+use crate::art::ArtBuffer;
+use crate::color::{CellColor, ColorMode};
+use crate::converter::{self, AsciiOptions, ImageConverter};
+use crate::error::AsciiGenError;
+use crate::output::Renderer;
+use crate::temporal::TemporalFilter;
+use std::{path::Path, sync::atomic::AtomicUsize, sync::atomic::Ordering, sync::Mutex};
+
+/// The art of one converted image, with a color per cell when converted in color.
+#[derive(Clone, Debug, Default)]
+pub struct AsciiArt {
+    pub art: String,
+    pub colors: Vec<CellColor>,
+}
+
+impl AsciiArt {
+    pub fn render(&self, renderer: &dyn Renderer, color_mode: ColorMode) -> String {
+        renderer.render(&self.art, &self.colors, color_mode)
+    }
+}
+
+/// Converts many image files on up to `parallelism` threads, every core when 0.
+///
+/// Each file gets its own result, in the order given, so one bad file does not stop the rest.
+///
+/// ```no_run
+/// use ascii_gen::{batch, color::ColorMode, converter::AsciiOptions};
+///
+/// let files = ["a.png", "b.jpg"];
+/// let arts = batch::convert_many(&files, &AsciiOptions::default(), ColorMode::None, 0);
+/// for (file, art) in files.iter().zip(arts) {
+///     match art {
+///         Ok(art) => println!("{}", art.art),
+///         Err(e) => eprintln!("{}: {}", file, e),
+///     }
+/// }
+/// ```
+pub fn convert_many<P: AsRef<Path> + Sync>(
+    inputs: &[P],
+    options: &AsciiOptions,
+    color_mode: ColorMode,
+    parallelism: usize,
+) -> Vec<Result<AsciiArt, AsciiGenError>> {
+    convert_many_with(inputs, parallelism, |path| {
+        let source = ImageConverter::from_image(converter::open_image(path)?);
+        let mut buffer = ArtBuffer::default();
+        buffer.convert(
+            &converter::crop(&source, options)?,
+            options,
+            color_mode,
+            &mut TemporalFilter::new(0),
+        );
+        Ok(AsciiArt {
+            art: buffer.art,
+            colors: buffer.colors,
+        })
+    })
+}
+
+/// Runs `convert` on every input like `convert_many`, for conversions of its own.
+pub fn convert_many_with<I, T, F>(
+    inputs: &[I],
+    parallelism: usize,
+    convert: F,
+) -> Vec<Result<T, AsciiGenError>>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&I) -> Result<T, AsciiGenError> + Sync,
+{
+    let threads = match parallelism {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+    .min(inputs.len());
+    if threads <= 1 {
+        return inputs.iter().map(convert).collect();
+    }
+
+    // Threads take the next input as they finish, so slow images do not hold up a share
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..inputs.len()).map(|_| None).collect::<Vec<_>>());
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(index) else {
+                    break;
+                };
+                let result = convert(input);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every input is converted"))
+        .collect()
+}
@@ -0,0 +1,157 @@
+use crate::cli::{self, Args};
+use crate::stats::millis;
+use ascii_gen::art::ArtBuffer;
+use ascii_gen::converter::{self, AsciiOptions, ImageConverter, PixelSource};
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::output;
+use ascii_gen::stream::VideoConverter;
+use ascii_gen::temporal::TemporalFilter;
+use image::{DynamicImage, RgbImage};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Average time of each stage at one width.
+struct Row {
+    width: u32,
+    height: u32,
+    decode: Option<Duration>,
+    convert: Duration,
+    render: Duration,
+}
+
+impl Row {
+    fn frames_per_second(&self) -> f64 {
+        let total = self.decode.unwrap_or_default() + self.convert + self.render;
+        1.0 / total.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Converts `frames` frames of the input at each width, returning the timings as a table.
+pub fn run(
+    input: Option<&str>,
+    widths: &[u32],
+    frames: usize,
+    args: &Args,
+) -> Result<String, AsciiGenError> {
+    let frames = frames.max(1);
+    let mut rows = Vec::with_capacity(widths.len());
+
+    match input {
+        Some(path) if cli::is_video_file(Path::new(path)) => {
+            for &width in widths {
+                rows.push(bench_video(path, width, frames, args)?);
+            }
+        }
+        Some(path) => {
+            let start = Instant::now();
+            let image = converter::open_image_with(path, args.convert.max_memory)?;
+            let decode = start.elapsed();
+            let source = ImageConverter::from_image(image);
+            for &width in widths {
+                let mut row = bench_image(&source, width, frames, args);
+                row.decode = Some(decode);
+                rows.push(row);
+            }
+        }
+        None => {
+            let source = ImageConverter::from_image(gradient(1920, 1080));
+            for &width in widths {
+                rows.push(bench_image(&source, width, frames, args));
+            }
+        }
+    }
+
+    let mut table = format!(
+        "{:>6} {:>6} {:>10} {:>11} {:>10} {:>8}\n",
+        "width", "height", "decode ms", "convert ms", "render ms", "fps"
+    );
+    for row in &rows {
+        let decode = row
+            .decode
+            .map_or("-".to_string(), |decode| format!("{:.2}", millis(decode)));
+        table.push_str(&format!(
+            "{:>6} {:>6} {:>10} {:>11.2} {:>10.2} {:>8.1}\n",
+            row.width,
+            row.height,
+            decode,
+            millis(row.convert),
+            millis(row.render),
+            row.frames_per_second()
+        ));
+    }
+    Ok(table)
+}
+
+fn options(source: (u32, u32), width: u32, args: &Args) -> AsciiOptions {
+    let height = converter::height_for_width(source, width);
+    AsciiOptions::new(width, height, args.convert.gamma)
+        .with_charset(args.convert.charset)
+        .with_invert(args.convert.invert)
+}
+
+fn bench_image(source: &ImageConverter, width: u32, frames: usize, args: &Args) -> Row {
+    let options = options(source.dimensions(), width, args);
+    let color_mode = args.convert.color;
+    let mut buffer = ArtBuffer::default();
+    let (mut convert, mut render) = (Duration::ZERO, Duration::ZERO);
+
+    for _ in 0..frames {
+        let start = Instant::now();
+        buffer.convert(source, &options, color_mode, &mut TemporalFilter::new(0));
+        convert += start.elapsed();
+
+        let start = Instant::now();
+        let rendered = output::render(&buffer.art, &buffer.colors, color_mode, args.convert.format);
+        render += start.elapsed();
+        std::hint::black_box(rendered);
+    }
+
+    Row {
+        width: options.width(),
+        height: options.height(),
+        decode: None,
+        convert: convert / frames as u32,
+        render: render / frames as u32,
+    }
+}
+
+fn bench_video(path: &str, width: u32, frames: usize, args: &Args) -> Result<Row, AsciiGenError> {
+    let mut converter = VideoConverter::open(path, AsciiOptions::default())?;
+    let options = options(converter.stream().dimensions(), width, args);
+    let color_mode = args.convert.color;
+    converter.set_options(options.clone(), color_mode);
+
+    let (mut decode, mut convert, mut render) = (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+    let mut count: u32 = 0;
+    for frame in converter.take(frames) {
+        let frame = frame?;
+        decode += frame.decode_time;
+        convert += frame.convert_time;
+
+        let start = Instant::now();
+        let rendered = output::render(&frame.art, &frame.colors, color_mode, args.convert.format);
+        render += start.elapsed();
+        std::hint::black_box(rendered);
+        count += 1;
+    }
+
+    let count = count.max(1);
+    Ok(Row {
+        width: options.width(),
+        height: options.height(),
+        decode: Some(decode / count),
+        convert: convert / count,
+        render: render / count,
+    })
+}
+
+/// A colorful gradient to measure with when no input is given.
+fn gradient(width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([
+            (x * 255 / width) as u8,
+            (y * 255 / height) as u8,
+            ((x + y) * 255 / (width + height)) as u8,
+        ])
+    }))
+}
@@ -0,0 +1,139 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List, ListItem},
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// An entry of the directory being browsed.
+struct Entry {
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+}
+
+/// Result of a key press in the file browser.
+pub enum BrowserAction {
+    None,
+    Close,
+    Open(PathBuf),
+}
+
+/// A directory listing with name filtering, used to pick another file to open.
+pub struct FileBrowser {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    filter: String,
+    selected: usize,
+}
+
+impl FileBrowser {
+    /// Lists the given directory, directories first and then files, both sorted by name.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        let dir = dir.canonicalize()?;
+        let mut entries = Vec::new();
+
+        if let Some(parent) = dir.parent() {
+            entries.push(Entry {
+                name: "..".to_string(),
+                path: parent.to_path_buf(),
+                is_dir: true,
+            });
+        }
+
+        let mut listed: Vec<Entry> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| Entry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                is_dir: entry.path().is_dir(),
+                path: entry.path(),
+            })
+            .filter(|entry| !entry.name.starts_with('.'))
+            .collect();
+        listed.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name)));
+        entries.extend(listed);
+
+        Ok(Self {
+            dir,
+            entries,
+            filter: String::new(),
+            selected: 0,
+        })
+    }
+
+    /// Entries whose name contains the filter, ignoring case.
+    fn visible(&self) -> Vec<&Entry> {
+        let filter = self.filter.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|entry| entry.name.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> BrowserAction {
+        let count = self.visible().len();
+
+        match code {
+            KeyCode::Esc => return BrowserAction::Close,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.selected = 0;
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.selected = 0;
+            }
+            KeyCode::Enter => {
+                let Some(entry) = self
+                    .visible()
+                    .get(self.selected)
+                    .map(|e| (e.path.clone(), e.is_dir))
+                else {
+                    return BrowserAction::None;
+                };
+
+                match entry {
+                    (path, true) => {
+                        if let Ok(browser) = FileBrowser::open(&path) {
+                            *self = browser;
+                        }
+                    }
+                    (path, false) => return BrowserAction::Open(path),
+                }
+            }
+            _ => {}
+        }
+
+        BrowserAction::None
+    }
+
+    /// Index of the highlighted entry among the visible ones.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn widget(&self) -> List<'_> {
+        let items: Vec<ListItem> = self
+            .visible()
+            .into_iter()
+            .map(|entry| {
+                let name = if entry.is_dir {
+                    format!("{}/", entry.name)
+                } else {
+                    entry.name.clone()
+                };
+                ListItem::new(name)
+            })
+            .collect();
+
+        let title = format!("Open: {} [{}]", self.dir.display(), self.filter);
+        List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    }
+}
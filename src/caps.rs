@@ -0,0 +1,178 @@
+use crate::color::ColorMode;
+use std::path::{Path, PathBuf};
+
+/// Magic numbers of compiled terminfo entries, with 16-bit and 32-bit numbers.
+const TERMINFO_MAGIC: (u16, u16) = (0o432, 0o1036);
+/// Position of `colors` among a terminfo entry's numbers.
+const TERMINFO_COLORS: usize = 13;
+
+/// The most colors a terminal can show, ordered from none to full RGB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    None,
+    /// The 16 basic colors
+    Ansi16,
+    /// The xterm 256-color palette
+    Ansi256,
+    /// 24-bit RGB colors
+    Truecolor,
+}
+
+impl ColorSupport {
+    /// What the terminal this process runs in supports, judging by its environment.
+    pub fn detect() -> Self {
+        let support = Self::from_env(|name| std::env::var(name).ok());
+        // Consoles before Windows 10 ignore escape codes, and newer ones need them enabled
+        #[cfg(all(windows, feature = "cli"))]
+        if support > ColorSupport::None && !crossterm::ansi_support::supports_ansi() {
+            return ColorSupport::None;
+        }
+        support
+    }
+
+    /// What a terminal with these environment variables supports. `NO_COLOR` turns color
+    /// off, `COLORTERM` and `TERM` name what the terminal can do, and the terminfo entry of
+    /// `TERM` is read when they do not say.
+    ///
+    /// ```
+    /// use ascii_gen::caps::ColorSupport;
+    ///
+    /// let env = |vars: &'static [(&'static str, &'static str)]| {
+    ///     move |name: &str| vars.iter().find(|(n, _)| *n == name).map(|(_, v)| v.to_string())
+    /// };
+    /// let support = ColorSupport::from_env(env(&[("TERM", "xterm-256color")]));
+    /// assert_eq!(support, ColorSupport::Ansi256);
+    /// let no_color = env(&[("COLORTERM", "truecolor"), ("NO_COLOR", "1")]);
+    /// assert_eq!(ColorSupport::from_env(no_color), ColorSupport::None);
+    /// ```
+    pub fn from_env(get: impl Fn(&str) -> Option<String>) -> Self {
+        // Any value but an empty one, see https://no-color.org
+        if get("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorSupport::None;
+        }
+        // Other values only say there are some colors, not how many
+        if let Some("truecolor" | "24bit") = get("COLORTERM").as_deref() {
+            return ColorSupport::Truecolor;
+        }
+        // Windows Terminal shows every color but sets neither of the above
+        if get("WT_SESSION").is_some() {
+            return ColorSupport::Truecolor;
+        }
+        if let Some("iTerm.app" | "WezTerm" | "vscode" | "ghostty") = get("TERM_PROGRAM").as_deref()
+        {
+            return ColorSupport::Truecolor;
+        }
+
+        let term = get("TERM").unwrap_or_default();
+        match term.as_str() {
+            "" => return default_support(),
+            "dumb" => return ColorSupport::None,
+            _ if term.ends_with("-direct") || term.contains("truecolor") => {
+                return ColorSupport::Truecolor
+            }
+            _ if term.contains("256color") => return ColorSupport::Ansi256,
+            _ => {}
+        }
+
+        let colors = terminfo_dirs(&get)
+            .iter()
+            .find_map(|dir| terminfo_colors(dir, &term));
+        match colors {
+            Some(colors) if colors >= 1 << 24 => ColorSupport::Truecolor,
+            Some(colors) if colors >= 256 => ColorSupport::Ansi256,
+            Some(colors) if colors >= 8 => ColorSupport::Ansi16,
+            Some(_) => ColorSupport::None,
+            // Nearly every terminal still in use knows the basic colors
+            None => ColorSupport::Ansi16,
+        }
+    }
+
+    /// The best color mode this support allows, for `--color auto`.
+    pub fn best(self) -> ColorMode {
+        ColorMode::Truecolor.downgrade(self)
+    }
+
+    /// Whether art in this color mode shows as intended.
+    pub fn allows(self, mode: ColorMode) -> bool {
+        mode.downgrade(self) == mode
+    }
+}
+
+impl ColorMode {
+    /// The closest mode a terminal with this support can show. Half blocks need every color
+    /// to keep the detail of their two pixels, so they fall back to the palettes as well.
+    pub fn downgrade(self, support: ColorSupport) -> Self {
+        match support {
+            ColorSupport::None => ColorMode::None,
+            _ if !self.is_enabled() => ColorMode::None,
+            ColorSupport::Ansi16 => ColorMode::Ansi16,
+            ColorSupport::Ansi256 if self == ColorMode::Ansi16 => ColorMode::Ansi16,
+            ColorSupport::Ansi256 => ColorMode::Ansi256,
+            ColorSupport::Truecolor => self,
+        }
+    }
+}
+
+/// What a terminal without `TERM` supports: the Windows console, and nothing elsewhere.
+fn default_support() -> ColorSupport {
+    if cfg!(windows) {
+        ColorSupport::Truecolor
+    } else {
+        ColorSupport::None
+    }
+}
+
+/// Directories terminfo entries are looked up in, in the order ncurses searches them.
+fn terminfo_dirs(get: &impl Fn(&str) -> Option<String>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = get("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = get("HOME") {
+        dirs.push(Path::new(&home).join(".terminfo"));
+    }
+    if let Some(list) = get("TERMINFO_DIRS") {
+        dirs.extend(
+            list.split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+    dirs.extend(
+        ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+            .iter()
+            .map(PathBuf::from),
+    );
+    dirs
+}
+
+/// The `colors` number of a compiled terminfo entry, stored under its first letter, or the
+/// letter's hex code on macOS.
+fn terminfo_colors(dir: &Path, term: &str) -> Option<u32> {
+    let first = term.chars().next()?;
+    let data = [first.to_string(), format!("{:x}", first as u32)]
+        .iter()
+        .find_map(|subdir| std::fs::read(dir.join(subdir).join(term)).ok())?;
+
+    let short = |at: usize| Some(u16::from_le_bytes([*data.get(at)?, *data.get(at + 1)?]));
+    let magic = short(0)?;
+    let wide = match magic {
+        m if m == TERMINFO_MAGIC.0 => false,
+        m if m == TERMINFO_MAGIC.1 => true,
+        _ => return None,
+    };
+    let (names, booleans, numbers) = (short(2)? as usize, short(4)? as usize, short(6)? as usize);
+    if numbers <= TERMINFO_COLORS {
+        return None;
+    }
+    // Numbers start on an even byte after the header, names and booleans
+    let start = (12 + names + booleans + 1) & !1;
+    let colors = if wide {
+        let at = start + TERMINFO_COLORS * 4;
+        i32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?)
+    } else {
+        i32::from(short(start + TERMINFO_COLORS * 2)? as i16)
+    };
+    // -1 marks a number the entry leaves out
+    u32::try_from(colors).ok()
+}
@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use unicode_width::UnicodeWidthChar;
+
+/// Upper luminance bound (inclusive) of each character of the standard charset.
+const STANDARD_THRESHOLDS: [u8; 9] = [25, 51, 76, 102, 127, 153, 178, 204, 255];
+
+/// Picks the character drawn for a cell, from its luminance and its average color.
+///
+/// `Charset` is the built-in implementation; `Ramp` takes any characters, e.g. katakana.
+pub trait CharMapper {
+    fn glyph(&self, luminance: u8, rgb: [u8; 3]) -> char;
+
+    /// A color replacing the cell's average color, if the mapper picks its own.
+    fn color(&self, _luminance: u8, _rgb: [u8; 3]) -> Option<[u8; 3]> {
+        None
+    }
+}
+
+/// A mapper shared with the threads converting video frames or live edits.
+pub type SharedMapper = Arc<dyn CharMapper + Send + Sync>;
+
+/// A character ramp of its own, ordered from darkest to brightest.
+///
+/// Every character takes one column, so the art stays a grid: fullwidth characters such as
+/// `ヲ` and combining ones are refused, while their halfwidth forms such as `ｦ` are fine.
+///
+/// ```
+/// use ascii_gen::charset::{Ramp, RampError};
+///
+/// assert!(Ramp::new("ｦｧｨｩｪ").is_ok());
+/// assert_eq!(Ramp::new("ヲァ"), Err(RampError::Width('ヲ', 2)));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ramp {
+    chars: Vec<char>,
+}
+
+/// Why `Ramp::new` refused the characters.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RampError {
+    /// No characters at all.
+    Empty,
+    /// A character taking this many columns instead of one.
+    Width(char, usize),
+}
+
+impl std::fmt::Display for RampError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("a charset needs at least one character"),
+            Self::Width(glyph, 0) => write!(
+                f,
+                "{:?} takes no column of its own, as combining and control characters do",
+                glyph
+            ),
+            Self::Width(glyph, width) => {
+                write!(f, "{} takes {} columns, one more than a cell", glyph, width)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RampError {}
+
+impl Ramp {
+    /// Creates a ramp from the characters of `chars`.
+    pub fn new(chars: &str) -> Result<Self, RampError> {
+        let chars: Vec<char> = chars.chars().collect();
+        if chars.is_empty() {
+            return Err(RampError::Empty);
+        }
+        if let Some(&glyph) = chars.iter().find(|&&glyph| glyph_width(glyph) != 1) {
+            return Err(RampError::Width(glyph, glyph_width(glyph)));
+        }
+        Ok(Self { chars })
+    }
+
+    /// Whether every character of the ramp is ASCII.
+    pub fn is_ascii(&self) -> bool {
+        self.chars.iter().all(char::is_ascii)
+    }
+}
+
+impl CharMapper for Ramp {
+    fn glyph(&self, luminance: u8, _rgb: [u8; 3]) -> char {
+        self.chars[luminance as usize * self.chars.len() / 256]
+    }
+}
+
+/// Columns a character takes in a terminal: 2 for fullwidth characters such as CJK, and 0
+/// for combining and control characters, which cannot be drawn in a cell of their own.
+pub fn glyph_width(glyph: char) -> usize {
+    glyph.width().unwrap_or(0)
+}
+
+/// Built-in character ramps, each ordered from darkest to brightest.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Charset {
+    /// The original nine character ramp
+    Standard,
+    /// A short ten character ramp
+    Simple,
+    /// Unicode shade blocks
+    Blocks,
+    /// Paul Bourke's seventy character ramp
+    Detailed,
+}
+
+const STANDARD: &[char] = &['#', '@', '8', '&', 'o', '*', ':', ',', '.'];
+const SIMPLE: &[char] = &['@', '%', '#', '*', '+', '=', '-', ':', '.', ' '];
+const BLOCKS: &[char] = &['█', '▓', '▒', '░', ' '];
+const DETAILED: &[char] = &[
+    '$', '@', 'B', '%', '8', '&', 'W', 'M', '#', '*', 'o', 'a', 'h', 'k', 'b', 'd', 'p', 'q', 'w',
+    'm', 'Z', 'O', '0', 'Q', 'L', 'C', 'J', 'U', 'Y', 'X', 'z', 'c', 'v', 'u', 'n', 'x', 'r', 'j',
+    'f', 't', '/', '\\', '|', '(', ')', '1', '{', '}', '[', ']', '?', '-', '_', '+', '~', '<', '>',
+    'i', '!', 'l', 'I', ';', ':', ',', '"', '^', '`', '\'', '.', ' ',
+];
+
+const ALL: [Charset; 4] = [
+    Charset::Standard,
+    Charset::Simple,
+    Charset::Blocks,
+    Charset::Detailed,
+];
+
+impl Charset {
+    pub fn name(self) -> &'static str {
+        match self {
+            Charset::Standard => "standard",
+            Charset::Simple => "simple",
+            Charset::Blocks => "blocks",
+            Charset::Detailed => "detailed",
+        }
+    }
+
+    /// The characters of the ramp, from darkest to brightest.
+    pub fn chars(self) -> &'static [char] {
+        match self {
+            Charset::Standard => STANDARD,
+            Charset::Simple => SIMPLE,
+            Charset::Blocks => BLOCKS,
+            Charset::Detailed => DETAILED,
+        }
+    }
+
+    /// Whether every character of the ramp is ASCII.
+    pub fn is_ascii(self) -> bool {
+        self.chars().iter().all(char::is_ascii)
+    }
+
+    /// The closest charset drawn in ASCII only, for consoles whose code page lacks the
+    /// others.
+    pub fn ascii(self) -> Self {
+        match self {
+            Charset::Blocks => Charset::Simple,
+            _ => self,
+        }
+    }
+
+    /// Returns the index into `chars` for the given luminance.
+    pub fn level(self, luminance: u8) -> usize {
+        match self {
+            Charset::Standard => STANDARD_THRESHOLDS
+                .iter()
+                .position(|&upper| luminance <= upper)
+                .unwrap_or(STANDARD_THRESHOLDS.len() - 1),
+            _ => luminance as usize * self.chars().len() / 256,
+        }
+    }
+
+    /// Returns the character for the given luminance.
+    pub fn char_for(self, luminance: u8) -> char {
+        self.chars()[self.level(luminance)]
+    }
+
+    /// Returns the inclusive luminance range mapped to the given level.
+    pub fn bounds(self, level: usize) -> (u8, u8) {
+        match self {
+            Charset::Standard => {
+                let lower = match level {
+                    0 => 0,
+                    _ => STANDARD_THRESHOLDS[level - 1] + 1,
+                };
+                (lower, STANDARD_THRESHOLDS[level])
+            }
+            _ => {
+                let len = self.chars().len();
+                let lower = (level * 256).div_ceil(len);
+                let upper = ((level + 1) * 256).div_ceil(len) - 1;
+                (lower as u8, upper.min(255) as u8)
+            }
+        }
+    }
+
+    /// Every built-in charset.
+    pub fn all() -> [Charset; 4] {
+        ALL
+    }
+
+    /// The next built-in charset, wrapping around.
+    pub fn next(self) -> Self {
+        let index = ALL.iter().position(|&c| c == self).unwrap_or(0);
+        ALL[(index + 1) % ALL.len()]
+    }
+
+    /// The previous built-in charset, wrapping around.
+    pub fn previous(self) -> Self {
+        let index = ALL.iter().position(|&c| c == self).unwrap_or(0);
+        ALL[(index + ALL.len() - 1) % ALL.len()]
+    }
+}
+
+impl CharMapper for Charset {
+    fn glyph(&self, luminance: u8, _rgb: [u8; 3]) -> char {
+        self.char_for(luminance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_luminance_falls_within_its_level_bounds() {
+        for charset in Charset::all() {
+            for luminance in 0..=255u8 {
+                let (lower, upper) = charset.bounds(charset.level(luminance));
+                assert!(
+                    (lower..=upper).contains(&luminance),
+                    "{:?}: {} outside {}..={}",
+                    charset,
+                    luminance,
+                    lower,
+                    upper
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_cover_every_luminance_without_gaps() {
+        for charset in Charset::all() {
+            let levels = charset.chars().len();
+            assert_eq!(charset.bounds(0).0, 0);
+            assert_eq!(charset.bounds(levels - 1).1, 255);
+            for level in 1..levels {
+                assert_eq!(
+                    charset.bounds(level - 1).1 as usize + 1,
+                    charset.bounds(level).0 as usize,
+                    "{:?} level {}",
+                    charset,
+                    level
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn standard_levels_follow_its_thresholds() {
+        assert_eq!(Charset::Standard.level(0), 0);
+        assert_eq!(Charset::Standard.level(25), 0);
+        assert_eq!(Charset::Standard.level(26), 1);
+        assert_eq!(Charset::Standard.level(255), STANDARD_THRESHOLDS.len() - 1);
+        assert_eq!(Charset::Standard.bounds(1), (26, 51));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::cli::Args;
+use crate::{compare, config, init_terminal, interrupted};
+use ascii_gen::art::ArtBuffer;
+use ascii_gen::charset::{CharMapper, Ramp, RampError};
+use ascii_gen::converter::{AsciiOptions, ImageConverter};
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::widget::AsciiArtWidget;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use image::{DynamicImage, Rgb, RgbImage};
+use ratatui::{prelude::*, widgets::*};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Rows of the gradient preview.
+const GRADIENT_ROWS: u16 = 2;
+
+/// The characters being edited and where the cursor is among them.
+struct RampEditor {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl RampEditor {
+    fn new(chars: &str) -> Self {
+        let chars: Vec<char> = chars.chars().collect();
+        Self {
+            cursor: chars.len(),
+            chars,
+        }
+    }
+
+    fn ramp(&self) -> Result<Ramp, RampError> {
+        Ramp::new(&self.text())
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// Applies a key press, returning whether the characters changed.
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let len = self.chars.len();
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        match code {
+            // The character under the cursor moves with it
+            KeyCode::Left if shift && self.cursor > 0 && self.cursor < len => {
+                self.chars.swap(self.cursor, self.cursor - 1);
+                self.cursor -= 1;
+                true
+            }
+            KeyCode::Right if shift && self.cursor + 1 < len => {
+                self.chars.swap(self.cursor, self.cursor + 1);
+                self.cursor += 1;
+                true
+            }
+            KeyCode::Left => {
+                self.cursor = self.cursor.saturating_sub(1);
+                false
+            }
+            KeyCode::Right => {
+                self.cursor = (self.cursor + 1).min(len);
+                false
+            }
+            KeyCode::Home => {
+                self.cursor = 0;
+                false
+            }
+            KeyCode::End => {
+                self.cursor = len;
+                false
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+                true
+            }
+            KeyCode::Delete if self.cursor < len => {
+                self.chars.remove(self.cursor);
+                true
+            }
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.chars.reverse();
+                self.cursor = len - self.cursor.min(len);
+                true
+            }
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The characters with the cursor drawn reversed, past the end once it is there.
+    fn line(&self) -> Line<'static> {
+        let cursor = Style::default().add_modifier(Modifier::REVERSED);
+        let mut spans: Vec<Span> = self
+            .chars
+            .iter()
+            .enumerate()
+            .map(|(index, c)| {
+                if index == self.cursor {
+                    Span::styled(c.to_string(), cursor)
+                } else {
+                    Span::raw(c.to_string())
+                }
+            })
+            .collect();
+        if self.cursor == self.chars.len() {
+            spans.push(Span::styled(" ", cursor));
+        }
+        Line::from(spans)
+    }
+}
+
+/// Edits the charset `name`, starting from its saved characters or those of `--charset`, with
+/// a gradient and the image `source` drawn with it as it changes. Ctrl+s saves it to the
+/// config file, returning the file; quitting returns `None`.
+pub fn run(
+    name: &str,
+    source: Option<&ImageConverter>,
+    args: &Args,
+) -> Result<Option<PathBuf>, AsciiGenError> {
+    let config_error = |e: Box<dyn std::error::Error>| AsciiGenError::Config(e.to_string());
+    let saved = config::charsets(args.config.as_deref()).map_err(config_error)?;
+    let mut editor = match saved.get(name) {
+        Some(chars) => RampEditor::new(chars),
+        None => RampEditor::new(&args.convert.charset.chars().iter().collect::<String>()),
+    };
+
+    let sample = sample_image();
+    let source = source.unwrap_or(&sample);
+    let mut preview = ArtBuffer::default();
+    let mut options = AsciiOptions::default();
+    let mut converted_for = None;
+    let mut remap = true;
+    let mut status = String::new();
+
+    let mut terminal = init_terminal(args.tui.record.as_deref())?;
+    let saved_to =
+        loop {
+            if interrupted() {
+                break None;
+            }
+            let ramp = editor.ramp();
+
+            // Sample again only once the preview changes size; new characters are just remapped
+            let area = terminal.size()?;
+            if converted_for != Some(area) {
+                let inner = Block::default()
+                    .borders(Borders::ALL)
+                    .inner(layout(area)[2]);
+                options =
+                    compare::convert_pane(&mut preview, source, args.convert.charset, inner, args)?;
+                converted_for = Some(area);
+                remap = true;
+            }
+            if remap {
+                if let Ok(ramp) = &ramp {
+                    preview.remap(ramp, &options, args.convert.color);
+                }
+                remap = false;
+            }
+
+            terminal.draw(|frame| {
+            let areas = layout(frame.size());
+            let title = format!("{} ({} characters)", name, editor.chars.len());
+            frame.render_widget(
+                Paragraph::new(editor.line())
+                    .block(Block::default().borders(Borders::ALL).title(title)),
+                areas[0],
+            );
+
+            let gradient = Block::default().borders(Borders::ALL).title("Gradient");
+            let inner = gradient.inner(areas[1]);
+            frame.render_widget(
+                Paragraph::new(gradient_lines(ramp.as_ref().ok(), inner.width, inner.height))
+                    .block(gradient),
+                areas[1],
+            );
+
+            let block = Block::default().borders(Borders::ALL).title("Preview");
+            match &ramp {
+                Ok(_) => frame.render_widget(
+                    AsciiArtWidget::new(&preview.art)
+                        .colors(&preview.colors, args.convert.color)
+                        .block(block),
+                    areas[2],
+                ),
+                Err(RampError::Empty) => frame.render_widget(
+                    Paragraph::new("Type the characters, darkest first").block(block),
+                    areas[2],
+                ),
+                Err(e) => frame.render_widget(
+                    Paragraph::new(format!("{}; the art would not line up", e)).block(block),
+                    areas[2],
+                ),
+            }
+
+            let help = if status.is_empty() {
+                " type to insert | shift+arrows move a character | ctrl+r reverses | ctrl+s saves \
+                 | esc quits"
+                    .to_string()
+            } else {
+                format!(" {}", status)
+            };
+            frame.render_widget(
+                Paragraph::new(help).style(Style::default().add_modifier(Modifier::REVERSED)),
+                areas[3],
+            );
+        })?;
+
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            status.clear();
+            match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if let Err(e) = editor.ramp() {
+                        status = format!("Cannot save: {}", e);
+                        continue;
+                    }
+                    match config::save_charset(args.config.as_deref(), name, &editor.text()) {
+                        Ok(path) => break Some(path),
+                        Err(e) => status = format!("Saving failed: {}", e),
+                    }
+                }
+                code => remap = editor.handle_key(code, key.modifiers),
+            }
+        };
+    terminal.restore()?;
+    Ok(saved_to)
+}
+
+/// Rows of the ramp's characters from black on the left to white on the right.
+fn gradient_lines(ramp: Option<&Ramp>, width: u16, height: u16) -> Vec<Line<'static>> {
+    let Some(ramp) = ramp else {
+        return Vec::new();
+    };
+    let span = width.saturating_sub(1).max(1) as u32;
+    let row: String = (0..width as u32)
+        .map(|x| {
+            let tone = (x * 255 / span).min(255) as u8;
+            ramp.glyph(tone, [tone; 3])
+        })
+        .collect();
+    (0..height.min(GRADIENT_ROWS))
+        .map(|_| Line::from(row.clone()))
+        .collect()
+}
+
+/// A lit sphere over a dark-to-light background, covering every tone in smooth steps and
+/// hard edges.
+fn sample_image() -> ImageConverter {
+    let (width, height) = (320, 240);
+    let image = RgbImage::from_fn(width, height, |x, y| {
+        let background = (x * 160 / width) as f32;
+        let (dx, dy) = (x as f32 - 200.0, y as f32 - 120.0);
+        let distance = (dx * dx + dy * dy).sqrt();
+        let tone = if distance < 100.0 {
+            // Lit from the top left
+            let (lx, ly) = (x as f32 - 160.0, y as f32 - 80.0);
+            255.0 - (lx * lx + ly * ly).sqrt() * 1.4
+        } else {
+            background
+        };
+        Rgb([tone.clamp(0.0, 255.0) as u8; 3])
+    });
+    ImageConverter::from_image(DynamicImage::ImageRgb8(image))
+}
+
+/// Splits the screen into the characters, the gradient, the preview and the help line.
+fn layout(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(GRADIENT_ROWS + 2),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(area)
+}
@@ -0,0 +1,1073 @@
+use crate::config::{Defaults, Profile};
+use crate::preset::Preset;
+use ascii_gen::caps::ColorSupport;
+use ascii_gen::charset::{Charset, SharedMapper};
+use ascii_gen::color::ColorMode;
+use ascii_gen::converter::AsciiOptions;
+use ascii_gen::curve::{self, ToneCurve};
+use ascii_gen::deinterlace::Deinterlace;
+use ascii_gen::effects::Effect;
+use ascii_gen::output::OutputFormat;
+use ascii_gen::palette::{self, Palette};
+use ascii_gen::pattern::TestPattern;
+use ascii_gen::plan::Upscale;
+use ascii_gen::qr::QrLevel;
+use ascii_gen::{scenes, stream};
+use clap::{parser::ValueSource, ArgMatches, FromArgMatches, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::io::{self, IsTerminal};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File extensions played as video rather than converted as an image.
+const VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "mov", "mkv", "webm", "avi"];
+/// File extensions played as music, showing their cover art.
+const AUDIO_EXTENSIONS: [&str; 6] = ["mp3", "flac", "ogg", "opus", "m4a", "wav"];
+
+/// Without a subcommand, plays FILE if it is a video or music and prints it otherwise.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, arg_required_else_help = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    files: FileArgs,
+    #[command(flatten)]
+    convert: ConvertArgs,
+    #[command(flatten)]
+    tui: TuiArgs,
+    #[command(flatten)]
+    playback: PlaybackArgs,
+    /// Read defaults and key bindings from this file instead of ~/.config/ascii-gen/config.toml
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    /// Log more details: -v for info, -vv for debug and -vvv for every frame
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Write the log to this file instead of stderr
+    #[arg(long, global = true)]
+    pub log_file: Option<PathBuf>,
+    /// Print the time spent decoding, sampling, mapping and rendering once done
+    #[cfg(feature = "profiling")]
+    #[arg(long, global = true, hide = true)]
+    pub profile_stages: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print images as ASCII art, one after another
+    Image {
+        #[command(flatten)]
+        files: FileArgs,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Tweak images in the live editor, one tab each, then print them
+    Live {
+        #[command(flatten)]
+        files: FileArgs,
+        /// Convert the images again whenever they change on disk, e.g. saved from an editor
+        #[arg(long)]
+        watch: bool,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+    },
+    /// Play a video with its audio
+    Video {
+        #[command(flatten)]
+        files: FileArgs,
+        /// Print the first frame of every scene with its timestamp instead of playing
+        #[arg(long)]
+        scenes: bool,
+        /// How far the cells' average luminance, out of 255, must move between frames to
+        /// count as a cut
+        #[arg(long, value_name = "LEVEL", default_value_t = scenes::DEFAULT_THRESHOLD)]
+        scene_threshold: u8,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Play music, showing its cover art with its title, artist and album
+    Audio {
+        /// The audio file to play
+        #[arg(value_name = "FILE")]
+        file: String,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Play a camera live
+    Cam {
+        /// The capture device to open
+        #[arg(long, default_value = stream::DEFAULT_CAMERA)]
+        device: String,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Play several videos and cameras at once, tiled in one terminal
+    Grid {
+        /// Video files, or cameras as `cam0`, `cam1` and so on or by device path
+        #[arg(value_name = "SOURCE", required = true)]
+        sources: Vec<String>,
+        /// Tiles side by side before starting another row
+        #[arg(long, default_value = "2")]
+        cols: usize,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Convert many images, writing the art of each to a file named after it
+    Batch {
+        #[command(flatten)]
+        files: FileArgs,
+        /// The directory the art files are written to
+        #[arg(long, default_value = ".")]
+        output: PathBuf,
+        /// How many images to convert at once, 0 for one per core
+        #[arg(short, long, default_value = "0")]
+        jobs: usize,
+        /// Convert each image once per size instead, e.g. `80x40,160x80`, decoding it only
+        /// once and writing `photo-80x40.ans`, `photo-160x80.ans` and so on
+        #[arg(long, value_delimiter = ',', value_parser = parse_art_size, conflicts_with = "fit")]
+        sizes: Vec<(u32, u32)>,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Suggest the size, gamma and charset for images from their size and tones
+    Analyze {
+        #[command(flatten)]
+        files: FileArgs,
+        /// Also print the art converted with the suggested settings
+        #[arg(long)]
+        apply: bool,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Measure decode, convert and render times at several widths
+    Bench {
+        /// The video or image measured, a generated 1080p gradient by default
+        #[arg(long, value_name = "FILE")]
+        input: Option<String>,
+        /// The art widths measured
+        #[arg(long, value_delimiter = ',', default_value = "80,160,320,640")]
+        widths: Vec<u32>,
+        /// How many frames to convert at each width
+        #[arg(long, default_value = "60")]
+        frames: usize,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Serve the art of images fetched by URL over HTTP at `GET /convert`, and of videos and
+    /// cameras as frame streams at `/ws` (WebSocket) and `/stream` (server-sent events)
+    #[cfg(feature = "serve")]
+    Serve {
+        /// The port to listen on
+        #[arg(long, default_value = "8080")]
+        port: u16,
+        /// The address to listen on; use 0.0.0.0 to accept other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        host: IpAddr,
+        /// A video the frame streams may play, picked with `?source=` and its file name;
+        /// repeatable
+        #[arg(long = "video", value_name = "FILE")]
+        videos: Vec<String>,
+        /// A capture device the frame streams may play, picked with `?source=cam0` for the
+        /// first; repeatable
+        #[arg(long = "camera", value_name = "DEVICE")]
+        cameras: Vec<String>,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Stream a video as ANSI art over raw TCP to every `nc` or `telnet` client
+    ServeMovie {
+        /// The video to play
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        /// The port to listen on
+        #[arg(long, default_value = "2323")]
+        port: u16,
+        /// The address to listen on; use 0.0.0.0 to accept other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        host: IpAddr,
+        /// How many clients may watch at once
+        #[arg(long, default_value = "32")]
+        max_clients: usize,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Play a procedural effect, optionally behind a logo, without any video
+    Fx {
+        /// The effect to play
+        #[arg(value_enum)]
+        effect: Effect,
+        /// Stop after this long, e.g. 30s or 2m, instead of running until quit
+        #[arg(long, value_parser = parse_duration)]
+        duration: Option<Duration>,
+        /// An image drawn over the effect; transparent or black pixels let it through
+        #[arg(long, value_name = "FILE")]
+        logo: Option<String>,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Print a generated test pattern, to calibrate the charset and gamma for a terminal
+    #[command(name = "testpattern")]
+    TestPattern {
+        /// The pattern to draw
+        #[arg(value_enum)]
+        pattern: TestPattern,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Print a QR code of the text in blocks a phone can scan off the terminal
+    Qr {
+        /// The text or URL to encode
+        text: String,
+        /// How much of the code may be misread while it still scans, larger codes
+        /// surviving more
+        #[arg(long, value_enum, default_value_t = QrLevel::Medium)]
+        level: QrLevel,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Write a logo sized for the terminal as a message of the day, e.g. for `/etc/motd`
+    Motd {
+        /// The logo to convert
+        #[arg(value_name = "FILE")]
+        file: String,
+        /// Columns to fit the logo in, instead of `$COLUMNS` or the terminal's width
+        #[arg(long)]
+        cols: Option<u32>,
+        /// Lines to fit the logo and banner in, instead of `$LINES` or the terminal's height
+        #[arg(long)]
+        lines: Option<u32>,
+        /// Spell the machine's hostname in big letters under the logo
+        #[arg(long, default_value = "false")]
+        hostname: bool,
+        /// Spell this text in big letters under the logo instead of the hostname
+        #[arg(long, value_name = "TEXT")]
+        banner: Option<String>,
+        /// The file to write, such as `/etc/motd`, instead of printing the message
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Compare two images on the same grid, highlighting the cells that changed
+    Diff {
+        /// The image compared against
+        #[arg(value_name = "BEFORE")]
+        before: String,
+        /// The image drawn, with its changes highlighted
+        #[arg(value_name = "AFTER")]
+        after: String,
+        /// How far a color channel may move before its cell counts as changed
+        #[arg(long, default_value = "16")]
+        threshold: u8,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Print a storyboard of frames spread evenly over a video, each under its timestamp
+    Sheet {
+        /// The video to sample
+        #[arg(value_name = "FILE")]
+        file: String,
+        /// Thumbnails side by side, sharing `--width` between them
+        #[arg(long, default_value = "4")]
+        cols: u32,
+        /// Rows of thumbnails, sharing `--height` between them
+        #[arg(long, default_value = "4")]
+        rows: u32,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        playback: PlaybackArgs,
+    },
+    /// Show an image converted with every built-in charset at once, printing the one picked
+    CompareCharsets {
+        /// The image to convert
+        #[arg(value_name = "FILE")]
+        file: String,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+    },
+    /// Design a charset with a live preview and save it to the config file by name
+    CharsetEdit {
+        /// Name to save the charset as; a saved charset of that name is opened for editing
+        #[arg(value_name = "NAME")]
+        name: String,
+        /// Preview the charset on this image instead of a generated gradient
+        #[arg(long, value_name = "FILE")]
+        image: Option<String>,
+        #[command(flatten)]
+        convert: ConvertArgs,
+        #[command(flatten)]
+        tui: TuiArgs,
+    },
+    /// Render ASCII or ANSI art from a text file to an image
+    Rasterize {
+        /// The text file, colored with ANSI escape codes or not
+        #[arg(value_name = "FILE")]
+        file: String,
+        /// The image to write, in the format of its extension
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        /// A TrueType or OpenType font to draw with instead of the built-in bitmap font
+        #[arg(long, value_name = "FILE")]
+        font: Option<PathBuf>,
+        /// Pixels from one line to the next with `--font`
+        #[arg(long, default_value = "16")]
+        font_size: f32,
+        /// Color of characters without one of their own, as `#rrggbb`
+        #[arg(long, value_parser = parse_hex_color, default_value = "#ffffff")]
+        fg: [u8; 3],
+        /// Color behind characters without a background of their own, as `#rrggbb`
+        #[arg(long, value_parser = parse_hex_color, default_value = "#000000")]
+        bg: [u8; 3],
+    },
+    /// Print the dominant colors of an image as swatches with their hex codes
+    Palette {
+        /// The image to take the colors from
+        #[arg(value_name = "FILE")]
+        file: String,
+        /// How many colors to find, at most 64
+        #[arg(long, default_value = "8")]
+        colors: usize,
+        /// Also print the image converted with only these colors
+        #[arg(long, default_value = "false")]
+        apply: bool,
+        #[command(flatten)]
+        convert: ConvertArgs,
+    },
+    /// Print the active key bindings in the config file's format
+    Keys,
+    /// Check the ffmpeg libraries, audio output, terminal colors, Unicode and config file,
+    /// with a fix for every problem found
+    Doctor,
+}
+
+/// The files to open, given as arguments or with `--file`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct FileArgs {
+    /// The image or video files to open
+    #[arg(value_name = "FILE")]
+    paths: Vec<String>,
+    /// Same as giving FILE, repeated for several files
+    #[arg(long = "file", value_name = "FILE")]
+    flagged: Vec<String>,
+}
+
+impl FileArgs {
+    /// Every file given, skipping empty paths.
+    pub fn paths(&self) -> Vec<String> {
+        self.paths
+            .iter()
+            .chain(&self.flagged)
+            .filter(|path| !path.is_empty())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Settings of the conversion itself, shared by every subcommand.
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConvertArgs {
+    /// The width of the ASCII art, from 1 to 2000 characters
+    #[arg(long, default_value = "160", value_parser = parse_dimension)]
+    pub width: u32,
+    /// The height of the ASCII art, from 1 to 2000 characters
+    #[arg(long, default_value = "90", value_parser = parse_dimension)]
+    pub height: u32,
+    /// The gamma of the ASCII art
+    #[arg(long, default_value = "1.0")]
+    pub gamma: f32,
+    /// How to color the ASCII art: none, truecolor, ansi256, ansi16 or halfblock, stepped
+    /// down to what the terminal shows. `auto` picks the most colors the terminal shows,
+    /// `never` is none and `always` is truecolor whatever the terminal seems to support
+    #[arg(long, value_parser = parse_color_mode, default_value = "none")]
+    pub color: ColorMode,
+    /// The characters used for the ASCII art
+    #[arg(long, value_enum, default_value_t = Charset::Standard)]
+    pub charset: Charset,
+    /// Whether or not to invert the brightness of the ASCII art
+    #[arg(long, default_value = "false")]
+    pub invert: bool,
+    /// Draw with ASCII characters only, for consoles whose code page cannot show block
+    /// characters: the blocks charset becomes simple and half blocks become truecolor
+    #[arg(long, default_value = "false")]
+    pub ascii_only: bool,
+    /// For screen readers: print the art as row by row descriptions of blank, dim and bright
+    /// runs, drawn with a three character charset and without colors or escape codes
+    #[arg(long, default_value = "false")]
+    pub accessible: bool,
+    /// Start accessible output with a summary of the art: its size and where it is brightest
+    /// and darkest
+    #[arg(long, default_value = "false", requires = "accessible")]
+    pub alt_text: bool,
+    /// Make the same art from the same input and flags on every run and platform, for art
+    /// checked into repositories: the terminal's colors are not detected, `--fit` is refused
+    /// and only a config file given with `--config` is read
+    #[arg(long, default_value = "false")]
+    pub deterministic: bool,
+    /// Seed of the randomness in palette extraction and the rain and fire effects
+    #[arg(long, requires = "deterministic")]
+    pub seed: Option<u64>,
+    /// Derive the width and height from the terminal size, or from `$COLUMNS` when printing
+    /// into a pipe
+    #[arg(long, default_value = "false")]
+    pub fit: bool,
+    /// Load the settings of a preset saved from the live editor
+    #[arg(long)]
+    pub preset: Option<String>,
+    /// Size, charset, colors and format for where the art goes: readme, twitter, irc,
+    /// terminal, or a profile of the config file's `[profiles]` table
+    #[arg(long)]
+    pub profile: Option<String>,
+    /// The format used when printing or saving the ASCII art
+    #[arg(long, value_enum, default_value_t = OutputFormat::Ansi)]
+    pub format: OutputFormat,
+    /// Tone curve applied before the charset, as 5 comma-separated output levels
+    #[arg(long, value_parser = curve::parse_curve)]
+    pub curve: Option<ToneCurve>,
+    /// Snap the colors to a palette, as comma-separated `#rrggbb` codes
+    #[arg(long, value_parser = palette::parse_palette)]
+    pub palette: Option<Palette>,
+    /// How to fill the cells of art larger than the source
+    #[arg(long, value_enum, default_value_t = Upscale::Nearest)]
+    pub upscale: Upscale,
+    /// Convert only part of the source, given as `x,y,width,height` in pixels
+    #[arg(long, value_parser = parse_crop)]
+    pub crop: Option<(u32, u32, u32, u32)>,
+    /// Pick each cell's character and color with the `map` function of a rhai script
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+    /// Draw with a charset saved with `charset-edit`, by name
+    #[arg(long, value_name = "NAME", conflicts_with = "script")]
+    pub ramp: Option<String>,
+    /// How many threads sample the source, 0 for one per core
+    #[arg(long, default_value = "0")]
+    pub threads: usize,
+    /// Memory a decoded image may take, e.g. 2G; larger JPEGs are decoded at a smaller size
+    #[arg(long, value_name = "SIZE", default_value = "512M", value_parser = parse_size)]
+    pub max_memory: u64,
+    /// Also post the printed art to this Discord or Slack webhook, as code blocks
+    #[arg(long, value_name = "URL")]
+    pub post_to: Option<String>,
+    /// Print art taller than the terminal straight out instead of through `$PAGER`
+    #[arg(long, default_value = "false")]
+    pub no_pager: bool,
+    /// Also report how closely the art resembles the image once drawn, as SSIM and PSNR
+    #[arg(long)]
+    pub score: bool,
+    /// Show the camera, size and capture date of photos, in a panel of the editor or on
+    /// stderr when printing
+    #[arg(long)]
+    pub show_meta: bool,
+}
+
+/// Settings of the terminal UI of the editor and the player.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TuiArgs {
+    /// Record the session to an asciinema v2 cast file
+    #[arg(long)]
+    pub record: Option<String>,
+    /// The format of the art copied to the clipboard with `y`
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub copy_format: OutputFormat,
+}
+
+/// Settings of video and camera playback.
+#[derive(clap::Args, Clone, Debug)]
+pub struct PlaybackArgs {
+    /// Most frames shown per second; the frames in between are skipped before conversion.
+    /// 0 shows every frame of a video and draws effects as fast as they convert
+    #[arg(long, default_value_t = 60.0)]
+    pub frame_rate: f32,
+    /// Blend each frame with the previous N frames to reduce flicker (0 disables)
+    #[arg(long, default_value = "0")]
+    pub temporal_smooth: u32,
+    /// Adjust gamma and contrast to each frame's brightness, easing the changes in over time
+    #[arg(long)]
+    pub auto_expose: bool,
+    /// What to do when the video finishes
+    #[arg(long, value_enum, default_value_t = OnEnd::Exit)]
+    pub on_end: OnEnd,
+    /// Deinterlace video frames with yadif
+    #[arg(long, value_enum, default_value_t = Deinterlace::Auto)]
+    pub deinterlace: Deinterlace,
+    /// Ignore the video's rotation metadata
+    #[arg(long, default_value = "false")]
+    pub no_autorotate: bool,
+    /// Play the video this many times faster, e.g. 30x, without sound and skipping the
+    /// frames in between
+    #[arg(long, value_name = "FACTOR", value_parser = parse_speed)]
+    pub timelapse: Option<f64>,
+    /// Take commands such as `pause` or `seek 30` a line at a time from a unix socket
+    /// created at this path, or from a named pipe already there
+    #[arg(long, value_name = "PATH")]
+    pub control: Option<PathBuf>,
+    /// How long connecting to a video or image given as a URL, or any read from it, may take
+    /// before the stream counts as dropped, e.g. 10s
+    #[arg(long, value_parser = parse_duration, default_value = "10s")]
+    pub timeout: Duration,
+    /// How many times a dropped live stream or download is tried again, waiting twice as
+    /// long each time
+    #[arg(long, default_value = "5")]
+    pub retries: u32,
+}
+
+impl Default for TuiArgs {
+    fn default() -> Self {
+        flag_defaults()
+    }
+}
+
+impl Default for PlaybackArgs {
+    fn default() -> Self {
+        flag_defaults()
+    }
+}
+
+/// A group of flags at their default values, for subcommands that do not take them.
+fn flag_defaults<T: clap::Args + FromArgMatches>() -> T {
+    let command = T::augment_args(clap::Command::new("defaults"));
+    T::from_arg_matches(&command.get_matches_from(["defaults"])).expect("flags have defaults")
+}
+
+/// Behavior once video playback reaches the end of the file.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnEnd {
+    /// Keep the last frame on screen until a key is pressed, then print it
+    Hold,
+    /// Exit immediately
+    Exit,
+    /// Start playing again from the beginning
+    Loop,
+    /// Clear the screen and exit
+    Clear,
+}
+
+/// Every setting of a run, with the flags its subcommand lacks at their defaults.
+#[derive(Clone)]
+pub struct Args {
+    pub convert: ConvertArgs,
+    pub tui: TuiArgs,
+    pub playback: PlaybackArgs,
+    pub config: Option<PathBuf>,
+    /// The compiled `--script`, once loaded.
+    pub mapper: Option<SharedMapper>,
+    /// The colors the terminal shows, once detected.
+    pub color_support: ColorSupport,
+}
+
+impl Cli {
+    /// The chosen subcommand, or the one FILE calls for without one.
+    pub fn into_command(self) -> Command {
+        if let Some(command) = self.command {
+            return command;
+        }
+
+        let first = self.files.paths().first().cloned().unwrap_or_default();
+        if is_audio_file(Path::new(&first)) {
+            Command::Audio {
+                file: first,
+                convert: self.convert,
+                tui: self.tui,
+                playback: self.playback,
+            }
+        } else if is_video_file(Path::new(&first)) {
+            Command::Video {
+                files: self.files,
+                scenes: false,
+                scene_threshold: scenes::DEFAULT_THRESHOLD,
+                convert: self.convert,
+                tui: self.tui,
+                playback: self.playback,
+            }
+        } else {
+            Command::Image {
+                files: self.files,
+                convert: self.convert,
+            }
+        }
+    }
+}
+
+impl Command {
+    /// The files the subcommand opens, if it opens any.
+    pub fn files(&self) -> Option<&FileArgs> {
+        match self {
+            Command::Image { files, .. }
+            | Command::Live { files, .. }
+            | Command::Video { files, .. }
+            | Command::Batch { files, .. }
+            | Command::Analyze { files, .. } => Some(files),
+            Command::Audio { .. }
+            | Command::Cam { .. }
+            | Command::Grid { .. }
+            | Command::Bench { .. }
+            | Command::ServeMovie { .. }
+            | Command::Fx { .. }
+            | Command::TestPattern { .. }
+            | Command::Qr { .. }
+            | Command::Motd { .. }
+            | Command::Diff { .. }
+            | Command::Sheet { .. }
+            | Command::CompareCharsets { .. }
+            | Command::CharsetEdit { .. }
+            | Command::Rasterize { .. }
+            | Command::Palette { .. }
+            | Command::Keys
+            | Command::Doctor => None,
+            #[cfg(feature = "serve")]
+            Command::Serve { .. } => None,
+        }
+    }
+
+    /// Whether the subcommand draws its art in this terminal: in the terminal UI, or by
+    /// printing it when `printing` says stdout is a terminal taking escape codes. Art written
+    /// to files, rendered to images or served to other machines is not.
+    pub fn draws_in_terminal(&self, printing: bool) -> bool {
+        match self {
+            Command::Live { .. }
+            | Command::Audio { .. }
+            | Command::Cam { .. }
+            | Command::Grid { .. }
+            | Command::Fx { .. }
+            | Command::CompareCharsets { .. }
+            | Command::CharsetEdit { .. } => true,
+            Command::Video { scenes, .. } => !scenes || printing,
+            Command::Motd { output, .. } => output.is_none() && printing,
+            Command::Image { .. }
+            | Command::Analyze { .. }
+            | Command::TestPattern { .. }
+            | Command::Qr { .. }
+            | Command::Diff { .. }
+            | Command::Sheet { .. }
+            | Command::Palette { .. } => printing,
+            Command::Batch { .. }
+            | Command::Bench { .. }
+            | Command::ServeMovie { .. }
+            | Command::Rasterize { .. }
+            | Command::Keys
+            | Command::Doctor => false,
+            #[cfg(feature = "serve")]
+            Command::Serve { .. } => false,
+        }
+    }
+
+    /// The settings of the subcommand.
+    pub fn args(&self, config: Option<PathBuf>) -> Args {
+        let (convert, tui, playback) = match self {
+            Command::Image { convert, .. }
+            | Command::Batch { convert, .. }
+            | Command::Analyze { convert, .. }
+            | Command::Bench { convert, .. }
+            | Command::TestPattern { convert, .. }
+            | Command::Qr { convert, .. }
+            | Command::Motd { convert, .. }
+            | Command::Diff { convert, .. }
+            | Command::Palette { convert, .. } => (convert.clone(), None, None),
+            Command::Live { convert, tui, .. }
+            | Command::CompareCharsets { convert, tui, .. }
+            | Command::CharsetEdit { convert, tui, .. } => {
+                (convert.clone(), Some(tui.clone()), None)
+            }
+            Command::Video {
+                convert,
+                tui,
+                playback,
+                ..
+            }
+            | Command::Audio {
+                convert,
+                tui,
+                playback,
+                ..
+            }
+            | Command::Cam {
+                convert,
+                tui,
+                playback,
+                ..
+            }
+            | Command::Grid {
+                convert,
+                tui,
+                playback,
+                ..
+            }
+            | Command::Fx {
+                convert,
+                tui,
+                playback,
+                ..
+            } => (convert.clone(), Some(tui.clone()), Some(playback.clone())),
+            #[cfg(feature = "serve")]
+            Command::Serve {
+                convert, playback, ..
+            } => (convert.clone(), None, Some(playback.clone())),
+            Command::ServeMovie {
+                convert, playback, ..
+            }
+            | Command::Sheet {
+                convert, playback, ..
+            } => (convert.clone(), None, Some(playback.clone())),
+            Command::Rasterize { .. } | Command::Keys | Command::Doctor => {
+                (flag_defaults(), None, None)
+            }
+        };
+
+        Args {
+            convert,
+            tui: tui.unwrap_or_default(),
+            playback: playback.unwrap_or_default(),
+            config,
+            mapper: None,
+            color_support: ColorSupport::Truecolor,
+        }
+    }
+}
+
+impl Args {
+    /// Takes every setting not given on the command line from the config file's defaults.
+    pub fn apply_defaults(&mut self, defaults: &Defaults, matches: &ArgMatches) {
+        let unset = |id: &str| is_unset(matches, id);
+        let options = &defaults.options;
+        let convert = &mut self.convert;
+
+        if unset("width") {
+            convert.width = options.width();
+        }
+        if unset("height") {
+            convert.height = options.height();
+        }
+        if unset("gamma") {
+            convert.gamma = options.gamma();
+        }
+        if unset("charset") {
+            convert.charset = options.charset();
+        }
+        if unset("invert") {
+            convert.invert = options.invert();
+        }
+        if unset("crop") {
+            convert.crop = options.crop();
+        }
+        if unset("curve") {
+            convert.curve = Some(options.curve().clone());
+        }
+        if unset("palette") {
+            convert.palette = options.palette().cloned();
+        }
+        if unset("upscale") {
+            convert.upscale = options.upscale();
+        }
+        if let Some(color) = defaults.color.filter(|_| unset("color")) {
+            convert.color = color;
+        }
+        if let Some(format) = defaults.format.filter(|_| unset("format")) {
+            convert.format = format;
+        }
+        if let Some(fit) = defaults.fit.filter(|_| unset("fit")) {
+            convert.fit = fit;
+        }
+        if let Some(ascii_only) = defaults.ascii_only.filter(|_| unset("ascii_only")) {
+            convert.ascii_only = ascii_only;
+        }
+        if let Some(accessible) = defaults.accessible.filter(|_| unset("accessible")) {
+            convert.accessible = accessible;
+        }
+        if let Some(profile) = defaults.profile.clone().filter(|_| unset("profile")) {
+            convert.profile = Some(profile);
+        }
+        if let Some(copy_format) = defaults.copy_format.filter(|_| unset("copy_format")) {
+            self.tui.copy_format = copy_format;
+        }
+        if let Some(deinterlace) = defaults.deinterlace.filter(|_| unset("deinterlace")) {
+            self.playback.deinterlace = deinterlace;
+        }
+        if let Some(on_end) = defaults.on_end.filter(|_| unset("on_end")) {
+            self.playback.on_end = on_end;
+        }
+        if let Some(strength) = defaults
+            .temporal_smooth
+            .filter(|_| unset("temporal_smooth"))
+        {
+            self.playback.temporal_smooth = strength;
+        }
+        if let Some(auto_expose) = defaults.auto_expose.filter(|_| unset("auto_expose")) {
+            self.playback.auto_expose = auto_expose;
+        }
+    }
+
+    /// Takes every setting the profile has and the command line does not give from the
+    /// profile, over the config file's defaults.
+    pub fn apply_profile(&mut self, profile: &Profile, matches: &ArgMatches) {
+        let unset = |id: &str| is_unset(matches, id);
+        let convert = &mut self.convert;
+
+        if let Some(width) = profile.width.filter(|_| unset("width")) {
+            convert.width = width;
+        }
+        if let Some(height) = profile.height.filter(|_| unset("height")) {
+            convert.height = height;
+        }
+        if let Some(charset) = profile.charset.filter(|_| unset("charset")) {
+            convert.charset = charset;
+        }
+        if let Some(color) = profile.color.filter(|_| unset("color")) {
+            convert.color = color;
+        }
+        if let Some(format) = profile.format.filter(|_| unset("format")) {
+            convert.format = format;
+        }
+        if let Some(ascii_only) = profile.ascii_only.filter(|_| unset("ascii_only")) {
+            convert.ascii_only = ascii_only;
+        }
+        if let Some(fit) = profile.fit.filter(|_| unset("fit")) {
+            convert.fit = fit;
+        }
+    }
+
+    /// Overrides the conversion settings with a saved preset.
+    pub fn apply_preset(&mut self, preset: &Preset) {
+        let convert = &mut self.convert;
+        convert.width = preset.width;
+        convert.height = preset.height;
+        convert.gamma = preset.gamma;
+        convert.charset = preset.charset;
+        convert.invert = preset.invert;
+        convert.color = preset.color;
+        convert.curve = Some(preset.curve.clone());
+    }
+}
+
+/// Whether a flag was left off the command line. Flags the subcommand does not take count
+/// as unset.
+pub fn is_unset(matches: &ArgMatches, id: &str) -> bool {
+    matches.ids().all(|given| given.as_str() != id)
+        || matches.value_source(id) != Some(ValueSource::CommandLine)
+}
+
+/// Parses a crop rectangle given as `x,y,width,height`.
+fn parse_crop(text: &str) -> Result<(u32, u32, u32, u32), String> {
+    let values = text
+        .split(',')
+        .map(|value| value.trim().parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    match values[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok((x, y, width, height)),
+        _ => Err("expected x,y,width,height with a non-zero size".to_string()),
+    }
+}
+
+/// Parses a byte count with an optional K, M or G suffix.
+fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let (number, unit) = match text.char_indices().find(|(_, c)| c.is_ascii_alphabetic()) {
+        Some((index, _)) => text.split_at(index),
+        None => (text, ""),
+    };
+    let scale: u64 = match unit.to_ascii_uppercase().trim_end_matches(['B', 'I']) {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => return Err(format!("unknown unit {:?}, expected K, M or G", unit)),
+    };
+    let number: u64 = number
+        .trim()
+        .parse()
+        .map_err(|e: std::num::ParseIntError| e.to_string())?;
+    number
+        .checked_mul(scale)
+        .ok_or_else(|| "size is too large".to_string())
+}
+
+/// Parses a duration given in seconds, with an optional ms, s, m or h suffix.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let (number, unit) = match text.char_indices().find(|(_, c)| c.is_ascii_alphabetic()) {
+        Some((index, _)) => text.split_at(index),
+        None => (text, ""),
+    };
+    let scale = match unit.to_ascii_lowercase().as_str() {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("unknown unit {:?}, expected ms, s, m or h", unit)),
+    };
+    let number: f64 = number
+        .trim()
+        .parse()
+        .map_err(|e: std::num::ParseFloatError| e.to_string())?;
+    Duration::try_from_secs_f64(number * scale).map_err(|e| e.to_string())
+}
+
+/// Parses a width or height of the art, in characters.
+fn parse_dimension(text: &str) -> Result<u32, String> {
+    let size: u32 = text
+        .trim()
+        .parse()
+        .map_err(|e: std::num::ParseIntError| e.to_string())?;
+    let (min, max) = AsciiOptions::SIZE_BOUNDS;
+    if !(min..=max).contains(&size) {
+        return Err(format!(
+            "{} is not from {} to {} characters",
+            size, min, max
+        ));
+    }
+    Ok(size)
+}
+
+/// Parses the size of the art as `WIDTHxHEIGHT` in characters, e.g. `80x40`.
+fn parse_art_size(text: &str) -> Result<(u32, u32), String> {
+    let (width, height) = text
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("{} is not WIDTHxHEIGHT", text.trim()))?;
+    Ok((parse_dimension(width)?, parse_dimension(height)?))
+}
+
+/// Parses a speed-up factor such as `30x` or `2.5`.
+fn parse_speed(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    let number = text.strip_suffix(['x', 'X']).unwrap_or(text);
+    let speed: f64 = number
+        .trim()
+        .parse()
+        .map_err(|e: std::num::ParseFloatError| e.to_string())?;
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(format!("{} is not a positive factor", text));
+    }
+    Ok(speed)
+}
+
+/// Parses a color mode, or `never`, `always` and `auto`. Art that is not shown in a terminal
+/// gets no colors from `auto`.
+fn parse_color_mode(text: &str) -> Result<ColorMode, String> {
+    match text.to_lowercase().as_str() {
+        "never" => Ok(ColorMode::None),
+        "always" => Ok(ColorMode::Truecolor),
+        "auto" if io::stdout().is_terminal() => Ok(ColorSupport::detect().best()),
+        "auto" => Ok(ColorMode::None),
+        _ => ColorMode::from_str(text, true).map_err(|_| {
+            format!(
+                "{} is not none, truecolor, ansi256, ansi16, halfblock, never, always or auto",
+                text
+            )
+        }),
+    }
+}
+
+/// Parses a color given as `#rrggbb`, the `#` being optional.
+fn parse_hex_color(text: &str) -> Result<[u8; 3], String> {
+    palette::parse_hex(text).ok_or_else(|| format!("{} is not a #rrggbb color", text))
+}
+
+/// Whether the file should be played as music, judging by its extension.
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| AUDIO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Whether the file should be played as a video, judging by its extension.
+pub fn is_video_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_crops() {
+        assert_eq!(parse_crop("1,2,30,40"), Ok((1, 2, 30, 40)));
+        assert_eq!(parse_crop(" 0, 0, 5 ,5"), Ok((0, 0, 5, 5)));
+        for text in [
+            "1,2,3",
+            "1,2,3,4,5",
+            "0,0,0,10",
+            "0,0,10,0",
+            "a,b,c,d",
+            "-1,0,1,1",
+            "",
+        ] {
+            assert!(parse_crop(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn parses_sizes_with_units() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("4K"), Ok(4 << 10));
+        assert_eq!(parse_size("4kb"), Ok(4 << 10));
+        assert_eq!(parse_size("256 MiB"), Ok(256 << 20));
+        assert_eq!(parse_size("2G"), Ok(2 << 30));
+        for text in ["", "G", "4T", "1.5M", "-1K"] {
+            assert!(parse_size(text).is_err(), "{:?}", text);
+        }
+        assert!(parse_size(&format!("{}G", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn parses_durations_with_units() {
+        assert_eq!(parse_duration("10"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1H"), Ok(Duration::from_secs(3600)));
+        for text in ["", "s", "10d", "-1", "NaN"] {
+            assert!(parse_duration(text).is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn parses_art_sizes() {
+        assert_eq!(parse_art_size("80x40"), Ok((80, 40)));
+        assert_eq!(parse_art_size(" 120 X 30 "), Ok((120, 30)));
+        let (_, max) = AsciiOptions::SIZE_BOUNDS;
+        for text in [
+            "80",
+            "80x",
+            "x40",
+            "0x40",
+            "80x0",
+            format!("{}x10", max + 1).as_str(),
+        ] {
+            assert!(parse_art_size(text).is_err(), "{:?}", text);
+        }
+    }
+}
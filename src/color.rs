@@ -0,0 +1,218 @@
+#[cfg(feature = "tui")]
+use ratatui::style::{Color, Style};
+use serde::{Deserialize, Serialize};
+
+/// Character used by the half-block renderer, colored with the top pixel as
+/// foreground and the bottom pixel as background.
+pub const HALF_BLOCK: char = '▀';
+
+/// How much a channel must change before a cell's color is updated between frames.
+const DIFF_THRESHOLD: u8 = 6;
+
+/// How the ASCII art is colored.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Plain characters without color
+    None,
+    /// 24-bit RGB colors
+    Truecolor,
+    /// The xterm 256-color palette
+    Ansi256,
+    /// The 16 basic terminal colors
+    Ansi16,
+    /// Colored half blocks, two pixels per cell
+    Halfblock,
+}
+
+/// Colors of a single cell of the art.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct CellColor {
+    pub fg: [u8; 3],
+    pub bg: Option<[u8; 3]>,
+}
+
+const ALL: [ColorMode; 5] = [
+    ColorMode::None,
+    ColorMode::Truecolor,
+    ColorMode::Ansi256,
+    ColorMode::Ansi16,
+    ColorMode::Halfblock,
+];
+
+impl ColorMode {
+    pub fn is_enabled(self) -> bool {
+        self != ColorMode::None
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::None => "none",
+            ColorMode::Truecolor => "truecolor",
+            ColorMode::Ansi256 => "ansi256",
+            ColorMode::Ansi16 => "ansi16",
+            ColorMode::Halfblock => "halfblock",
+        }
+    }
+
+    /// The next color mode, wrapping around.
+    pub fn next(self) -> Self {
+        let index = ALL.iter().position(|&m| m == self).unwrap_or(0);
+        ALL[(index + 1) % ALL.len()]
+    }
+
+    /// The previous color mode, wrapping around.
+    pub fn previous(self) -> Self {
+        let index = ALL.iter().position(|&m| m == self).unwrap_or(0);
+        ALL[(index + ALL.len() - 1) % ALL.len()]
+    }
+
+    /// Converts an RGB value into a terminal color for this mode.
+    #[cfg(feature = "tui")]
+    pub fn to_color(self, rgb: [u8; 3]) -> Color {
+        match self {
+            ColorMode::Ansi256 => Color::Indexed(rgb_to_ansi256(rgb)),
+            ColorMode::Ansi16 => Color::Indexed(rgb_to_ansi16(rgb)),
+            _ => Color::Rgb(rgb[0], rgb[1], rgb[2]),
+        }
+    }
+
+    /// Builds the style for a cell.
+    #[cfg(feature = "tui")]
+    pub fn style(self, cell: &CellColor) -> Style {
+        let style = Style::default().fg(self.to_color(cell.fg));
+        match cell.bg {
+            Some(bg) => style.bg(self.to_color(bg)),
+            None => style,
+        }
+    }
+}
+
+/// Maps an RGB value to the closest color of the xterm 256-color palette.
+pub fn rgb_to_ansi256(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb;
+
+    // Use the grayscale ramp when the channels are close together
+    if r.abs_diff(g) < 8 && g.abs_diff(b) < 8 {
+        let gray = (r as u16 + g as u16 + b as u16) / 3;
+        return match gray {
+            0..=7 => 16,
+            249..=255 => 231,
+            _ => 232 + ((gray - 8) * 24 / 241) as u8,
+        };
+    }
+
+    let level = |channel: u8| ((channel as u16 * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// Maps an RGB value to the closest of the 16 basic terminal colors.
+pub fn rgb_to_ansi16(rgb: [u8; 3]) -> u8 {
+    let distance = |other: [u8; 3]| -> u32 {
+        rgb.iter()
+            .zip(other)
+            .map(|(&a, b)| u32::from(a.abs_diff(b)).pow(2))
+            .sum()
+    };
+    (0..16)
+        .min_by_key(|&index| distance(BASIC[index as usize]))
+        .unwrap_or(0)
+}
+
+/// The RGB value of a color of the xterm 256-color palette.
+pub fn ansi256_to_rgb(index: u8) -> [u8; 3] {
+    let level = |step: u8| if step == 0 { 0 } else { 55 + step * 40 };
+
+    match index {
+        0..=15 => BASIC[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            [level(cube / 36), level(cube / 6 % 6), level(cube % 6)]
+        }
+        _ => [8 + (index - 232) * 10; 3],
+    }
+}
+
+/// The 16 basic colors as xterm shows them, the first 16 of its 256-color palette.
+const BASIC: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// Keeps the previous frame's colors for cells that barely changed, so the
+/// terminal diff only redraws cells that visibly differ.
+pub fn stabilize(previous: &[CellColor], current: &mut [CellColor]) {
+    if previous.len() != current.len() {
+        return;
+    }
+
+    let close = |a: [u8; 3], b: [u8; 3]| {
+        a.iter()
+            .zip(b.iter())
+            .all(|(x, y)| x.abs_diff(*y) <= DIFF_THRESHOLD)
+    };
+
+    for (old, new) in previous.iter().zip(current.iter_mut()) {
+        let bg_close = match (old.bg, new.bg) {
+            (Some(a), Some(b)) => close(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        if close(old.fg, new.fg) && bg_close {
+            *new = *old;
+        }
+    }
+}
+
+/// Renders the art with ANSI escape codes for the given color mode.
+pub fn to_ansi(art: &str, colors: &[CellColor], mode: ColorMode) -> String {
+    if !mode.is_enabled() || colors.is_empty() {
+        return art.to_string();
+    }
+
+    let escape = |rgb: [u8; 3], layer: u8| match mode {
+        ColorMode::Ansi256 => format!("\x1b[{};5;{}m", layer, rgb_to_ansi256(rgb)),
+        // 30-37 and 90-97 color the text, 40-47 and 100-107 the background
+        ColorMode::Ansi16 => {
+            let index = rgb_to_ansi16(rgb);
+            let base = if index < 8 { layer - 8 } else { layer + 52 };
+            format!("\x1b[{}m", base + index % 8)
+        }
+        _ => format!("\x1b[{};2;{};{};{}m", layer, rgb[0], rgb[1], rgb[2]),
+    };
+
+    let mut output = String::with_capacity(art.len() * 20);
+    let mut cells = colors.iter();
+
+    for c in art.chars() {
+        if c == '\n' {
+            output.push_str("\x1b[0m\n");
+            continue;
+        }
+        if let Some(cell) = cells.next() {
+            output.push_str(&escape(cell.fg, 38));
+            if let Some(bg) = cell.bg {
+                output.push_str(&escape(bg, 48));
+            }
+        }
+        output.push(c);
+    }
+
+    output.push_str("\x1b[0m");
+    output
+}
@@ -0,0 +1,159 @@
+use crate::cli::Args;
+use crate::{init_terminal, interrupted, App};
+use ascii_gen::art::ArtBuffer;
+use ascii_gen::charset::Charset;
+use ascii_gen::converter::{self, AsciiOptions, ImageConverter, PixelSource};
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::progress::ConversionHandle;
+use ascii_gen::temporal::TemporalFilter;
+use ascii_gen::widget::AsciiArtWidget;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{prelude::*, widgets::*};
+use std::time::Duration;
+
+/// Panes side by side; the charsets fill two rows of them.
+const COLUMNS: usize = 2;
+
+/// Shows the image converted with every built-in charset at once. Returns the art of the one
+/// picked with Enter, at the size and in the format given on the command line, or `None`
+/// when quit.
+pub fn run(source: &ImageConverter, args: &Args) -> Result<Option<String>, AsciiGenError> {
+    let charsets = Charset::all();
+    let mut selected = charsets
+        .iter()
+        .position(|&charset| charset == args.convert.charset)
+        .unwrap_or(0);
+    let mut panes: Vec<ArtBuffer> = charsets.iter().map(|_| ArtBuffer::default()).collect();
+    let mut converted_for = None;
+
+    let mut terminal = init_terminal(args.tui.record.as_deref())?;
+    let picked = loop {
+        if interrupted() {
+            break None;
+        }
+        // Convert again only once the panes change size
+        let area = terminal.size()?;
+        if converted_for != Some(area) {
+            let areas = layout(area).0;
+            for ((pane, &charset), &area) in panes.iter_mut().zip(&charsets).zip(&areas) {
+                let inner = Block::default().borders(Borders::ALL).inner(area);
+                convert_pane(pane, source, charset, inner, args)?;
+            }
+            converted_for = Some(area);
+        }
+
+        terminal.draw(|frame| {
+            let (areas, help) = layout(frame.size());
+            for (index, (pane, charset)) in panes.iter().zip(&charsets).enumerate() {
+                let mut block = Block::default().borders(Borders::ALL).title(format!(
+                    "{} {}",
+                    index + 1,
+                    charset.name()
+                ));
+                if index == selected {
+                    block = block.border_style(Style::default().fg(Color::Yellow));
+                }
+                frame.render_widget(
+                    AsciiArtWidget::new(&pane.art)
+                        .colors(&pane.colors, args.convert.color)
+                        .block(block),
+                    areas[index],
+                );
+            }
+            frame.render_widget(
+                Paragraph::new(" arrows pick a charset | enter prints it | q quits")
+                    .style(Style::default().add_modifier(Modifier::REVERSED)),
+                help,
+            );
+        })?;
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let count = charsets.len();
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => {
+                selected = (selected + count - 1) % count
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => selected = (selected + 1) % count,
+            KeyCode::Up | KeyCode::Char('k') => selected = (selected + count - COLUMNS) % count,
+            KeyCode::Down | KeyCode::Char('j') => selected = (selected + COLUMNS) % count,
+            KeyCode::Char(digit @ '1'..='9') if (digit as usize - '1' as usize) < count => {
+                selected = digit as usize - '1' as usize
+            }
+            KeyCode::Enter => break Some(charsets[selected]),
+            KeyCode::Esc | KeyCode::Char('q') => break None,
+            _ => {}
+        }
+    };
+    terminal.restore()?;
+
+    let Some(charset) = picked else {
+        return Ok(None);
+    };
+    let mut args = args.clone();
+    args.convert.charset = charset;
+    App::convert_image(source, &args, &ConversionHandle::default()).map(Some)
+}
+
+/// Converts the image as large as it fits in `area` with its aspect ratio kept, returning
+/// the options it was converted with.
+pub fn convert_pane(
+    pane: &mut ArtBuffer,
+    source: &ImageConverter,
+    charset: Charset,
+    area: Rect,
+    args: &Args,
+) -> Result<AsciiOptions, AsciiGenError> {
+    let mut args = args.clone();
+    args.convert.charset = charset;
+    let options = App::new(&args).options();
+    let dimensions = converter::crop(source, &options)?.dimensions();
+
+    let (width, height) = (area.width.max(1) as u32, area.height.max(1) as u32);
+    args.convert.width = width;
+    args.convert.height = converter::height_for_width(dimensions, width);
+    if args.convert.height > height {
+        args.convert.height = height;
+        args.convert.width = converter::width_for_height(dimensions, height).min(width);
+    }
+
+    let options = App::new(&args).options();
+    pane.convert(
+        &converter::crop(source, &options)?,
+        &options,
+        args.convert.color,
+        &mut TemporalFilter::new(0),
+    );
+    Ok(options)
+}
+
+/// Splits the screen into a pane per charset and the help line below them.
+fn layout(area: Rect) -> (Vec<Rect>, Rect) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    let mut panes = Vec::with_capacity(COLUMNS * 2);
+    for &row in &rows[..2] {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(row);
+        panes.extend(columns.iter().copied());
+    }
+    (panes, rows[2])
+}
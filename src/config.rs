@@ -0,0 +1,211 @@
+use crate::cli::OnEnd;
+use ascii_gen::charset::{Charset, Ramp};
+use ascii_gen::color::ColorMode;
+use ascii_gen::converter::AsciiOptions;
+use ascii_gen::deinterlace::Deinterlace;
+use ascii_gen::output::OutputFormat;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Location of the config file, next to the presets.
+pub fn path() -> Option<PathBuf> {
+    Some(crate::preset::config_dir()?.join("config.toml"))
+}
+
+/// Reads the config file given with `--config`, or the default one if it exists.
+pub fn read(config: Option<&Path>) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let path = match config {
+        Some(path) => path.to_path_buf(),
+        None => match path() {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && config.is_none() => Ok(None),
+        Err(e) => Err(format!("{}: {}", path.display(), e).into()),
+    }
+}
+
+/// The `[defaults]` table of the config file, used for every setting not given on the
+/// command line.
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Defaults {
+    #[serde(flatten)]
+    pub options: AsciiOptions,
+    pub color: Option<ColorMode>,
+    pub format: Option<OutputFormat>,
+    pub copy_format: Option<OutputFormat>,
+    pub deinterlace: Option<Deinterlace>,
+    pub on_end: Option<OnEnd>,
+    pub temporal_smooth: Option<u32>,
+    pub auto_expose: Option<bool>,
+    pub fit: Option<bool>,
+    pub ascii_only: Option<bool>,
+    pub accessible: Option<bool>,
+    pub profile: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct ConfigFile {
+    #[serde(default)]
+    defaults: Defaults,
+    #[serde(default)]
+    charsets: BTreeMap<String, String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Defaults {
+    /// Loads the defaults, which are empty without a config file.
+    pub fn load(config: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        match read(config)? {
+            Some(contents) => {
+                let defaults = toml::from_str::<ConfigFile>(&contents)?.defaults;
+                // Options read from a file skip the clamping of the command line
+                defaults.options.validate()?;
+                Ok(defaults)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Settings for where the art ends up, picked with `--profile`. Settings a profile leaves out
+/// keep their defaults, and flags on the command line override the profile.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Profile {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub charset: Option<Charset>,
+    pub color: Option<ColorMode>,
+    pub format: Option<OutputFormat>,
+    pub ascii_only: Option<bool>,
+    pub fit: Option<bool>,
+}
+
+impl Profile {
+    /// Names of the built-in profiles, which `[profiles]` in the config file can redefine.
+    pub const BUILT_IN: [&'static str; 4] = ["readme", "twitter", "irc", "terminal"];
+
+    fn built_in(name: &str) -> Option<Self> {
+        let plain = |width, height, charset| Self {
+            width: Some(width),
+            height: Some(height),
+            charset: Some(charset),
+            color: Some(ColorMode::None),
+            format: Some(OutputFormat::Text),
+            ascii_only: Some(true),
+            fit: Some(false),
+        };
+        match name {
+            // A code block on GitHub shows 80 columns without scrolling
+            "readme" => Some(plain(80, 40, Charset::Standard)),
+            // Posts hold 280 characters with the newlines, and anything past Latin counts twice
+            "twitter" => Some(plain(24, 10, Charset::Simple)),
+            // Every row is a message of its own, so fewer of them keep clear of flood limits
+            "irc" => Some(plain(60, 20, Charset::Standard)),
+            "terminal" => Some(Self {
+                charset: Some(Charset::Blocks),
+                color: Some(ColorMode::Truecolor),
+                format: Some(OutputFormat::Ansi),
+                fit: Some(true),
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The profile `name`, from the `[profiles]` table of the config file or else built in.
+pub fn profile(config: Option<&Path>, name: &str) -> Result<Profile, Box<dyn std::error::Error>> {
+    let mut profiles = match read(config)? {
+        Some(contents) => toml::from_str::<ConfigFile>(&contents)?.profiles,
+        None => BTreeMap::new(),
+    };
+    let profile = match profiles.remove(name) {
+        Some(profile) => profile,
+        None => built_in_profile(name)?,
+    };
+    // Sizes read from a file skip the checks of the command line
+    let (min, max) = AsciiOptions::SIZE_BOUNDS;
+    for size in [profile.width, profile.height].into_iter().flatten() {
+        if !(min..=max).contains(&size) {
+            return Err(format!(
+                "profile {}: {} is not from {} to {} characters",
+                name, size, min, max
+            )
+            .into());
+        }
+    }
+    Ok(profile)
+}
+
+/// The built-in profile `name`, ignoring any config file.
+pub fn built_in_profile(name: &str) -> Result<Profile, Box<dyn std::error::Error>> {
+    Profile::built_in(name).ok_or_else(|| {
+        format!(
+            "unknown profile: {}, expected {} or one of [profiles] in the config file",
+            name,
+            Profile::BUILT_IN.join(", ")
+        )
+        .into()
+    })
+}
+
+/// The `[charsets]` table of the config file: charsets saved with `charset-edit`, each a
+/// string of characters from darkest to brightest.
+pub fn charsets(
+    config: Option<&Path>,
+) -> Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    match read(config)? {
+        Some(contents) => Ok(toml::from_str::<ConfigFile>(&contents)?.charsets),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// The saved charset `name`, for `--ramp`.
+pub fn ramp(config: Option<&Path>, name: &str) -> Result<Ramp, Box<dyn std::error::Error>> {
+    let charsets = charsets(config)?;
+    let chars = charsets
+        .get(name)
+        .ok_or_else(|| format!("unknown charset: {}", name))?;
+    Ramp::new(chars).map_err(|e| format!("charset {}: {}", name, e).into())
+}
+
+/// Saves `chars` as the charset `name` in the `[charsets]` table, keeping the rest of the
+/// file as written, comments included. Returns the file saved to.
+pub fn save_charset(
+    config: Option<&Path>,
+    name: &str,
+    chars: &str,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = match config {
+        Some(path) => path.to_path_buf(),
+        None => path().ok_or("could not determine the config directory")?,
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("{}: {}", path.display(), e).into()),
+    };
+
+    let mut document: toml_edit::DocumentMut = contents.parse()?;
+    let charsets = document
+        .entry("charsets")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| format!("{}: charsets is not a table", path.display()))?;
+    charsets.insert(name, toml_edit::value(chars));
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, document.to_string())?;
+    Ok(path)
+}
@@ -0,0 +1,273 @@
+use ascii_gen::error::AsciiGenError;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Slowest and fastest speed `speed` sets, as times faster than recorded.
+pub const SPEED_BOUNDS: (f64, f64) = (0.1, 100.0);
+
+/// A command sent to the player with `--control`, one per line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlCommand {
+    Pause,
+    Play,
+    TogglePause,
+    /// Jump to a position in seconds, or by that many seconds when `relative`.
+    Seek {
+        seconds: f64,
+        relative: bool,
+    },
+    /// Play this many times faster than recorded.
+    Speed(f64),
+    Quit,
+}
+
+impl FromStr for ControlCommand {
+    type Err = String;
+
+    /// Parses `pause`, `play`, `toggle`, `seek 30`, `seek +10`, `seek -10`, `speed 1.5` or
+    /// `quit`.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default().to_lowercase();
+        let argument = words.next();
+        let number = |name: &str| {
+            argument
+                .ok_or_else(|| format!("{} needs a number", name))?
+                .parse::<f64>()
+                .map_err(|e| format!("invalid {}: {}", name, e))
+                .and_then(|value| {
+                    if value.is_finite() {
+                        Ok(value)
+                    } else {
+                        Err(format!("invalid {}: {}", name, value))
+                    }
+                })
+        };
+
+        match command.as_str() {
+            "pause" => Ok(ControlCommand::Pause),
+            "play" | "resume" => Ok(ControlCommand::Play),
+            "toggle" => Ok(ControlCommand::TogglePause),
+            "seek" => Ok(ControlCommand::Seek {
+                seconds: number("seek")?,
+                relative: argument.is_some_and(|text| text.starts_with(['+', '-'])),
+            }),
+            "speed" => match number("speed")? {
+                speed if speed > 0.0 => Ok(ControlCommand::Speed(speed)),
+                speed => Err(format!("invalid speed: {}", speed)),
+            },
+            "quit" | "exit" => Ok(ControlCommand::Quit),
+            "" => Err("empty command".to_string()),
+            other => Err(format!("unknown command: {}", other)),
+        }
+    }
+}
+
+/// Seeks and speed changes for the audio thread to follow, set by the video thread, and
+/// the position of the audio for the video thread to compare its frames with.
+#[derive(Debug)]
+pub struct Transport {
+    /// Position to jump to, in seconds as recorded.
+    pub seek: Option<f64>,
+    pub speed: f64,
+    /// Where the audio is playing, in seconds as recorded, set by the audio thread while
+    /// it plays.
+    pub audio: Option<f64>,
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self {
+            seek: None,
+            speed: 1.0,
+            audio: None,
+        }
+    }
+}
+
+/// Receives commands for the player from other processes, through a unix socket created at
+/// the path or a named pipe already there, made with `mkfifo`. Each connection to the
+/// socket is answered `ok` or `error: ...` line by line.
+pub struct ControlServer {
+    commands: Receiver<ControlCommand>,
+    /// The socket to remove once the player exits, if one was created.
+    socket: Option<PathBuf>,
+}
+
+impl ControlServer {
+    #[cfg(unix)]
+    pub fn bind(path: &Path) -> Result<Self, AsciiGenError> {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        let bind_error = |source| AsciiGenError::Write {
+            path: path.to_path_buf(),
+            source,
+        };
+        let (sender, commands) = mpsc::channel();
+
+        let is_fifo = std::fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_fifo());
+        if is_fifo {
+            let path = path.to_path_buf();
+            std::thread::spawn(move || read_fifo(&path, &sender));
+            return Ok(Self {
+                commands,
+                socket: None,
+            });
+        }
+
+        // A socket left behind by a player that crashed would refuse the bind, while one that
+        // still answers belongs to another player
+        if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            if UnixStream::connect(path).is_ok() {
+                return Err(bind_error(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "another player is listening there",
+                )));
+            }
+            std::fs::remove_file(path).map_err(bind_error)?;
+        }
+        let listener = UnixListener::bind(path).map_err(bind_error)?;
+        tracing::debug!(path = %path.display(), "listening for control commands");
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let sender = sender.clone();
+                        std::thread::spawn(move || {
+                            let reader = match stream.try_clone() {
+                                Ok(reader) => reader,
+                                Err(e) => {
+                                    tracing::warn!(error = %e, "cannot read control commands");
+                                    return;
+                                }
+                            };
+                            if let Err(e) = serve(BufReader::new(reader), stream, &sender) {
+                                tracing::debug!(error = %e, "control connection closed");
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!(error = %e, "accepting a control connection failed"),
+                }
+            }
+        });
+
+        Ok(Self {
+            commands,
+            socket: Some(path.to_path_buf()),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(path: &Path) -> Result<Self, AsciiGenError> {
+        Err(AsciiGenError::Write {
+            path: path.to_path_buf(),
+            source: io::Error::new(
+                io::ErrorKind::Unsupported,
+                "--control needs unix sockets or named pipes",
+            ),
+        })
+    }
+
+    /// The commands received since the last call, in order.
+    pub fn commands(&self) -> Vec<ControlCommand> {
+        self.commands.try_iter().collect()
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        if let Some(socket) = &self.socket {
+            let _ = std::fs::remove_file(socket);
+        }
+    }
+}
+
+/// Answers the commands of one connection until it closes or the player is gone.
+#[cfg(unix)]
+fn serve(
+    reader: impl BufRead,
+    mut writer: impl Write,
+    sender: &Sender<ControlCommand>,
+) -> io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse() {
+            Ok(command) => {
+                if sender.send(command).is_err() {
+                    return Ok(());
+                }
+                writeln!(writer, "ok")?;
+            }
+            Err(e) => writeln!(writer, "error: {}", e)?,
+        }
+    }
+    Ok(())
+}
+
+/// Reads commands from a named pipe, opening it again after each writer closes it.
+#[cfg(unix)]
+fn read_fifo(path: &Path, sender: &Sender<ControlCommand>) {
+    loop {
+        let fifo = match std::fs::File::open(path) {
+            Ok(fifo) => fifo,
+            Err(e) => {
+                tracing::warn!(error = %e, "cannot open the control pipe");
+                return;
+            }
+        };
+        for line in BufReader::new(fifo).lines().map_while(Result::ok) {
+            match line.parse() {
+                Ok(command) => {
+                    if sender.send(command).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "ignoring a control command"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!("pause".parse(), Ok(ControlCommand::Pause));
+        assert_eq!("  Resume ".parse(), Ok(ControlCommand::Play));
+        assert_eq!("toggle".parse(), Ok(ControlCommand::TogglePause));
+        assert_eq!("exit".parse(), Ok(ControlCommand::Quit));
+        assert_eq!("speed 1.5".parse(), Ok(ControlCommand::Speed(1.5)));
+    }
+
+    #[test]
+    fn parses_absolute_and_relative_seeks() {
+        let seek = |seconds, relative| Ok(ControlCommand::Seek { seconds, relative });
+        assert_eq!("seek 30".parse(), seek(30.0, false));
+        assert_eq!("seek +10".parse(), seek(10.0, true));
+        assert_eq!("seek -10".parse(), seek(-10.0, true));
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        for line in [
+            "",
+            "rewind",
+            "seek",
+            "seek ten",
+            "seek inf",
+            "speed 0",
+            "speed -2",
+            "speed NaN",
+        ] {
+            assert!(line.parse::<ControlCommand>().is_err(), "{:?}", line);
+        }
+    }
+}
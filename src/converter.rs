@@ -1,19 +1,204 @@
-use image::{DynamicImage, GenericImageView, ImageBuffer, Luma, Pixel};
+use crate::charset::{CharMapper, Charset};
+use crate::color::{CellColor, ColorMode, HALF_BLOCK};
+use crate::curve::ToneCurve;
+use crate::error::AsciiGenError;
+use crate::histogram::Histogram;
+use crate::metadata::{ImageMetadata, Orientation};
+use crate::output::Renderer;
+use crate::palette::Palette;
+use crate::plan::{Sampler, Upscale};
+use crate::progress::ConversionHandle;
+use crate::rotation::Rotation;
+use crate::tone::ToneTable;
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-/// Options for the ASCII art conversion.
+/// How many times taller than wide a terminal character cell is.
+pub const CHAR_ASPECT_RATIO: f32 = 2.0;
+
+/// Memory an image may take once decoded unless told otherwise, as in the image crate.
+pub const DEFAULT_MAX_MEMORY: u64 = 512 * 1024 * 1024;
+
+/// Options for the ASCII art conversion, created with `AsciiOptions::builder()`.
+///
+/// Missing fields take their default when deserialized.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[non_exhaustive]
 pub struct AsciiOptions {
     width: u32,
     height: u32,
     gamma: f32,
+    charset: Charset,
+    invert: bool,
+    /// Part of the source to convert, as `(x, y, width, height)` in pixels.
+    crop: Option<(u32, u32, u32, u32)>,
+    curve: ToneCurve,
+    /// Colors the art's colors are snapped to, if any.
+    palette: Option<Palette>,
+    /// How cells are filled when the art is larger than the source.
+    upscale: Upscale,
 }
 
 /// Default implementation for the ASCII art conversion options.
 impl AsciiOptions {
+    /// Smallest and largest allowed width or height, in characters.
+    pub const SIZE_BOUNDS: (u32, u32) = (1, 2000);
+    /// Smallest and largest allowed gamma.
+    pub const GAMMA_BOUNDS: (f32, f32) = (0.1, 10.0);
+
+    /// Creates the options, clamping every value to its bounds.
     pub fn new(width: u32, height: u32, gamma: f32) -> Self {
         Self {
-            width,
-            height,
-            gamma,
+            width: Self::clamp_size(width),
+            height: Self::clamp_size(height),
+            gamma: Self::clamp_gamma(gamma),
+            charset: Charset::Standard,
+            invert: false,
+            crop: None,
+            curve: ToneCurve::default(),
+            palette: None,
+            upscale: Upscale::default(),
+        }
+    }
+
+    /// Starts from the default options, checking every value once `build` is called.
+    ///
+    /// ```
+    /// use ascii_gen::{charset::Charset, converter::AsciiOptions};
+    ///
+    /// let options = AsciiOptions::builder()
+    ///     .size(80, 24)
+    ///     .charset(Charset::Blocks)
+    ///     .build()?;
+    /// assert_eq!(options.width(), 80);
+    /// assert!(AsciiOptions::builder().gamma(0.0).build().is_err());
+    /// # Ok::<(), ascii_gen::converter::OptionsError>(())
+    /// ```
+    pub fn builder() -> AsciiOptionsBuilder {
+        AsciiOptionsBuilder::default()
+    }
+
+    /// Clamps a width or height to `SIZE_BOUNDS`.
+    pub fn clamp_size(size: u32) -> u32 {
+        size.clamp(Self::SIZE_BOUNDS.0, Self::SIZE_BOUNDS.1)
+    }
+
+    /// Clamps a gamma to `GAMMA_BOUNDS`, treating NaN as the default of 1.0.
+    pub fn clamp_gamma(gamma: f32) -> f32 {
+        if gamma.is_nan() {
+            return 1.0;
+        }
+        gamma.clamp(Self::GAMMA_BOUNDS.0, Self::GAMMA_BOUNDS.1)
+    }
+
+    /// Changes the size, clamping it like `new`.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = Self::clamp_size(width);
+        self.height = Self::clamp_size(height);
+        self
+    }
+
+    /// Changes the gamma, clamping it like `new`.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = Self::clamp_gamma(gamma);
+        self
+    }
+
+    pub fn with_charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    pub fn with_invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    pub fn with_crop(mut self, crop: Option<(u32, u32, u32, u32)>) -> Self {
+        self.crop = crop;
+        self
+    }
+
+    pub fn with_curve(mut self, curve: ToneCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn with_palette(mut self, palette: Option<Palette>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    pub fn with_upscale(mut self, upscale: Upscale) -> Self {
+        self.upscale = upscale;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    pub fn charset(&self) -> Charset {
+        self.charset
+    }
+
+    pub fn invert(&self) -> bool {
+        self.invert
+    }
+
+    pub fn crop(&self) -> Option<(u32, u32, u32, u32)> {
+        self.crop
+    }
+
+    pub fn curve(&self) -> &ToneCurve {
+        &self.curve
+    }
+
+    pub fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    pub fn upscale(&self) -> Upscale {
+        self.upscale
+    }
+
+    /// Checks the options against their bounds, as options read from a file skip the
+    /// clamping of `new`.
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        let (min_size, max_size) = Self::SIZE_BOUNDS;
+        for size in [self.width, self.height] {
+            if !(min_size..=max_size).contains(&size) {
+                return Err(OptionsError::Size(size));
+            }
+        }
+        let (min_gamma, max_gamma) = Self::GAMMA_BOUNDS;
+        if !(min_gamma..=max_gamma).contains(&self.gamma) {
+            return Err(OptionsError::Gamma(self.gamma));
+        }
+        if let Some((_, _, 0, _) | (_, _, _, 0)) = self.crop {
+            return Err(OptionsError::EmptyCrop);
+        }
+        Ok(())
+    }
+
+    /// Computes the luminance of an RGB value after gamma correction, the tone curve and
+    /// inversion.
+    pub fn tone(&self, rgb: [u8; 3]) -> u8 {
+        let luminance = self.curve.apply(luminance(rgb, self.gamma));
+        if self.invert {
+            255 - luminance
+        } else {
+            luminance
         }
     }
 }
@@ -25,84 +210,821 @@ impl Default for AsciiOptions {
     }
 }
 
+/// Builds `AsciiOptions`, leaving unset options at their defaults.
+#[derive(Clone, Debug, Default)]
+pub struct AsciiOptionsBuilder {
+    options: AsciiOptions,
+    /// Largest width and height allowed on top of `SIZE_BOUNDS`.
+    max_size: Option<(u32, u32)>,
+}
+
+impl AsciiOptionsBuilder {
+    /// Size of the art in characters.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.options.width = width;
+        self.options.height = height;
+        self
+    }
+
+    pub fn gamma(mut self, gamma: f32) -> Self {
+        self.options.gamma = gamma;
+        self
+    }
+
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.options.charset = charset;
+        self
+    }
+
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.options.invert = invert;
+        self
+    }
+
+    /// Part of the source to convert, as `(x, y, width, height)` in pixels.
+    pub fn crop(mut self, crop: Option<(u32, u32, u32, u32)>) -> Self {
+        self.options.crop = crop;
+        self
+    }
+
+    pub fn curve(mut self, curve: ToneCurve) -> Self {
+        self.options.curve = curve;
+        self
+    }
+
+    /// Colors the art's colors are snapped to.
+    pub fn palette(mut self, palette: Option<Palette>) -> Self {
+        self.options.palette = palette;
+        self
+    }
+
+    /// How cells are filled when the art is larger than the source.
+    pub fn upscale(mut self, upscale: Upscale) -> Self {
+        self.options.upscale = upscale;
+        self
+    }
+
+    /// Refuses art wider or taller than this, e.g. the terminal or a page to print on.
+    ///
+    /// ```
+    /// use ascii_gen::converter::{AsciiOptions, OptionsError};
+    ///
+    /// let options = AsciiOptions::builder().size(120, 40).max_size(80, 24).build();
+    /// assert_eq!(options, Err(OptionsError::TooLarge { size: (120, 40), max: (80, 24) }));
+    /// ```
+    pub fn max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    /// Checks the options against their bounds instead of clamping them like `AsciiOptions::new`.
+    pub fn build(self) -> Result<AsciiOptions, OptionsError> {
+        let options = self.options;
+        options.validate()?;
+        if let Some(max) = self.max_size {
+            let size = (options.width, options.height);
+            if size.0 > max.0 || size.1 > max.1 {
+                return Err(OptionsError::TooLarge { size, max });
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Why `AsciiOptionsBuilder::build` rejected the options.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum OptionsError {
+    /// A width or height outside `AsciiOptions::SIZE_BOUNDS`.
+    Size(u32),
+    /// A gamma outside `AsciiOptions::GAMMA_BOUNDS`, or NaN.
+    Gamma(f32),
+    /// A crop with no width or height.
+    EmptyCrop,
+    /// A size beyond the one given to `AsciiOptionsBuilder::max_size`.
+    TooLarge { size: (u32, u32), max: (u32, u32) },
+}
+
+impl std::fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Size(size) => {
+                let (min, max) = AsciiOptions::SIZE_BOUNDS;
+                write!(f, "size {} is outside {}..={}", size, min, max)
+            }
+            Self::Gamma(gamma) => {
+                let (min, max) = AsciiOptions::GAMMA_BOUNDS;
+                write!(f, "gamma {} is outside {}..={}", gamma, min, max)
+            }
+            Self::EmptyCrop => f.write_str("crop must be at least one pixel wide and tall"),
+            Self::TooLarge { size, max } => write!(
+                f,
+                "size {}x{} is larger than the largest allowed, {}x{}",
+                size.0, size.1, max.0, max.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {}
+
 /// Trait for converting something generically to ASCII art.
 pub trait ToAsciiArt {
-    fn to_ascii_art(&self, options: Option<AsciiOptions>) -> String;
+    /// Converts the part of the source selected by the options' crop, or all of it.
+    fn to_ascii_art(&self, options: Option<AsciiOptions>) -> Result<String, AsciiGenError>;
+
+    /// Same as `to_ascii_art`, reporting progress to `handle` and stopping once it is
+    /// cancelled.
+    fn to_ascii_art_tracked(
+        &self,
+        options: Option<AsciiOptions>,
+        handle: &ConversionHandle,
+    ) -> Result<String, AsciiGenError>;
+
+    /// Converts with a custom character mapper and renderer instead of the options' charset
+    /// and plain text. Cells are colored with their average color when `color_mode` is set.
+    ///
+    /// ```
+    /// use ascii_gen::{charset::Ramp, color::ColorMode, output::HtmlRenderer};
+    /// use ascii_gen::converter::{ImageConverter, ToAsciiArt};
+    ///
+    /// let source = ImageConverter::from_image(image::DynamicImage::new_rgb8(8, 8));
+    /// let katakana = Ramp::new("ｦｧｨｩｪｫｬｭｮｯ").unwrap();
+    /// let html = source.to_ascii_art_with(None, &katakana, &HtmlRenderer, ColorMode::Truecolor)?;
+    /// assert!(html.starts_with("<!DOCTYPE html>"));
+    /// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+    /// ```
+    fn to_ascii_art_with(
+        &self,
+        options: Option<AsciiOptions>,
+        mapper: &dyn CharMapper,
+        renderer: &dyn Renderer,
+        color_mode: ColorMode,
+    ) -> Result<String, AsciiGenError>;
+}
+
+/// A source of RGB pixels that can be sampled into ASCII art cells.
+///
+/// Sources are `Sync` so that rows can be sampled in parallel with the `parallel` feature.
+pub trait PixelSource: Sync {
+    /// Width and height of the source in pixels.
+    fn dimensions(&self) -> (u32, u32);
+
+    /// Color of the pixel at the given coordinates.
+    fn rgb(&self, x: u32, y: u32) -> [u8; 3];
+
+    /// Computes the average color of every cell of a `width` x `height` grid, row by row.
+    fn to_color_grid(&self, target_width: u32, target_height: u32) -> Vec<[u8; 3]> {
+        let mut grid = Vec::with_capacity((target_width * target_height) as usize);
+        self.to_color_grid_into(&mut grid, target_width, target_height);
+        grid
+    }
+
+    /// Same as `to_color_grid`, but reuses the allocation of `grid`.
+    fn to_color_grid_into(&self, grid: &mut Vec<[u8; 3]>, target_width: u32, target_height: u32) {
+        // A fresh handle is never cancelled
+        let _ = self.to_color_grid_tracked(
+            grid,
+            target_width,
+            target_height,
+            &ConversionHandle::default(),
+        );
+    }
+
+    /// Same as `to_color_grid_into`, reporting each row to `handle` and stopping once it is
+    /// cancelled.
+    fn to_color_grid_tracked(
+        &self,
+        grid: &mut Vec<[u8; 3]>,
+        target_width: u32,
+        target_height: u32,
+        handle: &ConversionHandle,
+    ) -> Result<(), AsciiGenError> {
+        self.to_color_grid_upscaled(
+            grid,
+            target_width,
+            target_height,
+            Upscale::default(),
+            handle,
+        )
+    }
+
+    /// Same as `to_color_grid_tracked`, filling the cells of a grid larger than the source
+    /// as `upscale` says.
+    fn to_color_grid_upscaled(
+        &self,
+        grid: &mut Vec<[u8; 3]>,
+        target_width: u32,
+        target_height: u32,
+        upscale: Upscale,
+        handle: &ConversionHandle,
+    ) -> Result<(), AsciiGenError> {
+        Sampler::new(self.dimensions(), target_width, target_height, upscale)
+            .sample(self, grid, handle)
+    }
+
+    /// Same as `to_color_grid_into`, with the size and upscaling of `options` and `rows`
+    /// rows.
+    fn to_color_grid_for(&self, grid: &mut Vec<[u8; 3]>, options: &AsciiOptions, rows: u32) {
+        // A fresh handle is never cancelled
+        let _ = self.to_color_grid_upscaled(
+            grid,
+            options.width,
+            rows,
+            options.upscale,
+            &ConversionHandle::default(),
+        );
+    }
+
+    /// Computes the gamma corrected luminance of every cell, row by row.
+    fn to_luminance_grid(&self, options: &AsciiOptions) -> Vec<u8> {
+        let mut grid = Vec::new();
+        self.to_luminance_grid_into(&mut grid, &mut Vec::new(), options);
+        grid
+    }
+
+    /// Same as `to_luminance_grid`, but reuses the allocations of `grid` and `pixels`.
+    fn to_luminance_grid_into(
+        &self,
+        grid: &mut Vec<u8>,
+        pixels: &mut Vec<[u8; 3]>,
+        options: &AsciiOptions,
+    ) {
+        self.to_color_grid_for(pixels, options, options.height);
+        ToneTable::new(options).tones_into(pixels, grid);
+    }
+
+    /// Writes the ASCII art into `art`, reusing its allocation and the `pixels` scratch buffer.
+    fn to_ascii_into(&self, art: &mut String, pixels: &mut Vec<[u8; 3]>, options: &AsciiOptions) {
+        self.to_color_grid_for(pixels, options, options.height);
+        pixels_to_ascii_art_into(art, pixels, options);
+    }
+
+    /// Renders the source as half blocks, each cell showing two vertically stacked pixels.
+    fn to_half_blocks(&self, options: &AsciiOptions) -> (String, Vec<CellColor>) {
+        let mut art = String::with_capacity((options.width * options.height) as usize * 4);
+        let mut cells = Vec::with_capacity((options.width * options.height) as usize);
+        self.to_half_blocks_into(&mut art, &mut cells, &mut Vec::new(), options);
+        (art, cells)
+    }
+
+    /// Same as `to_half_blocks`, but reuses the allocations of `art`, `cells` and `pixels`.
+    fn to_half_blocks_into(
+        &self,
+        art: &mut String,
+        cells: &mut Vec<CellColor>,
+        pixels: &mut Vec<[u8; 3]>,
+        options: &AsciiOptions,
+    ) {
+        self.to_color_grid_for(pixels, options, options.height * 2);
+        pixels_to_half_blocks_into(art, cells, pixels, options);
+    }
+}
+
+/// Implementation for converting a pixel source to ASCII art.
+impl<T: PixelSource> ToAsciiArt for T {
+    fn to_ascii_art(&self, options: Option<AsciiOptions>) -> Result<String, AsciiGenError> {
+        let options = options.unwrap_or_default();
+        let grid = crop(self, &options)?.to_luminance_grid(&options);
+        Ok(grid_to_ascii_art(&grid, &options))
+    }
+
+    fn to_ascii_art_tracked(
+        &self,
+        options: Option<AsciiOptions>,
+        handle: &ConversionHandle,
+    ) -> Result<String, AsciiGenError> {
+        let options = options.unwrap_or_default();
+        let mut pixels = Vec::new();
+        crop(self, &options)?.to_color_grid_upscaled(
+            &mut pixels,
+            options.width,
+            options.height,
+            options.upscale,
+            handle,
+        )?;
+
+        let mut art = String::new();
+        pixels_to_ascii_art_into(&mut art, &pixels, &options);
+        Ok(art)
+    }
+
+    fn to_ascii_art_with(
+        &self,
+        options: Option<AsciiOptions>,
+        mapper: &dyn CharMapper,
+        renderer: &dyn Renderer,
+        color_mode: ColorMode,
+    ) -> Result<String, AsciiGenError> {
+        let options = options.unwrap_or_default();
+        let mut pixels = Vec::new();
+        crop(self, &options)?.to_color_grid_for(&mut pixels, &options, options.height);
+
+        let mut art = String::with_capacity(pixels.len() + options.height as usize);
+        for row in pixels.chunks(options.width.max(1) as usize) {
+            for &rgb in row {
+                art.push(mapper.glyph(options.tone(rgb), rgb));
+            }
+            art.push('\n');
+        }
+
+        let mut colors: Vec<CellColor> = if color_mode.is_enabled() {
+            pixels
+                .iter()
+                .map(|&rgb| CellColor {
+                    fg: mapper.color(options.tone(rgb), rgb).unwrap_or(rgb),
+                    bg: None,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if let Some(palette) = options.palette() {
+            palette.quantize(&mut colors);
+        }
+        Ok(renderer.render(&art, &colors, color_mode))
+    }
 }
 
 /// Implementation for converting an image to ASCII art.
+///
+/// The image is converted once to a contiguous RGB buffer that sampling indexes directly,
+/// which is much faster than going through `GenericImageView::get_pixel` for every pixel.
 pub struct ImageConverter {
-    image: DynamicImage,
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
 }
 
 /// Implementation for converting an image to ASCII art.
 impl ImageConverter {
-    pub fn from_image_buffer(image: ImageBuffer<Luma<u8>, Vec<u8>>) -> Self {
+    pub fn from_image(image: DynamicImage) -> Self {
+        let (width, height) = image.dimensions();
         Self {
-            image: DynamicImage::ImageLuma8(image),
+            pixels: image.into_rgb8().into_raw(),
+            width,
+            height,
         }
     }
 }
 
-/// Implementation for converting an image to ASCII art.
-impl ToAsciiArt for ImageConverter {
-    fn to_ascii_art(&self, options: Option<AsciiOptions>) -> String {
-        let options = options.unwrap_or_default();
+/// Reads and decodes an image, guessing its format from the contents and turning photos
+/// upright by their EXIF orientation.
+pub fn open_image(path: impl AsRef<Path>) -> Result<DynamicImage, AsciiGenError> {
+    open_image_with(path, DEFAULT_MAX_MEMORY)
+}
 
-        let target_width = options.width;
-        let target_height = options.height;
-        let gamma = options.gamma;
-
-        let width_ratio = self.image.width() as f32 / target_width as f32;
-        let height_ratio = self.image.height() as f32 / target_height as f32;
-
-        let mut ascii_art = String::with_capacity((target_width * target_height) as usize);
-
-        for y in 0..target_height {
-            for x in 0..target_width {
-                let start_x = (x as f32 * width_ratio) as u32;
-                let start_y = (y as f32 * height_ratio) as u32;
-
-                let mut total_r = 0;
-                let mut total_g = 0;
-                let mut total_b = 0;
-
-                for dy in 0..height_ratio as u32 {
-                    for dx in 0..width_ratio as u32 {
-                        let pixel = self.image.get_pixel(start_x + dx, start_y + dy);
-                        let channels = pixel.channels();
-                        total_r += channels[0] as u32;
-                        total_g += channels[1] as u32;
-                        total_b += channels[2] as u32;
-                    }
-                }
-
-                let count = (width_ratio * height_ratio) as u32;
-                let avg_r = (total_r / count) as u8;
-                let avg_g = (total_g / count) as u8;
-                let avg_b = (total_b / count) as u8;
-
-                let base_luminance =
-                    (0.2126 * avg_r as f32 + 0.7152 * avg_g as f32 + 0.0722 * avg_b as f32) as u8;
-                let luminance = ((base_luminance as f32 / 255.0).powf(gamma) * 255.0) as u8;
-
-                let character = match luminance {
-                    0..=25 => '#',
-                    26..=51 => '@',
-                    52..=76 => '8',
-                    77..=102 => '&',
-                    103..=127 => 'o',
-                    128..=153 => '*',
-                    154..=178 => ':',
-                    179..=204 => ',',
-                    205..=255 => '.',
-                };
-
-                ascii_art.push(character);
-            }
-            ascii_art.push('\n');
+/// Same as `open_image`, but decodes JPEGs taking more than `max_memory` bytes at a fraction
+/// of their size and fails on other images that large instead of running out of memory.
+pub fn open_image_with(
+    path: impl AsRef<Path>,
+    max_memory: u64,
+) -> Result<DynamicImage, AsciiGenError> {
+    let _span = tracing::trace_span!("decode").entered();
+    let path = path.as_ref();
+    let read_error = |source| AsciiGenError::Read {
+        path: path.to_path_buf(),
+        source,
+    };
+    let decode_error = |source| AsciiGenError::Decode {
+        path: path.to_path_buf(),
+        source,
+    };
+    let open = || {
+        image::io::Reader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(read_error)
+    };
+
+    // Look at the size before decoding anything, assuming four bytes a pixel
+    let reader = open()?;
+    let format = reader.format();
+    let (width, height) = reader.into_dimensions().map_err(decode_error)?;
+    let needed = width as u64 * height as u64 * 4;
+
+    let image = if needed <= max_memory {
+        let mut reader = open()?;
+        let mut limits = image::io::Limits::default();
+        limits.max_alloc = Some(max_memory);
+        reader.limits(limits);
+        reader.decode().map_err(decode_error)?
+    } else if format == Some(image::ImageFormat::Jpeg) {
+        let file = std::fs::File::open(path).map_err(read_error)?;
+        let mut decoder = image::codecs::jpeg::JpegDecoder::new(std::io::BufReader::new(file))
+            .map_err(decode_error)?;
+        let shrink = (max_memory as f64 / needed as f64).sqrt();
+        let (scaled_width, scaled_height) = decoder
+            .scale(
+                (width as f64 * shrink).clamp(1.0, u16::MAX as f64) as u16,
+                (height as f64 * shrink).clamp(1.0, u16::MAX as f64) as u16,
+            )
+            .map_err(decode_error)?;
+        // JPEGs shrink by an eighth at most while decoding
+        if scaled_width as u64 * scaled_height as u64 * 4 > max_memory {
+            return Err(too_large(path, width, height, max_memory));
+        }
+        tracing::info!(
+            path = %path.display(),
+            width,
+            height,
+            scaled_width,
+            scaled_height,
+            "decoding a large image at a smaller size"
+        );
+        DynamicImage::from_decoder(decoder).map_err(decode_error)?
+    } else {
+        return Err(too_large(path, width, height, max_memory));
+    };
+
+    // Phones store portrait photos sideways, leaving the turn to an EXIF tag
+    let image = match ImageMetadata::read(path) {
+        Some(metadata) if metadata.orientation != Orientation::Normal => {
+            tracing::debug!(orientation = ?metadata.orientation, "applying the EXIF orientation");
+            metadata.orientation.apply(image)
+        }
+        _ => image,
+    };
+
+    let (width, height) = image.dimensions();
+    tracing::debug!(path = %path.display(), width, height, "decoded image");
+    match (width, height) {
+        (0, _) | (_, 0) => Err(AsciiGenError::EmptySource),
+        _ => Ok(image),
+    }
+}
+
+fn too_large(path: &Path, width: u32, height: u32, max_memory: u64) -> AsciiGenError {
+    AsciiGenError::TooLarge {
+        path: path.to_path_buf(),
+        width,
+        height,
+        max_memory,
+    }
+}
+
+/// Implementation for sampling pixels from an image.
+impl PixelSource for ImageConverter {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    fn rgb(&self, x: u32, y: u32) -> [u8; 3] {
+        let offset = (y as usize * self.width as usize + x as usize) * 3;
+        [
+            self.pixels[offset],
+            self.pixels[offset + 1],
+            self.pixels[offset + 2],
+        ]
+    }
+}
+
+/// A rectangular region of another pixel source, used to crop or zoom into part of it.
+pub struct Region<'a, S: PixelSource> {
+    source: &'a S,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Implementation for sampling part of another source.
+impl<'a, S: PixelSource> Region<'a, S> {
+    /// Wraps the given region, shrinking it to fit inside the source.
+    ///
+    /// Fails if the source has no pixels to keep.
+    pub fn new(
+        source: &'a S,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, AsciiGenError> {
+        let (source_width, source_height) = source.dimensions();
+        if source_width == 0 || source_height == 0 {
+            return Err(AsciiGenError::EmptySource);
+        }
+        let x = x.min(source_width - 1);
+        let y = y.min(source_height - 1);
+        Ok(Self {
+            source,
+            x,
+            y,
+            width: width.clamp(1, source_width - x),
+            height: height.clamp(1, source_height - y),
+        })
+    }
+}
+
+/// The part of `source` selected by the crop option, or all of it without one.
+///
+/// Fails if the source has no pixels.
+pub fn crop<'a, S: PixelSource>(
+    source: &'a S,
+    options: &AsciiOptions,
+) -> Result<Region<'a, S>, AsciiGenError> {
+    let (width, height) = source.dimensions();
+    let (x, y, width, height) = options.crop.unwrap_or((0, 0, width, height));
+    Region::new(source, x, y, width, height)
+}
+
+/// Implementation for sampling pixels from a region.
+impl<S: PixelSource> PixelSource for Region<'_, S> {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn rgb(&self, x: u32, y: u32) -> [u8; 3] {
+        self.source.rgb(self.x + x, self.y + y)
+    }
+}
+
+/// Converts a packed RGB24 plane, such as a decoded video frame, without copying it.
+pub struct FrameConverter<'a> {
+    data: &'a [u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    rotation: Rotation,
+}
+
+/// Implementation for converting a video frame to ASCII art.
+impl<'a> FrameConverter<'a> {
+    /// Wraps a plane whose rows are `stride` bytes apart, which may exceed `width * 3`.
+    ///
+    /// Fails if `data` is too short to hold every row, if rows are closer than `width * 3`
+    /// bytes, or if the frame has no pixels.
+    pub fn from_rgb_plane(
+        data: &'a [u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+        rotation: Rotation,
+    ) -> Result<Self, AsciiGenError> {
+        if width == 0 || height == 0 {
+            return Err(AsciiGenError::EmptySource);
+        }
+        let row = width as usize * 3;
+        if stride < row {
+            return Err(AsciiGenError::Stride { stride, row });
+        }
+        // The last row only needs its pixels, not the padding up to the next stride
+        let expected = (height as usize - 1) * stride + width as usize * 3;
+        if data.len() < expected {
+            return Err(AsciiGenError::BufferSize {
+                expected,
+                actual: data.len(),
+            });
         }
 
-        ascii_art
+        Ok(Self {
+            data,
+            width,
+            height,
+            stride,
+            rotation,
+        })
+    }
+}
+
+/// Implementation for sampling pixels from a video frame, honoring its rotation.
+impl PixelSource for FrameConverter<'_> {
+    fn dimensions(&self) -> (u32, u32) {
+        self.rotation.dimensions(self.width, self.height)
+    }
+
+    fn rgb(&self, x: u32, y: u32) -> [u8; 3] {
+        let (x, y) = self.rotation.source_coords(x, y, self.width, self.height);
+        let offset = y as usize * self.stride + x as usize * 3;
+        [
+            self.data[offset],
+            self.data[offset + 1],
+            self.data[offset + 2],
+        ]
+    }
+}
+
+/// Longest side of the grid sampled when analyzing a source.
+const ANALYSIS_SIZE: u32 = 256;
+
+/// What `analyze` measured about a source, with the options it suggests.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    /// Width and height in pixels.
+    pub dimensions: (u32, u32),
+    /// Width divided by height.
+    pub aspect_ratio: f32,
+    pub mean_luminance: u8,
+    pub median_luminance: u8,
+    /// Luminance of the darkest and brightest 5% of the image, ignoring outliers.
+    pub luminance_range: (u8, u8),
+    /// Size fitting the bounds, a gamma bringing the median to mid-gray, and a charset
+    /// suiting the size and contrast.
+    pub suggested: AsciiOptions,
+}
+
+/// Measures the size and luminance distribution of a source to suggest options for art
+/// fitting within `bounds` characters.
+pub fn analyze(source: &impl PixelSource, bounds: (u32, u32)) -> Analysis {
+    let (width, height) = source.dimensions();
+    let scale = (ANALYSIS_SIZE as f32 / width.max(height).max(1) as f32).min(1.0);
+    let grid = source.to_color_grid(
+        ((width as f32 * scale) as u32).max(1),
+        ((height as f32 * scale) as u32).max(1),
+    );
+
+    let mut histogram = Histogram::default();
+    histogram.fill(grid.iter().map(|&rgb| luminance(rgb, 1.0)));
+    let median = histogram.percentile(0.5);
+    let range = (histogram.percentile(0.05), histogram.percentile(0.95));
+
+    // Solve (median / 255) ^ gamma = 0.5, rounded to the editor's 0.05 steps
+    let gamma = match median {
+        0 | 255 => 1.0,
+        _ => (libm::logf(0.5) / libm::logf(median as f32 / 255.0)).clamp(0.4, 2.5),
+    };
+    let gamma = (gamma * 20.0).round() / 20.0;
+
+    let (art_width, art_height) = fit_dimensions((width, height), bounds);
+    // Small art only has room for a few characters, while large or flat images need many
+    // levels to show their detail
+    let contrast = range.1.saturating_sub(range.0);
+    let charset = match art_width {
+        0..=60 => Charset::Simple,
+        _ if art_width >= 120 || contrast < 96 => Charset::Detailed,
+        _ => Charset::Standard,
+    };
+
+    Analysis {
+        dimensions: (width, height),
+        aspect_ratio: width as f32 / height.max(1) as f32,
+        mean_luminance: histogram.mean(),
+        median_luminance: median,
+        luminance_range: range,
+        suggested: AsciiOptions::new(art_width, art_height, gamma).with_charset(charset),
+    }
+}
+
+/// Picks the largest grid fitting within `bounds` that keeps the source's aspect ratio,
+/// compensating for characters being taller than they are wide.
+pub fn fit_dimensions(source: (u32, u32), bounds: (u32, u32)) -> (u32, u32) {
+    let (source_width, source_height) = (source.0.max(1) as f32, source.1.max(1) as f32);
+    let (max_width, max_height) = (bounds.0.max(1) as f32, bounds.1.max(1) as f32);
+
+    let mut width = max_width;
+    let mut height = width * source_height / source_width / CHAR_ASPECT_RATIO;
+    if height > max_height {
+        height = max_height;
+        width = height * CHAR_ASPECT_RATIO * source_width / source_height;
+    }
+
+    (
+        AsciiOptions::clamp_size(width.round() as u32),
+        AsciiOptions::clamp_size(height.round() as u32),
+    )
+}
+
+/// The height keeping the source's aspect ratio at the given width, in characters.
+pub fn height_for_width(source: (u32, u32), width: u32) -> u32 {
+    let (source_width, source_height) = (source.0.max(1) as f32, source.1.max(1) as f32);
+    let height = width as f32 * source_height / source_width / CHAR_ASPECT_RATIO;
+    AsciiOptions::clamp_size(height.round() as u32)
+}
+
+/// The width keeping the source's aspect ratio at the given height, in characters.
+pub fn width_for_height(source: (u32, u32), height: u32) -> u32 {
+    let (source_width, source_height) = (source.0.max(1) as f32, source.1.max(1) as f32);
+    let width = height as f32 * CHAR_ASPECT_RATIO * source_width / source_height;
+    AsciiOptions::clamp_size(width.round() as u32)
+}
+
+/// Computes the gamma corrected luminance of an RGB value.
+pub fn luminance(rgb: [u8; 3], gamma: f32) -> u8 {
+    gamma_correct(base_luminance(rgb), gamma)
+}
+
+/// Luminance of a color before gamma correction.
+#[inline(always)]
+pub fn base_luminance(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb;
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) as u8
+}
+
+/// Applies gamma to a luminance. The power comes from `libm` rather than the platform's
+/// math library, whose rounding differs between systems, so art is the same everywhere.
+pub fn gamma_correct(luminance: u8, gamma: f32) -> u8 {
+    (libm::powf(luminance as f32 / 255.0, gamma) * 255.0) as u8
+}
+
+/// Maps a grid of luminance values to ASCII art, one line per row.
+pub fn grid_to_ascii_art(grid: &[u8], options: &AsciiOptions) -> String {
+    let mut ascii_art =
+        String::with_capacity(grid.len() + grid.len() / options.width.max(1) as usize);
+    grid_to_ascii_art_into(&mut ascii_art, grid, options);
+    ascii_art
+}
+
+/// Maps the cell colors sampled by `to_color_grid` to characters, reusing the allocation of `art`.
+pub fn pixels_to_ascii_art_into(art: &mut String, pixels: &[[u8; 3]], options: &AsciiOptions) {
+    let (mut tones, mut levels) = (Vec::new(), Vec::new());
+    let table = ToneTable::new(options);
+    table.cells_into(pixels, &mut tones, &mut levels);
+    table.art_into(&levels, options.width, art);
+}
+
+/// Pairs the rows of a color grid sampled at twice the height into half blocks, reusing the
+/// allocations of `art` and `cells`.
+pub fn pixels_to_half_blocks_into(
+    art: &mut String,
+    cells: &mut Vec<CellColor>,
+    pixels: &[[u8; 3]],
+    options: &AsciiOptions,
+) {
+    let width = options.width.max(1) as usize;
+    art.clear();
+    cells.clear();
+
+    for rows in pixels.chunks(width * 2) {
+        let (top, bottom) = rows.split_at(width.min(rows.len()));
+        for (&fg, &bg) in top.iter().zip(bottom.iter()) {
+            art.push(HALF_BLOCK);
+            cells.push(CellColor { fg, bg: Some(bg) });
+        }
+        art.push('\n');
+    }
+}
+
+/// Same as `grid_to_ascii_art`, but reuses the allocation of `ascii_art`.
+pub fn grid_to_ascii_art_into(ascii_art: &mut String, grid: &[u8], options: &AsciiOptions) {
+    let glyphs: [char; 256] = std::array::from_fn(|tone| options.charset.char_for(tone as u8));
+    ascii_art.clear();
+
+    for row in grid.chunks(options.width.max(1) as usize) {
+        ascii_art.extend(row.iter().map(|&luminance| glyphs[luminance as usize]));
+        ascii_art.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    /// Black on the left half, white on the right.
+    fn halves() -> ImageConverter {
+        let image = RgbImage::from_fn(8, 4, |x, _| {
+            if x < 4 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+        ImageConverter::from_image(DynamicImage::ImageRgb8(image))
+    }
+
+    #[test]
+    fn to_ascii_art_applies_the_crop() {
+        let white = Charset::Standard.char_for(255);
+        let options = AsciiOptions::builder()
+            .size(4, 2)
+            .crop(Some((4, 0, 4, 4)))
+            .build()
+            .unwrap();
+        let art = halves().to_ascii_art(Some(options.clone())).unwrap();
+        assert!(art.lines().all(|line| line.chars().all(|c| c == white)));
+
+        let tracked = halves()
+            .to_ascii_art_tracked(Some(options), &ConversionHandle::default())
+            .unwrap();
+        assert_eq!(tracked, art);
+    }
+
+    #[test]
+    fn to_ascii_art_without_a_crop_converts_everything() {
+        let options = AsciiOptions::builder().size(8, 2).build().unwrap();
+        let art = halves().to_ascii_art(Some(options)).unwrap();
+        let first = art.lines().next().unwrap();
+        assert_eq!(first.chars().next(), Some(Charset::Standard.char_for(0)));
+        assert_eq!(first.chars().last(), Some(Charset::Standard.char_for(255)));
+    }
+
+    #[test]
+    fn region_shrinks_to_fit_the_source() {
+        let source = halves();
+        let region = Region::new(&source, 6, 10, 100, 100).unwrap();
+        assert_eq!(region.dimensions(), (2, 1));
+    }
+
+    #[test]
+    fn empty_source_is_an_error() {
+        let source = ImageConverter::from_image(DynamicImage::new_rgb8(0, 0));
+        assert!(matches!(
+            Region::new(&source, 0, 0, 1, 1),
+            Err(AsciiGenError::EmptySource)
+        ));
+        assert!(matches!(
+            source.to_ascii_art(None),
+            Err(AsciiGenError::EmptySource)
+        ));
     }
 }
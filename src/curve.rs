@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+/// Number of control points of a tone curve, evenly spaced across the luminance range.
+pub const CONTROL_POINTS: usize = 5;
+
+/// A piecewise linear remapping of luminance, applied after gamma and before the charset.
+///
+/// Stored in presets as the output level of each control point, e.g. `[0, 64, 128, 191, 255]`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+pub struct ToneCurve {
+    levels: [u8; CONTROL_POINTS],
+    /// The curve evaluated at every input level.
+    table: [u8; 256],
+}
+
+impl ToneCurve {
+    /// Creates a curve through the given output levels, one per control point.
+    pub fn new(levels: [u8; CONTROL_POINTS]) -> Self {
+        let mut table = [0; 256];
+        for (input, output) in table.iter_mut().enumerate() {
+            let position = input as f32 / 255.0 * (CONTROL_POINTS - 1) as f32;
+            let segment = (position as usize).min(CONTROL_POINTS - 2);
+            let t = position - segment as f32;
+            let (start, end) = (levels[segment] as f32, levels[segment + 1] as f32);
+            *output = (start + (end - start) * t).round() as u8;
+        }
+        Self { levels, table }
+    }
+
+    /// Input level of a control point.
+    pub fn input(point: usize) -> u8 {
+        (point * 255 / (CONTROL_POINTS - 1)) as u8
+    }
+
+    pub fn levels(&self) -> [u8; CONTROL_POINTS] {
+        self.levels
+    }
+
+    /// Moves a control point to a new output level.
+    pub fn set_level(&mut self, point: usize, level: u8) {
+        let mut levels = self.levels;
+        levels[point] = level;
+        *self = Self::new(levels);
+    }
+
+    pub fn apply(&self, luminance: u8) -> u8 {
+        self.table[luminance as usize]
+    }
+}
+
+impl Default for ToneCurve {
+    fn default() -> Self {
+        Self::new(std::array::from_fn(Self::input))
+    }
+}
+
+impl TryFrom<Vec<u8>> for ToneCurve {
+    type Error = String;
+
+    /// Fails for anything but one level per control point, naming how many are expected.
+    fn try_from(levels: Vec<u8>) -> Result<Self, Self::Error> {
+        let count = levels.len();
+        let levels: [u8; CONTROL_POINTS] = levels.try_into().map_err(|_| {
+            format!(
+                "a tone curve needs {} levels, got {}",
+                CONTROL_POINTS, count
+            )
+        })?;
+        Ok(Self::new(levels))
+    }
+}
+
+impl From<ToneCurve> for Vec<u8> {
+    fn from(curve: ToneCurve) -> Self {
+        curve.levels.to_vec()
+    }
+}
+
+/// Parses a curve given as comma-separated output levels, e.g. `0,40,128,220,255`.
+pub fn parse_curve(text: &str) -> Result<ToneCurve, String> {
+    let levels = text
+        .split(',')
+        .map(|value| value.trim().parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let levels: [u8; CONTROL_POINTS] = levels
+        .try_into()
+        .map_err(|_| format!("expected {} comma-separated levels", CONTROL_POINTS))?;
+    Ok(ToneCurve::new(levels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::value::{Error, SeqDeserializer};
+
+    fn deserialize(levels: Vec<u8>) -> Result<ToneCurve, Error> {
+        ToneCurve::deserialize(SeqDeserializer::<_, Error>::new(levels.into_iter()))
+    }
+
+    #[test]
+    fn deserializes_one_level_per_control_point() {
+        let curve = deserialize(vec![0, 40, 128, 220, 255]).unwrap();
+        assert_eq!(curve.levels(), [0, 40, 128, 220, 255]);
+        assert_eq!(curve.apply(0), 0);
+        assert_eq!(curve.apply(255), 255);
+    }
+
+    #[test]
+    fn wrong_number_of_levels_is_an_error() {
+        let error = deserialize(vec![0, 128, 255]).unwrap_err().to_string();
+        assert!(error.contains("needs 5 levels, got 3"), "{}", error);
+        assert!(ToneCurve::try_from(vec![0; 6]).is_err());
+    }
+
+    #[test]
+    fn default_curve_is_close_to_the_identity() {
+        let curve = ToneCurve::default();
+        assert!((0..=255u8).all(|level| curve.apply(level).abs_diff(level) <= 1));
+    }
+}
@@ -0,0 +1,72 @@
+use ffmpeg_next as ffmpeg;
+use serde::{Deserialize, Serialize};
+
+/// When to deinterlace decoded video frames.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Deinterlace {
+    /// Only deinterlace frames flagged as interlaced
+    Auto,
+    /// Always deinterlace
+    On,
+    /// Never deinterlace
+    Off,
+}
+
+/// A yadif filter graph that removes combing from interlaced frames.
+pub struct Deinterlacer {
+    graph: ffmpeg::filter::Graph,
+}
+
+impl Deinterlacer {
+    pub fn new(
+        decoder: &ffmpeg::decoder::Video,
+        time_base: ffmpeg::Rational,
+    ) -> Result<Self, ffmpeg::Error> {
+        let mut graph = ffmpeg::filter::Graph::new();
+
+        let aspect_ratio = decoder.aspect_ratio();
+        let buffer_args = format!(
+            "video_size={}x{}:pix_fmt={}:time_base={}/{}:pixel_aspect={}/{}",
+            decoder.width(),
+            decoder.height(),
+            ffmpeg::ffi::AVPixelFormat::from(decoder.format()) as i32,
+            time_base.numerator(),
+            time_base.denominator(),
+            aspect_ratio.numerator().max(1),
+            aspect_ratio.denominator().max(1),
+        );
+
+        graph.add(
+            &ffmpeg::filter::find("buffer").ok_or(ffmpeg::Error::FilterNotFound)?,
+            "in",
+            &buffer_args,
+        )?;
+        graph.add(
+            &ffmpeg::filter::find("buffersink").ok_or(ffmpeg::Error::FilterNotFound)?,
+            "out",
+            "",
+        )?;
+        graph.output("in", 0)?.input("out", 0)?.parse("yadif")?;
+        graph.validate()?;
+
+        Ok(Self { graph })
+    }
+
+    /// Feeds a frame through the filter, returning false while yadif is still buffering.
+    pub fn filter(
+        &mut self,
+        frame: &ffmpeg::frame::Video,
+        output: &mut ffmpeg::frame::Video,
+    ) -> Result<bool, ffmpeg::Error> {
+        self.graph
+            .get("in")
+            .ok_or(ffmpeg::Error::FilterNotFound)?
+            .source()
+            .add(frame)?;
+
+        let mut sink = self.graph.get("out").ok_or(ffmpeg::Error::FilterNotFound)?;
+        Ok(sink.sink().frame(output).is_ok())
+    }
+}
@@ -0,0 +1,161 @@
+/// The charset of accessible output: blank, dim and bright cells, from darkest to brightest.
+pub const LEVELS: &str = " .#";
+
+/// Words read out in place of the characters of `LEVELS`.
+const NAMES: [&str; 3] = ["blank", "dim", "bright"];
+
+/// Regions of the art, in the order of a three by three grid, named as alt text reads them.
+const REGIONS: [&str; 9] = [
+    "top left",
+    "top",
+    "top right",
+    "left",
+    "center",
+    "right",
+    "bottom left",
+    "bottom",
+    "bottom right",
+];
+
+/// Regions whose brightness is this close to the brightest or darkest count as it too.
+const REGION_MARGIN: f32 = 0.1;
+
+/// The level of a character, with anything drawn that is not in `LEVELS` counted as bright.
+fn level(glyph: char) -> usize {
+    LEVELS.find(glyph).unwrap_or(2)
+}
+
+/// Describes art drawn with `LEVELS` a row at a time as runs of blank, dim and bright
+/// cells, with rows that read the same merged, so a screen reader speaks words rather than
+/// punctuation.
+///
+/// ```
+/// use ascii_gen::describe;
+///
+/// let text = describe::rows("  ##\n  ##\n....\n    ");
+/// assert_eq!(text, "Rows 1 to 2: 2 blank, 2 bright.\nRow 3: 4 dim.\nRow 4: blank.");
+/// ```
+pub fn rows(art: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut previous: Option<(String, usize, usize)> = None;
+    let mut flush = |(runs, first, last): (String, usize, usize)| {
+        lines.push(if first == last {
+            format!("Row {}: {}.", first, runs)
+        } else {
+            format!("Rows {} to {}: {}.", first, last, runs)
+        });
+    };
+
+    for (index, line) in art.lines().enumerate() {
+        let row = runs(line);
+        previous = match previous {
+            Some((previous_row, first, _)) if previous_row == row => {
+                Some((previous_row, first, index + 1))
+            }
+            Some(done) => {
+                flush(done);
+                Some((row, index + 1, index + 1))
+            }
+            None => Some((row, index + 1, index + 1)),
+        };
+    }
+    if let Some(done) = previous {
+        flush(done);
+    }
+    lines.join("\n")
+}
+
+/// The runs of one row, such as `2 blank, 2 bright`, or just `blank` for an empty row.
+fn runs(line: &str) -> String {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for glyph in line.chars() {
+        match runs.last_mut() {
+            Some((run_level, count)) if *run_level == level(glyph) => *count += 1,
+            _ => runs.push((level(glyph), 1)),
+        }
+    }
+    match runs.as_slice() {
+        [] | [(0, _)] => NAMES[0].to_string(),
+        runs => runs
+            .iter()
+            .map(|&(level, count)| format!("{} {}", count, NAMES[level]))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// A short summary of art drawn with `LEVELS` for alt text: its size and the source's, how
+/// bright it is overall, and where its brightest and darkest regions are.
+///
+/// ```
+/// use ascii_gen::describe;
+///
+/// let text = describe::alt_text("#..\n...\n.. ", (64, 32));
+/// assert_eq!(
+///     text,
+///     "ASCII art of an image of 64 by 32 pixels, 3 columns by 3 rows. Mixed light and \
+///      dark, brightest at the top left, darkest at the bottom right."
+/// );
+/// ```
+pub fn alt_text(art: &str, source: (u32, u32)) -> String {
+    let lines: Vec<Vec<char>> = art.lines().map(|line| line.chars().collect()).collect();
+    let height = lines.len();
+    let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+    let mut text = format!(
+        "ASCII art of an image of {} by {} pixels, {} columns by {} rows.",
+        source.0, source.1, width, height
+    );
+    if width == 0 || height == 0 {
+        return text;
+    }
+
+    // Brightness from 0 for blank to 1 for bright, over the whole art and each region
+    let mut totals = [(0.0f32, 0u32); 9];
+    for (y, line) in lines.iter().enumerate() {
+        for x in 0..width {
+            let glyph = line.get(x).copied().unwrap_or(' ');
+            let region = (y * 3 / height) * 3 + x * 3 / width;
+            totals[region].0 += level(glyph) as f32 / 2.0;
+            totals[region].1 += 1;
+        }
+    }
+    let overall = totals.iter().map(|total| total.0).sum::<f32>() / (width * height) as f32;
+    // Art narrower or shorter than three cells leaves some regions empty
+    let regions: Vec<(&str, f32)> = REGIONS
+        .iter()
+        .zip(totals)
+        .filter(|(_, (_, count))| *count > 0)
+        .map(|(&name, (sum, count))| (name, sum / count as f32))
+        .collect();
+
+    text.push(' ');
+    text.push_str(match overall {
+        overall if overall < 1.0 / 3.0 => "Mostly dark",
+        overall if overall > 2.0 / 3.0 => "Mostly bright",
+        _ => "Mixed light and dark",
+    });
+    let brightest = regions.iter().map(|region| region.1).fold(0.0, f32::max);
+    let darkest = regions.iter().map(|region| region.1).fold(1.0, f32::min);
+    if brightest - darkest <= REGION_MARGIN {
+        text.push_str(", evenly throughout.");
+        return text;
+    }
+    let near = |target: f32| {
+        let names: Vec<&str> = regions
+            .iter()
+            .filter(|region| (region.1 - target).abs() <= REGION_MARGIN)
+            .map(|region| region.0)
+            .collect();
+        match names.split_last() {
+            Some((last, [])) => last.to_string(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+            None => String::new(),
+        }
+    };
+    text.push_str(&format!(
+        ", brightest at the {}, darkest at the {}.",
+        near(brightest),
+        near(darkest)
+    ));
+    text
+}
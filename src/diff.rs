@@ -0,0 +1,125 @@
+use crate::art::ArtBuffer;
+use crate::color::{CellColor, ColorMode};
+use crate::converter::{AsciiOptions, PixelSource};
+use crate::temporal::TemporalFilter;
+
+/// Drawn over changed cells when there are no colors to highlight them with.
+pub const CHANGED: char = '×';
+
+/// Background of changed cells, and the tint of changed half blocks.
+const HIGHLIGHT: [u8; 3] = [200, 0, 0];
+
+/// Two images converted on the same grid, drawn as the second one with the cells that
+/// differ from the first highlighted.
+///
+/// ```
+/// use ascii_gen::{color::ColorMode, converter::AsciiOptions, diff::ArtDiff};
+/// use image::{DynamicImage, Rgb, RgbImage};
+/// use ascii_gen::converter::ImageConverter;
+///
+/// let before = ImageConverter::from_image(DynamicImage::new_rgb8(40, 20));
+/// let mut changed = RgbImage::new(40, 20);
+/// changed.put_pixel(0, 0, Rgb([255, 255, 255]));
+/// let after = ImageConverter::from_image(DynamicImage::ImageRgb8(changed));
+///
+/// let options = AsciiOptions::new(40, 20, 1.0);
+/// let diff = ArtDiff::new(&before, &after, &options, ColorMode::None, 16);
+/// assert_eq!((diff.changed, diff.cells), (1, 800));
+/// assert!(diff.art.starts_with(ascii_gen::diff::CHANGED));
+/// ```
+pub struct ArtDiff {
+    pub art: String,
+    /// Colors of the cells, empty without a color mode.
+    pub colors: Vec<CellColor>,
+    /// Cells where any channel of any pixel moved by more than the threshold.
+    pub changed: usize,
+    pub cells: usize,
+}
+
+impl ArtDiff {
+    /// Converts both images with `options` and compares the colors sampled for each cell,
+    /// ignoring changes of `threshold` or less in every channel, e.g. compression noise.
+    ///
+    /// With colors, unchanged cells are dimmed and changed ones get a red background, or
+    /// turn red in half-block mode. Without, changed cells are drawn as `CHANGED`.
+    pub fn new(
+        before: &impl PixelSource,
+        after: &impl PixelSource,
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+        threshold: u8,
+    ) -> Self {
+        let before = convert(before, options, color_mode);
+        let mut after = convert(after, options, color_mode);
+
+        let width = options.width().max(1) as usize;
+        let differs = |pixel: usize| {
+            let (old, new) = (before.sampled()[pixel], after.sampled()[pixel]);
+            old.iter()
+                .zip(new)
+                .any(|(old, new)| old.abs_diff(new) > threshold)
+        };
+        let cells = (options.width() * options.height()) as usize;
+        let changed: Vec<bool> = (0..cells)
+            .map(|cell| match color_mode {
+                // Each cell shows two rows of pixels
+                ColorMode::Halfblock => {
+                    let top = (cell / width) * 2 * width + cell % width;
+                    differs(top) || differs(top + width)
+                }
+                _ => differs(cell),
+            })
+            .collect();
+
+        let mut marks = changed.iter();
+        let art = after
+            .art
+            .chars()
+            .map(|c| match c {
+                '\n' => c,
+                _ if marks.next() == Some(&true) && !color_mode.is_enabled() => CHANGED,
+                _ => c,
+            })
+            .collect();
+
+        for (cell, &changed) in after.colors.iter_mut().zip(&changed) {
+            *cell = match (changed, cell.bg) {
+                (true, Some(bg)) => CellColor {
+                    fg: tint(cell.fg),
+                    bg: Some(tint(bg)),
+                },
+                (true, None) => CellColor {
+                    fg: cell.fg,
+                    bg: Some(HIGHLIGHT),
+                },
+                (false, bg) => CellColor {
+                    fg: dim(cell.fg),
+                    bg: bg.map(dim),
+                },
+            };
+        }
+
+        Self {
+            art,
+            colors: after.colors,
+            changed: changed.iter().filter(|&&changed| changed).count(),
+            cells,
+        }
+    }
+}
+
+fn convert(source: &impl PixelSource, options: &AsciiOptions, color_mode: ColorMode) -> ArtBuffer {
+    let mut art = ArtBuffer::default();
+    art.convert(source, options, color_mode, &mut TemporalFilter::new(0));
+    art
+}
+
+/// Halves the brightness, so the changed cells stand out.
+fn dim(rgb: [u8; 3]) -> [u8; 3] {
+    rgb.map(|channel| channel / 2)
+}
+
+/// Mixes the color halfway towards `HIGHLIGHT`.
+fn tint(rgb: [u8; 3]) -> [u8; 3] {
+    std::array::from_fn(|i| ((rgb[i] as u16 + HIGHLIGHT[i] as u16) / 2) as u8)
+}
@@ -0,0 +1,401 @@
+use crate::audio;
+use crate::config::{self, Defaults};
+use crate::keymap::KeyMap;
+use ascii_gen::caps::ColorSupport;
+use ascii_gen::charset;
+use ascii_gen::stream;
+use crossterm::{cursor, style::Print, terminal, ExecutableCommand};
+use ffmpeg_next as ffmpeg;
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use std::fmt::Write;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// Decoders most videos need, with the names people know them by.
+const DECODERS: [(ffmpeg::codec::Id, &str); 4] = [
+    (ffmpeg::codec::Id::H264, "H.264"),
+    (ffmpeg::codec::Id::HEVC, "H.265"),
+    (ffmpeg::codec::Id::VP9, "VP9"),
+    (ffmpeg::codec::Id::AV1, "AV1"),
+];
+
+/// Glyphs of the blocks charset and half block mode, which fonts and terminals most often
+/// get wrong.
+const GLYPHS: [char; 3] = ['█', '░', '▀'];
+
+/// How bad a finding is.
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Ok,
+    /// Something works less well than it could, such as video without sound
+    Warn,
+    /// Something does not work at all
+    Fail,
+}
+
+/// One finding, with what to do about it unless it is fine.
+struct Check {
+    area: &'static str,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(area: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            area,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(area: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            area,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(area: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Fail,
+            ..Self::warn(area, detail, fix)
+        }
+    }
+}
+
+/// Checks the ffmpeg libraries, audio output, terminal and config file, returning a report
+/// with a fix for every problem found.
+pub fn run(config: Option<&Path>) -> String {
+    let mut checks = ffmpeg_checks();
+    checks.push(audio_check());
+    checks.extend(terminal_checks());
+    checks.extend(unicode_checks());
+    checks.push(config_check(config));
+
+    let mut report = String::new();
+    for check in &checks {
+        let status = match check.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        let _ = writeln!(report, "{:<9}{:<6}{}", check.area, status, check.detail);
+        if let Some(fix) = &check.fix {
+            let _ = writeln!(report, "{:<9}{:<6}{}", "", "fix:", fix);
+        }
+    }
+    let problems = checks
+        .iter()
+        .filter(|check| check.status != Status::Ok)
+        .count();
+    let _ = match problems {
+        0 => writeln!(report, "\nEverything looks fine."),
+        1 => writeln!(report, "\n1 problem found."),
+        _ => writeln!(report, "\n{} problems found.", problems),
+    };
+    report
+}
+
+/// Whether the ffmpeg libraries load, match the versions ascii-gen was built against and
+/// decode the usual codecs.
+fn ffmpeg_checks() -> Vec<Check> {
+    let install = if cfg!(target_os = "macos") {
+        "install ffmpeg with `brew install ffmpeg`"
+    } else if cfg!(windows) {
+        "put the ffmpeg 7 shared build's DLLs next to ascii-gen or on PATH"
+    } else {
+        "install your distribution's ffmpeg libraries, e.g. `apt install ffmpeg`"
+    };
+    if let Err(e) = ffmpeg::init() {
+        return vec![Check::fail(
+            "ffmpeg",
+            format!("cannot start: {}", e),
+            install,
+        )];
+    }
+
+    // The runtime versions pack major, minor and micro into one number
+    let libraries = [
+        (
+            "libavformat",
+            ffmpeg::format::version(),
+            ffmpeg::ffi::LIBAVFORMAT_VERSION_MAJOR,
+        ),
+        (
+            "libavcodec",
+            ffmpeg::codec::version(),
+            ffmpeg::ffi::LIBAVCODEC_VERSION_MAJOR,
+        ),
+        (
+            "libavutil",
+            ffmpeg::util::version(),
+            ffmpeg::ffi::LIBAVUTIL_VERSION_MAJOR,
+        ),
+        (
+            "libswscale",
+            ffmpeg::software::scaling::version(),
+            ffmpeg::ffi::LIBSWSCALE_VERSION_MAJOR,
+        ),
+    ];
+    let mut checks = Vec::new();
+    let versions: Vec<String> = libraries
+        .iter()
+        .map(|(name, version, _)| {
+            let (major, minor, micro) = (version >> 16, (version >> 8) & 0xff, version & 0xff);
+            format!("{} {}.{}.{}", name, major, minor, micro)
+        })
+        .collect();
+    match libraries
+        .iter()
+        .find(|(_, version, built)| version >> 16 != *built)
+    {
+        Some((name, version, built)) => checks.push(Check::fail(
+            "ffmpeg",
+            format!(
+                "{} is version {}, but ascii-gen was built against {}",
+                name,
+                version >> 16,
+                built
+            ),
+            format!(
+                "{}, or build ascii-gen again against the installed ones",
+                install
+            ),
+        )),
+        None => checks.push(Check::ok("ffmpeg", versions.join(", "))),
+    }
+
+    let missing: Vec<&str> = DECODERS
+        .iter()
+        .filter(|(id, _)| ffmpeg::decoder::find(*id).is_none())
+        .map(|(_, name)| *name)
+        .collect();
+    if missing.is_empty() {
+        let names: Vec<&str> = DECODERS.iter().map(|(_, name)| *name).collect();
+        checks.push(Check::ok("codecs", format!("decodes {}", names.join(", "))));
+    } else {
+        checks.push(Check::warn(
+            "codecs",
+            format!("cannot decode {}", missing.join(", ")),
+            format!("{}, rather than a minimal build", install),
+        ));
+    }
+
+    if stream::camera_supported() {
+        checks.push(Check::ok("camera", "ffmpeg can read capture devices"));
+    } else {
+        checks.push(Check::warn(
+            "camera",
+            "ffmpeg was built without the capture input, so `cam` cannot open cameras",
+            format!("{} with libavdevice", install),
+        ));
+    }
+    checks
+}
+
+/// Whether an audio device opens, as videos play without sound otherwise.
+fn audio_check() -> Check {
+    let default_host = rodio::cpal::default_host();
+    let default_device = default_host
+        .default_output_device()
+        .and_then(|device| device.name().ok());
+    let devices: usize = rodio::cpal::available_hosts()
+        .into_iter()
+        .filter_map(|id| rodio::cpal::host_from_id(id).ok())
+        .filter_map(|host| host.output_devices().ok())
+        .map(Iterator::count)
+        .sum();
+
+    let fix = if cfg!(target_os = "macos") {
+        "pick an output device in System Settings > Sound"
+    } else if cfg!(windows) {
+        "enable an output device in Sound settings; over remote desktop, let it play audio on \
+         this computer"
+    } else {
+        "start PipeWire or PulseAudio, or add your user to the audio group for ALSA"
+    };
+    match (audio::output(), default_device) {
+        (Ok(_), Some(name)) => Check::ok(
+            "audio",
+            format!("plays through {} ({})", name, default_host.id().name()),
+        ),
+        (Ok(_), None) => Check::ok("audio", "no default device, but another one plays"),
+        (Err(e), _) if devices == 0 => Check::warn(
+            "audio",
+            format!("no output device ({}); videos play without sound", e),
+            fix,
+        ),
+        (Err(e), _) => Check::warn(
+            "audio",
+            format!(
+                "none of {} devices opens ({}); videos play without sound",
+                devices, e
+            ),
+            fix,
+        ),
+    }
+}
+
+/// Whether the output is a terminal, and how many colors it shows.
+fn terminal_checks() -> Vec<Check> {
+    let mut checks = Vec::new();
+    if !io::stdout().is_terminal() {
+        checks.push(Check::warn(
+            "terminal",
+            "the output is not a terminal, so it cannot be checked",
+            "run `ascii-gen doctor` in the terminal videos play in, without a pipe",
+        ));
+        return checks;
+    }
+    #[cfg(windows)]
+    if !crossterm::ansi_support::supports_ansi() {
+        checks.push(Check::fail(
+            "terminal",
+            "the console does not take escape codes",
+            "use Windows Terminal, or Windows 10 or later",
+        ));
+    }
+    let size = terminal::size()
+        .map(|(columns, rows)| format!("{}x{}", columns, rows))
+        .unwrap_or_else(|_| "unknown size".to_string());
+    let term = std::env::var("TERM").unwrap_or_default();
+    checks.push(Check::ok(
+        "terminal",
+        format!(
+            "{}, TERM={}",
+            size,
+            if term.is_empty() { "(unset)" } else { &term }
+        ),
+    ));
+
+    let support = ColorSupport::detect();
+    let colors = support.best().name();
+    checks.push(match support {
+        ColorSupport::Truecolor => Check::ok("colors", format!("{}, every color shows", colors)),
+        ColorSupport::None if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) => {
+            Check::warn(
+                "colors",
+                "none, as NO_COLOR is set",
+                "unset NO_COLOR, or give --color always",
+            )
+        }
+        ColorSupport::None => Check::warn(
+            "colors",
+            format!("none, as TERM={} shows no colors", term),
+            "set TERM to your terminal's entry, such as xterm-256color",
+        ),
+        _ => Check::warn(
+            "colors",
+            format!(
+                "{}, so colors are rounded to the nearest ones it has",
+                colors
+            ),
+            "if the terminal shows 24-bit color, set COLORTERM=truecolor",
+        ),
+    });
+    checks
+}
+
+/// Whether the locale is UTF-8 and the terminal draws block characters one column wide.
+fn unicode_checks() -> Vec<Check> {
+    let mut checks = Vec::new();
+    // Windows consoles take UTF-8 whatever the locale
+    if cfg!(unix) {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        let utf8 = locale
+            .to_ascii_lowercase()
+            .replace('-', "")
+            .contains("utf8");
+        checks.push(if utf8 {
+            Check::ok("locale", locale)
+        } else {
+            Check::warn(
+                "locale",
+                format!(
+                    "{:?} is not UTF-8, so block characters may not show",
+                    locale
+                ),
+                "set LANG to a UTF-8 locale such as en_US.UTF-8, or give --ascii-only",
+            )
+        });
+    }
+
+    if !io::stdout().is_terminal() || !io::stdin().is_terminal() {
+        return checks;
+    }
+    let mut wrong = Vec::new();
+    for glyph in GLYPHS {
+        match drawn_width(glyph) {
+            Ok(width) if usize::from(width) == charset::glyph_width(glyph) => {}
+            Ok(width) => wrong.push(format!("{} is {} columns wide", glyph, width)),
+            Err(e) => {
+                checks.push(Check::warn(
+                    "unicode",
+                    format!("cannot measure glyphs: {}", e),
+                    "check that `█░▀` shows as three solid, shaded and half blocks",
+                ));
+                return checks;
+            }
+        }
+    }
+    checks.push(if wrong.is_empty() {
+        let glyphs: String = GLYPHS.iter().collect();
+        Check::ok("unicode", format!("{} draw one column wide", glyphs))
+    } else {
+        Check::warn(
+            "unicode",
+            wrong.join(", "),
+            "set ambiguous-width characters to narrow in the terminal's settings, use a font \
+             with block elements, or give --ascii-only",
+        )
+    });
+    checks
+}
+
+/// Columns the terminal moves the cursor by to draw `glyph`, found by drawing it at the
+/// start of a line and asking where the cursor ended up.
+fn drawn_width(glyph: char) -> io::Result<u16> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    let measured = stdout
+        .execute(cursor::MoveToColumn(0))
+        .and_then(|stdout| stdout.execute(Print(glyph)))
+        .and_then(|_| cursor::position());
+    let cleared = stdout
+        .execute(cursor::MoveToColumn(0))
+        .and_then(|stdout| stdout.execute(terminal::Clear(terminal::ClearType::CurrentLine)));
+    terminal::disable_raw_mode()?;
+    cleared?;
+    measured.map(|(column, _)| column)
+}
+
+/// Whether the config file and key bindings load.
+fn config_check(config: Option<&Path>) -> Check {
+    let path = config
+        .map(Path::to_path_buf)
+        .or_else(config::path)
+        .map_or_else(
+            || "no config file".to_string(),
+            |path| path.display().to_string(),
+        );
+    let loaded = Defaults::load(config)
+        .map(|_| ())
+        .and_then(|()| KeyMap::load(config).map(|_| ()));
+    match loaded {
+        Ok(()) => Check::ok("config", path),
+        Err(e) => Check::fail(
+            "config",
+            format!("{}: {}", path, e),
+            "fix the setting named, or move the file away to start from the defaults",
+        ),
+    }
+}
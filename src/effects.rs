@@ -0,0 +1,243 @@
+use crate::converter::PixelSource;
+use crate::random::XorShift;
+use image::{imageops::FilterType, DynamicImage, Pixel};
+use serde::{Deserialize, Serialize};
+
+/// Frames per second the stateful effects are stepped at, whatever the display rate.
+const STEP_RATE: f64 = 30.0;
+
+/// A procedural animation, drawn without any source file.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Effect {
+    /// Green trails falling down the screen
+    Rain,
+    /// Flames rising from the bottom
+    Fire,
+    /// Slowly cycling waves of color
+    Plasma,
+}
+
+/// One falling trail of the rain effect.
+struct Trail {
+    /// Row of the head, negative while it is still above the screen.
+    head: f32,
+    /// Rows per step.
+    speed: f32,
+}
+
+/// Draws an effect frame by frame into an RGB canvas, which converts like any other source.
+///
+/// ```
+/// use ascii_gen::{art::ArtBuffer, color::ColorMode, converter::AsciiOptions};
+/// use ascii_gen::{effects::{Effect, EffectCanvas}, temporal::TemporalFilter};
+///
+/// let mut canvas = EffectCanvas::new(Effect::Plasma, 80, 24);
+/// canvas.draw(1.5);
+/// let options = AsciiOptions::new(80, 24, 1.0);
+/// let mut art = ArtBuffer::default();
+/// art.convert(&canvas, &options, ColorMode::None, &mut TemporalFilter::new(0));
+/// assert_eq!(art.art.lines().count(), 24);
+/// ```
+pub struct EffectCanvas {
+    effect: Effect,
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 3]>,
+    /// Heat of each pixel of the fire, from 0 (cold) to 255.
+    heat: Vec<u8>,
+    trails: Vec<Trail>,
+    /// The logo drawn over the effect, `None` where it is transparent.
+    logo: Vec<Option<[u8; 3]>>,
+    /// Steps taken so far by the stateful effects.
+    steps: u64,
+    rng: XorShift,
+}
+
+impl EffectCanvas {
+    /// A canvas of `width` x `height` pixels, usually one per cell of the art.
+    pub fn new(effect: Effect, width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut canvas = Self {
+            effect,
+            width,
+            height,
+            pixels: vec![[0; 3]; (width * height) as usize],
+            heat: vec![0; (width * height) as usize],
+            trails: Vec::new(),
+            logo: Vec::new(),
+            steps: 0,
+            rng: XorShift::default(),
+        };
+        canvas.trails = canvas.new_trails();
+        canvas
+    }
+
+    /// Draws the randomness of rain and fire from `seed`, so runs with the same seed look
+    /// the same.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = XorShift::new(seed);
+        self.trails = self.new_trails();
+        self
+    }
+
+    /// A trail for every column, starting above the canvas at random heights.
+    fn new_trails(&mut self) -> Vec<Trail> {
+        (0..self.width)
+            .map(|_| Trail {
+                head: -(self.random(self.height * 2) as f32),
+                speed: self.trail_speed(),
+            })
+            .collect()
+    }
+
+    /// Draws `logo` over the effect, stretched to the canvas like any converted image.
+    /// Transparent pixels, or black ones in images without alpha, let the effect through.
+    pub fn with_logo(mut self, logo: &DynamicImage) -> Self {
+        let has_alpha = logo.color().has_alpha();
+        let resized = image::imageops::resize(
+            &logo.to_rgba8(),
+            self.width,
+            self.height,
+            FilterType::Triangle,
+        );
+        self.logo = resized
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                let dark = !has_alpha && pixel.to_luma().0[0] < 16;
+                (a >= 128 && !dark).then_some([r, g, b])
+            })
+            .collect();
+        self
+    }
+
+    /// Draws the effect as it looks `time` seconds in. Rain and fire carry over from the
+    /// previous frame, so time should only move forward.
+    pub fn draw(&mut self, time: f64) {
+        match self.effect {
+            Effect::Plasma => self.draw_plasma(time),
+            Effect::Rain | Effect::Fire => {
+                let target = (time.max(0.0) * STEP_RATE) as u64;
+                // Catch up a few steps at most after a stall rather than all at once
+                self.steps = self.steps.max(target.saturating_sub(4));
+                while self.steps < target {
+                    match self.effect {
+                        Effect::Rain => self.step_rain(),
+                        _ => self.step_fire(),
+                    }
+                    self.steps += 1;
+                }
+                if self.effect == Effect::Fire {
+                    for (pixel, &heat) in self.pixels.iter_mut().zip(&self.heat) {
+                        *pixel = fire_color(heat);
+                    }
+                }
+            }
+        }
+
+        for (pixel, logo) in self.pixels.iter_mut().zip(&self.logo) {
+            if let Some(logo) = logo {
+                *pixel = *logo;
+            }
+        }
+    }
+
+    /// Fades every trail and moves the heads down, starting a new trail once one is gone.
+    fn step_rain(&mut self) {
+        for pixel in &mut self.pixels {
+            *pixel = [0, pixel[1].saturating_sub(pixel[1] / 8 + 2), 0];
+        }
+
+        let width = self.width as usize;
+        for x in 0..width {
+            let trail = &self.trails[x];
+            let (from, to) = (trail.head, trail.head + trail.speed);
+            for y in (from.max(0.0) as u32)..(to.max(0.0) as u32).min(self.height) {
+                self.pixels[y as usize * width + x] = [0, 255, 0];
+            }
+            let head = to as i64;
+            if (0..self.height as i64).contains(&head) {
+                self.pixels[head as usize * width + x] = [200, 255, 200];
+            }
+
+            if to > self.height as f32 * 1.5 {
+                let restart = -(self.random(self.height) as f32);
+                let speed = self.trail_speed();
+                self.trails[x] = Trail {
+                    head: restart,
+                    speed,
+                };
+            } else {
+                self.trails[x].head = to;
+            }
+        }
+    }
+
+    fn trail_speed(&mut self) -> f32 {
+        0.3 + self.random(100) as f32 / 100.0
+    }
+
+    /// Spreads the heat of each pixel to the one above, cooling it a little on the way.
+    fn step_fire(&mut self) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        // Flames reach about halfway up whatever the height
+        let cooling = (1024 / height).clamp(1, 255) as u32;
+        self.heat[(height - 1) * width..].fill(255);
+
+        for y in 1..height {
+            for x in 0..width {
+                let random = self.random(cooling + 1);
+                let heat = self.heat[y * width + x];
+                // Drift sideways now and then, as flames flicker
+                let drift = (random & 1) as usize;
+                let above = (y - 1) * width + (x + width - drift) % width;
+                self.heat[above] = heat.saturating_sub(random as u8);
+            }
+        }
+    }
+
+    fn draw_plasma(&mut self, time: f64) {
+        let (width, height) = (self.width as f64, self.height as f64);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // The same waves at any size
+                let (u, v) = (x as f64 / width * 16.0, y as f64 / height * 16.0);
+                let value = (u + time).sin()
+                    + (v * 0.8 + time * 1.3).sin()
+                    + ((u + v) * 0.6 + time * 0.7).sin()
+                    + ((u * u + v * v).sqrt() - time * 2.0).sin();
+                let phase = value * std::f64::consts::PI / 4.0;
+                let channel = |offset: f64| ((phase + offset).sin() * 127.5 + 127.5) as u8;
+                self.pixels[(y * self.width + x) as usize] =
+                    [channel(0.0), channel(2.094), channel(4.189)];
+            }
+        }
+    }
+
+    /// A pseudo-random number below `bound`, from the seeded generator.
+    fn random(&mut self, bound: u32) -> u32 {
+        self.rng.below(u64::from(bound)) as u32
+    }
+}
+
+/// Black through red, orange and yellow to white.
+fn fire_color(heat: u8) -> [u8; 3] {
+    let heat = heat as u32;
+    [
+        (heat * 3).min(255) as u8,
+        (heat.saturating_sub(85) * 3).min(255) as u8,
+        (heat.saturating_sub(170) * 3).min(255) as u8,
+    ]
+}
+
+impl PixelSource for EffectCanvas {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn rgb(&self, x: u32, y: u32) -> [u8; 3] {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
@@ -0,0 +1,95 @@
+use crate::converter::OptionsError;
+#[cfg(feature = "video")]
+use ffmpeg_next as ffmpeg;
+use std::{io, path::PathBuf};
+
+/// Everything that can go wrong loading, converting or playing a file.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum AsciiGenError {
+    #[error("cannot read {}: {source}", .path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("cannot write {}: {source}", .path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("cannot decode {}: {source}", .path.display())]
+    Decode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    #[error("the source has no pixels")]
+    EmptySource,
+    /// An image that would take more memory than allowed once decoded.
+    #[error(
+        "{} is {width}x{height} pixels, too large to decode within {} MiB",
+        .path.display(),
+        .max_memory / (1024 * 1024)
+    )]
+    TooLarge {
+        path: PathBuf,
+        width: u32,
+        height: u32,
+        max_memory: u64,
+    },
+    /// A raw frame smaller than its size and stride say it should be.
+    #[error("frame buffer holds {actual} bytes but {expected} are needed")]
+    BufferSize { expected: usize, actual: usize },
+    /// A raw frame whose rows overlap, being closer together than a row of pixels is long.
+    #[error("frame rows are {stride} bytes apart but each holds {row}")]
+    Stride { stride: usize, row: usize },
+    /// Video frames the scaler cannot convert to RGB, such as ones left in GPU memory.
+    #[cfg(feature = "video")]
+    #[error("cannot convert video frames from the {0} pixel format")]
+    PixelFormat(String),
+    #[cfg(feature = "video")]
+    #[error("cannot play video: {0}")]
+    Video(#[from] ffmpeg::Error),
+    /// No audio device, or an audio track rodio cannot decode.
+    #[error("cannot play audio: {0}")]
+    Audio(String),
+    #[error("invalid options: {0}")]
+    Options(#[from] OptionsError),
+    /// A bad preset, key binding or config file.
+    #[error("{0}")]
+    Config(String),
+    /// A mapping script that does not compile or lacks its `map` function.
+    #[error("invalid script: {0}")]
+    Script(String),
+    #[error("terminal error: {0}")]
+    Terminal(#[from] io::Error),
+    /// Text too long for a QR code at the chosen level.
+    #[error("cannot encode the QR code: {0}")]
+    Qr(String),
+    /// A webhook that did not take the art. Only the scheme and host of its URL are shown,
+    /// as the rest is the webhook's secret.
+    #[error("cannot post to {site}: {reason}")]
+    Post { site: String, reason: String },
+    /// A font file that is not TrueType or OpenType.
+    #[error("invalid font: {0}")]
+    Font(String),
+    /// Stopped through a `ConversionHandle`.
+    #[error("the conversion was cancelled")]
+    Cancelled,
+    /// A worker thread, such as video or audio playback, that panicked instead of finishing.
+    #[error("the {0} thread panicked")]
+    Panicked(&'static str),
+}
+
+impl AsciiGenError {
+    /// Process exit code for the error, so scripts can tell failures apart.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Terminal(_) | Self::Cancelled | Self::Panicked(_) => 1,
+            Self::Options(_) | Self::Config(_) | Self::Script(_) | Self::Qr(_) => 2,
+            Self::Read { .. } | Self::Write { .. } | Self::Post { .. } => 3,
+            Self::Decode { .. }
+            | Self::EmptySource
+            | Self::TooLarge { .. }
+            | Self::BufferSize { .. }
+            | Self::Stride { .. }
+            | Self::Font(_) => 4,
+            #[cfg(feature = "video")]
+            Self::Video(_) | Self::PixelFormat(_) => 4,
+            Self::Audio(_) => 5,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::converter::{self, AsciiOptions, PixelSource};
+use crate::curve::ToneCurve;
+use crate::histogram::Histogram;
+use std::time::Duration;
+
+/// Largest side, in pixels, of the grid a frame is sampled at to measure its exposure.
+const SAMPLE_SIZE: u32 = 64;
+/// Time the exposure takes to move about two thirds of the way to a new scene's, slow
+/// enough that flashes and pans do not make the art pump.
+const ADAPTATION: Duration = Duration::from_millis(800);
+/// Smallest and largest gamma the exposure puts on top of the options'.
+const GAMMA_BOUNDS: (f32, f32) = (0.4, 2.5);
+/// Narrowest luminance range stretched to the full range, so nearly flat frames such as
+/// fades to black are not blown up into noise.
+const MIN_RANGE: f32 = 64.0;
+/// Steps the gamma and the black and white points are rounded to, so the conversion is
+/// only planned again once the exposure visibly changed.
+const GAMMA_STEP: f32 = 0.05;
+const LEVEL_STEP: f32 = 4.0;
+
+/// Exposure of a video adjusted frame by frame: a gamma that brings each frame's median to
+/// mid gray and a contrast stretch between its darkest and brightest few percent, both
+/// eased towards each new frame's over time.
+///
+/// ```
+/// use ascii_gen::converter::{AsciiOptions, ImageConverter};
+/// use ascii_gen::exposure::AutoExposure;
+/// use std::time::Duration;
+///
+/// // A dark scene, from black to a dim gray
+/// let image = image::RgbImage::from_fn(64, 32, |x, _| image::Rgb([x as u8 / 2; 3]));
+/// let frame = ImageConverter::from_image(image.into());
+///
+/// let mut exposure = AutoExposure::default();
+/// exposure.measure(&frame, Duration::from_millis(40));
+/// let options = exposure.apply(&AsciiOptions::default());
+/// assert!(options.gamma() < 1.0);
+/// ```
+#[derive(Default)]
+pub struct AutoExposure {
+    histogram: Histogram,
+    pixels: Vec<[u8; 3]>,
+    /// The eased exposure, once a frame was measured.
+    exposure: Option<Exposure>,
+}
+
+#[derive(Clone, Copy)]
+struct Exposure {
+    gamma: f32,
+    /// Base luminance shown as black and as white.
+    black: f32,
+    white: f32,
+}
+
+impl AutoExposure {
+    /// Measures a frame shown for `elapsed` since the last one and moves the exposure
+    /// towards it. The first frame is taken as it is.
+    pub fn measure(&mut self, source: &impl PixelSource, elapsed: Duration) {
+        let (width, height) = source.dimensions();
+        let scale = (SAMPLE_SIZE as f32 / width.max(height).max(1) as f32).min(1.0);
+        source.to_color_grid_into(
+            &mut self.pixels,
+            ((width as f32 * scale) as u32).max(1),
+            ((height as f32 * scale) as u32).max(1),
+        );
+        self.histogram.fill(
+            self.pixels
+                .iter()
+                .map(|&rgb| converter::base_luminance(rgb)),
+        );
+        let target = self.target();
+
+        self.exposure = Some(match self.exposure {
+            Some(current) => {
+                let weight = 1.0 - libm::expf(-elapsed.as_secs_f32() / ADAPTATION.as_secs_f32());
+                let ease = |from: f32, to: f32| from + (to - from) * weight;
+                Exposure {
+                    gamma: ease(current.gamma, target.gamma),
+                    black: ease(current.black, target.black),
+                    white: ease(current.white, target.white),
+                }
+            }
+            None => target,
+        });
+    }
+
+    /// The exposure the last measured frame asks for on its own.
+    fn target(&self) -> Exposure {
+        let mut black = self.histogram.percentile(0.02) as f32;
+        let mut white = self.histogram.percentile(0.98) as f32;
+        if white - black < MIN_RANGE {
+            let center = ((black + white) / 2.0).clamp(MIN_RANGE / 2.0, 255.0 - MIN_RANGE / 2.0);
+            black = black.min(center - MIN_RANGE / 2.0);
+            white = white.max(center + MIN_RANGE / 2.0);
+        }
+
+        // Solve median ^ gamma = 0.5 within the stretched range
+        let median = (self.histogram.percentile(0.5) as f32 - black) / (white - black);
+        let gamma = match median {
+            median if median <= 0.0 || median >= 1.0 => 1.0,
+            median => (libm::logf(0.5) / libm::logf(median)).clamp(GAMMA_BOUNDS.0, GAMMA_BOUNDS.1),
+        };
+        Exposure {
+            gamma,
+            black,
+            white,
+        }
+    }
+
+    /// The options with the exposure's gamma multiplied into theirs and its contrast
+    /// stretch put in front of their tone curve, or the options as they are before any
+    /// frame was measured.
+    pub fn apply(&self, options: &AsciiOptions) -> AsciiOptions {
+        let Some(exposure) = self.exposure else {
+            return options.clone();
+        };
+        let gamma = (options.gamma() * exposure.gamma / GAMMA_STEP).round() * GAMMA_STEP;
+        let options = options.clone().with_gamma(gamma);
+
+        // The curve sees luminance after gamma, so the black and white points go through it
+        let level = |base: f32| {
+            let corrected = converter::gamma_correct(base as u8, options.gamma()) as f32;
+            (corrected / LEVEL_STEP).round() * LEVEL_STEP
+        };
+        let (black, white) = (level(exposure.black), level(exposure.white));
+        if white - black < LEVEL_STEP {
+            return options;
+        }
+        let curve = options.curve();
+        let levels = std::array::from_fn(|point| {
+            let input = ToneCurve::input(point) as f32;
+            let stretched = ((input - black) / (white - black) * 255.0).clamp(0.0, 255.0);
+            curve.apply(stretched.round() as u8)
+        });
+        options.with_curve(ToneCurve::new(levels))
+    }
+}
@@ -0,0 +1,261 @@
+use crate::cli::{Args, OnEnd};
+use crate::{init_terminal, interrupted, App, Tui, VideoInput};
+use ascii_gen::converter::AsciiOptions;
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::stream::{self, AsciiFrame, VideoConverter};
+use ascii_gen::widget::{self, AsciiArtWidget};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{prelude::*, widgets::*};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the wall is drawn, whatever the sources' frame rates.
+const DRAW_INTERVAL: Duration = Duration::from_millis(33);
+/// How often a waiting pipeline checks whether the wall closed or was resumed.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What a source's pipeline shares with the screen.
+struct Tile {
+    /// Options for the next frames, sized to the tile.
+    options: AsciiOptions,
+    /// The newest frame, until the screen takes it.
+    frame: Option<AsciiFrame>,
+    /// The tile's status line.
+    status: String,
+}
+
+/// Plays every source at once, tiled `columns` to a row, each converted on its own thread
+/// to the size of its tile. Files play without sound; Space pauses them all.
+pub fn run(sources: &[String], columns: usize, args: &Args) -> Result<(), AsciiGenError> {
+    let running = Arc::new(Mutex::new(true));
+    let paused = Arc::new(Mutex::new(false));
+    let options = App::new(args).options();
+    let tiles: Vec<Arc<Mutex<Tile>>> = sources
+        .iter()
+        .map(|_| {
+            Arc::new(Mutex::new(Tile {
+                options: options.clone(),
+                frame: None,
+                status: "starting".to_string(),
+            }))
+        })
+        .collect();
+
+    let mut terminal = init_terminal(args.tui.record.as_deref())?;
+    let threads: Vec<_> = sources
+        .iter()
+        .zip(&tiles)
+        .map(|(source, tile)| {
+            let (input, args) = (input(source), args.clone());
+            let (tile, running, paused) = (tile.clone(), running.clone(), paused.clone());
+            std::thread::spawn(move || {
+                let status = match play(&input, &args, &tile, &running, &paused) {
+                    Ok(()) => "ended".to_string(),
+                    Err(e) => format!("error: {}", e),
+                };
+                tile.lock().unwrap().status = status;
+            })
+        })
+        .collect();
+
+    let shown = show(&mut terminal, sources, columns, &tiles, &paused, args);
+    *running.lock().unwrap() = false;
+    // The terminal is given back however the wall closed
+    let restored = terminal.restore();
+    // Pipelines notice they should stop once their current frame is done
+    for thread in threads {
+        let _ = thread.join();
+    }
+    shown?;
+    Ok(restored?)
+}
+
+/// Draws the tiles until the wall is closed with `q` or interrupted.
+fn show(
+    terminal: &mut Tui,
+    sources: &[String],
+    columns: usize,
+    tiles: &[Arc<Mutex<Tile>>],
+    paused: &Mutex<bool>,
+    args: &Args,
+) -> Result<(), AsciiGenError> {
+    let mut shown: Vec<AsciiFrame> = sources.iter().map(|_| AsciiFrame::default()).collect();
+    while !interrupted() {
+        let draw_start = Instant::now();
+        let areas = widget::tile(layout(terminal.size()?).0, sources.len(), columns);
+        let mut statuses = Vec::with_capacity(sources.len());
+        for ((tile, shown), &area) in tiles.iter().zip(&mut shown).zip(&areas) {
+            let mut tile = tile.lock().unwrap();
+            let (width, height) = pane_size(area);
+            tile.options = tile.options.clone().with_size(width, height);
+            if let Some(frame) = tile.frame.take() {
+                *shown = frame;
+            }
+            statuses.push(tile.status.clone());
+        }
+        let is_paused = *paused.lock().unwrap();
+
+        terminal.draw(|frame| {
+            let (area, help) = layout(frame.size());
+            let areas = widget::tile(area, sources.len(), columns);
+            for (((source, shown), status), &area) in
+                sources.iter().zip(&shown).zip(&statuses).zip(&areas)
+            {
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(source.as_str())
+                    .title(block::Title::from(status.as_str()).position(block::Position::Bottom));
+                frame.render_widget(
+                    AsciiArtWidget::new(&shown.art)
+                        .colors(&shown.colors, args.convert.color)
+                        .block(block),
+                    area,
+                );
+            }
+            let help_text = if is_paused {
+                " paused | space resumes | q quits"
+            } else {
+                " space pauses | q quits"
+            };
+            frame.render_widget(
+                Paragraph::new(help_text).style(Style::default().add_modifier(Modifier::REVERSED)),
+                help,
+            );
+        })?;
+
+        if event::poll(DRAW_INTERVAL.saturating_sub(draw_start.elapsed()))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char(' ') => {
+                            let mut paused = paused.lock().unwrap();
+                            *paused = !*paused;
+                        }
+                        KeyCode::Esc | KeyCode::Char('q') => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Plays one source into its tile until the wall closes, or it ends without `--on-end loop`.
+fn play(
+    input: &VideoInput,
+    args: &Args,
+    tile: &Mutex<Tile>,
+    running: &Mutex<bool>,
+    paused: &Mutex<bool>,
+) -> Result<(), AsciiGenError> {
+    loop {
+        let stream = input.open(&args.playback)?;
+        let mut converter = VideoConverter::new(stream, tile.lock().unwrap().options.clone())
+            .with_color_mode(args.convert.color)
+            .with_temporal_smooth(args.playback.temporal_smooth)
+            .with_auto_expose(args.playback.auto_expose);
+        if let Some(mapper) = args.mapper.clone() {
+            converter = converter.with_mapper(mapper);
+        }
+        let mut start_time = Instant::now();
+        converter.set_start_time(Some(start_time));
+        let (mut second_start, mut frames, mut frame_rate) = (Instant::now(), 0u32, 0.0);
+
+        while *running.lock().unwrap() {
+            if *paused.lock().unwrap() {
+                let pause_start = Instant::now();
+                while *paused.lock().unwrap() && *running.lock().unwrap() {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                start_time += pause_start.elapsed();
+                converter.set_start_time(Some(start_time));
+            }
+
+            let options = tile.lock().unwrap().options.clone();
+            converter.set_options(options, args.convert.color);
+            let Some(frame) = converter.next() else {
+                break;
+            };
+            let frame = frame?;
+            if let Some(timestamp) = frame.timestamp {
+                sleep_while_running(timestamp.saturating_sub(start_time.elapsed()), running);
+            }
+
+            frames += 1;
+            if second_start.elapsed() >= Duration::from_secs(1) {
+                frame_rate = f64::from(frames) / second_start.elapsed().as_secs_f64();
+                (second_start, frames) = (Instant::now(), 0);
+            }
+            let status = match frame.timestamp {
+                Some(timestamp) => format!(
+                    "{} | {:.1} fps | {} dropped",
+                    stream::format_timestamp(timestamp.as_secs_f64()),
+                    frame_rate,
+                    converter.dropped_frames()
+                ),
+                None => format!("{:.1} fps", frame_rate),
+            };
+
+            let mut tile = tile.lock().unwrap();
+            tile.status = status;
+            if let Some(old) = tile.frame.replace(frame) {
+                converter.recycle(old);
+            }
+        }
+
+        if !*running.lock().unwrap() || args.playback.on_end != OnEnd::Loop {
+            return Ok(());
+        }
+    }
+}
+
+/// Sleeps for `duration`, waking early once the wall closes.
+fn sleep_while_running(duration: Duration, running: &Mutex<bool>) {
+    let end = Instant::now() + duration;
+    while *running.lock().unwrap() {
+        let left = end.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        std::thread::sleep(left.min(POLL_INTERVAL));
+    }
+}
+
+/// What to play for a source argument: `cam0`, `cam1` and so on are cameras by number,
+/// device paths such as `/dev/video2` are cameras too, and anything else is a video file.
+fn input(source: &str) -> VideoInput {
+    if Path::new(source).is_file() {
+        return VideoInput::File(source.to_string());
+    }
+    if let Some(index) = source
+        .strip_prefix("cam")
+        .and_then(|index| index.parse::<u32>().ok())
+    {
+        return VideoInput::Camera(stream::camera_device(index));
+    }
+    if source.starts_with("/dev/") {
+        VideoInput::Camera(source.to_string())
+    } else {
+        VideoInput::File(source.to_string())
+    }
+}
+
+/// Cells of art fitting inside a tile's border, in the tile's own shape.
+fn pane_size(area: Rect) -> (u32, u32) {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    (
+        AsciiOptions::clamp_size(u32::from(inner.width)),
+        AsciiOptions::clamp_size(u32::from(inner.height)),
+    )
+}
+
+/// Splits the screen into the wall and the help line below it.
+fn layout(area: Rect) -> (Rect, Rect) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+        .split(area);
+    (rows[0], rows[1])
+}
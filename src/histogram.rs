@@ -0,0 +1,132 @@
+#[cfg(feature = "tui")]
+use crate::charset::Charset;
+#[cfg(feature = "tui")]
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    widgets::{Block, Widget},
+};
+
+/// Bar characters from empty to full, in eighths of a cell.
+#[cfg(feature = "tui")]
+const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How many converted cells fall on each luminance value.
+#[derive(Clone)]
+pub struct Histogram {
+    counts: [u32; 256],
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self { counts: [0; 256] }
+    }
+}
+
+impl Histogram {
+    /// Recounts the histogram from the luminance of every cell.
+    pub fn fill(&mut self, luminance: impl IntoIterator<Item = u8>) {
+        self.counts = [0; 256];
+        for value in luminance {
+            self.counts[value as usize] += 1;
+        }
+    }
+
+    /// Average luminance, or 0 when nothing was counted.
+    pub fn mean(&self) -> u8 {
+        let total: u64 = self.counts.iter().map(|&count| count as u64).sum();
+        let sum: u64 = (0..256u64)
+            .zip(&self.counts)
+            .map(|(v, &c)| v * c as u64)
+            .sum();
+        sum.checked_div(total).unwrap_or(0) as u8
+    }
+
+    /// The luminance below which `fraction` of the cells fall.
+    pub fn percentile(&self, fraction: f32) -> u8 {
+        let total: u64 = self.counts.iter().map(|&count| count as u64).sum();
+        let target = (total as f32 * fraction.clamp(0.0, 1.0)) as u64;
+        let mut seen = 0;
+        for (value, &count) in self.counts.iter().enumerate() {
+            seen += count as u64;
+            if seen > target {
+                return value as u8;
+            }
+        }
+        255
+    }
+
+    /// Sums the counts into `buckets` equally wide ranges of luminance.
+    #[cfg(feature = "tui")]
+    fn buckets(&self, buckets: usize) -> Vec<u32> {
+        let mut sums = vec![0; buckets];
+        for (value, &count) in self.counts.iter().enumerate() {
+            sums[value * buckets / 256] += count;
+        }
+        sums
+    }
+}
+
+/// Draws a histogram as vertical bars, with markers where the charset switches characters.
+#[cfg(feature = "tui")]
+pub struct HistogramWidget<'a> {
+    histogram: &'a Histogram,
+    charset: Charset,
+    block: Option<Block<'a>>,
+}
+
+#[cfg(feature = "tui")]
+impl<'a> HistogramWidget<'a> {
+    pub fn new(histogram: &'a Histogram, charset: Charset) -> Self {
+        Self {
+            histogram,
+            charset,
+            block: None,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Widget for HistogramWidget<'_> {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        let area = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        if area.width == 0 || area.height < 2 {
+            return;
+        }
+
+        // The last row holds the threshold markers
+        let width = area.width as usize;
+        let bar_rows = area.height as usize - 1;
+        let buckets = self.histogram.buckets(width);
+        let max = buckets.iter().copied().max().unwrap_or(0).max(1) as usize;
+
+        for (column, &count) in buckets.iter().enumerate() {
+            let mut eighths = count as usize * bar_rows * 8 / max;
+            for row in (0..bar_rows).rev() {
+                let fill = eighths.min(8);
+                eighths -= fill;
+                buf.get_mut(area.x + column as u16, area.y + row as u16)
+                    .set_char(BARS[fill]);
+            }
+        }
+
+        let marker_y = area.bottom() - 1;
+        for level in 1..self.charset.chars().len() {
+            let (lower, _) = self.charset.bounds(level);
+            let column = lower as usize * width / 256;
+            buf.get_mut(area.x + column as u16, marker_y).set_char('^');
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+/// Something a key can be bound to in the live editor or the video player.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Undo,
+    Redo,
+    PreviousField,
+    NextField,
+    /// Lowers the selected option, or steps back through its choices.
+    Decrease,
+    Increase,
+    /// Starts typing a number into the selected option, or confirms on `Confirm`.
+    Select,
+    /// Links width and height edits through the source's aspect ratio.
+    ToggleAspectLock,
+    ToggleCompare,
+    /// Shows the camera, size and capture date of the photo next to the art.
+    ToggleMetadata,
+    OpenFile,
+    SaveArt,
+    SavePreset,
+    NextPreset,
+    /// Copies the art to the clipboard, only while paused in the video player.
+    Copy,
+    PanLeft,
+    PanDown,
+    PanUp,
+    PanRight,
+    ZoomIn,
+    ZoomOut,
+    /// Goes back to converting the whole source after cropping it with the mouse.
+    ClearCrop,
+    /// Leaves the editor printing the art, whichever option is selected.
+    Finish,
+    Quit,
+    /// Saves the art as an ANSI text file.
+    Screenshot,
+    /// Saves the decoded video frame as a PNG.
+    SaveFrame,
+    ToggleStats,
+    /// Shows the frequencies of the audio playing next to the video.
+    ToggleSpectrum,
+    TogglePause,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// A key together with the modifiers held down, e.g. `Ctrl+r`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // The case of a character already says whether Shift was held
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    fn plain(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// Parses names like `q`, `Up`, `Shift+Left` or `Ctrl+r`.
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = text;
+        // Split on `+` only before the last character so `+` itself stays a key
+        while let Some((modifier, rest)) = name.split_once('+').filter(|(_, rest)| !rest.is_empty())
+        {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier in key: {}", text)),
+            };
+            name = rest;
+        }
+
+        let code = match name {
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Enter" => KeyCode::Enter,
+            "Esc" => KeyCode::Esc,
+            "Tab" => KeyCode::Tab,
+            "Backspace" => KeyCode::Backspace,
+            "Space" => KeyCode::Char(' '),
+            _ if name.chars().count() == 1 => KeyCode::Char(name.chars().next().unwrap()),
+            _ => match name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+                Some(n) => KeyCode::F(n),
+                None => return Err(format!("unknown key: {}", text)),
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The key bindings of the live editor and the video player.
+pub struct KeyMap {
+    editor: HashMap<Key, Action>,
+    video: HashMap<Key, Action>,
+}
+
+/// The `[keys]` tables of `~/.config/ascii-gen/config.toml`.
+#[derive(Serialize, Deserialize, Default)]
+struct KeysFile {
+    #[serde(default)]
+    editor: BTreeMap<String, Action>,
+    #[serde(default)]
+    video: BTreeMap<String, Action>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct ConfigFile {
+    #[serde(default)]
+    keys: KeysFile,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        use Action::*;
+
+        let editor = [
+            (Key::plain('u'), Undo),
+            (Key::new(KeyCode::Char('r'), KeyModifiers::CONTROL), Redo),
+            (Key::new(KeyCode::Up, KeyModifiers::NONE), PreviousField),
+            (Key::new(KeyCode::Down, KeyModifiers::NONE), NextField),
+            (Key::new(KeyCode::Left, KeyModifiers::NONE), Decrease),
+            (Key::new(KeyCode::Right, KeyModifiers::NONE), Increase),
+            (Key::new(KeyCode::Enter, KeyModifiers::NONE), Select),
+            (Key::new(KeyCode::Tab, KeyModifiers::NONE), ToggleCompare),
+            (Key::plain('i'), ToggleMetadata),
+            (Key::plain('L'), ToggleAspectLock),
+            (Key::plain('o'), OpenFile),
+            (Key::plain('w'), SaveArt),
+            (Key::plain('P'), SavePreset),
+            (Key::plain('p'), NextPreset),
+            (Key::plain('y'), Copy),
+            (Key::plain('h'), PanLeft),
+            (Key::plain('j'), PanDown),
+            (Key::plain('k'), PanUp),
+            (Key::plain('l'), PanRight),
+            (Key::plain('+'), ZoomIn),
+            (Key::plain('='), ZoomIn),
+            (Key::plain('-'), ZoomOut),
+            (Key::plain('c'), ClearCrop),
+            (Key::new(KeyCode::Char('s'), KeyModifiers::CONTROL), Finish),
+            (Key::plain('q'), Quit),
+            (Key::new(KeyCode::Esc, KeyModifiers::NONE), Quit),
+            (Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL), Quit),
+        ];
+        let video = [
+            (Key::plain('q'), Quit),
+            (Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL), Quit),
+            (Key::plain('s'), Screenshot),
+            (Key::plain('S'), SaveFrame),
+            (Key::plain('i'), ToggleStats),
+            (Key::plain('v'), ToggleSpectrum),
+            (Key::plain(' '), TogglePause),
+            (Key::plain('y'), Copy),
+            (Key::plain('h'), ScrollLeft),
+            (Key::plain('j'), ScrollDown),
+            (Key::plain('k'), ScrollUp),
+            (Key::plain('l'), ScrollRight),
+            (Key::new(KeyCode::Up, KeyModifiers::NONE), PreviousField),
+            (Key::new(KeyCode::Down, KeyModifiers::NONE), NextField),
+            (Key::new(KeyCode::Left, KeyModifiers::NONE), Decrease),
+            (Key::new(KeyCode::Right, KeyModifiers::NONE), Increase),
+            (Key::new(KeyCode::Enter, KeyModifiers::NONE), Select),
+            (Key::plain('L'), ToggleAspectLock),
+        ];
+
+        Self {
+            editor: editor.into_iter().collect(),
+            video: video.into_iter().collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Loads the default bindings, overridden by any in the config file.
+    pub fn load(config: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut keymap = Self::default();
+        let Some(contents) = crate::config::read(config)? else {
+            return Ok(keymap);
+        };
+
+        let config: ConfigFile = toml::from_str(&contents)?;
+        for (key, action) in config.keys.editor {
+            keymap.editor.insert(Key::parse(&key)?, action);
+        }
+        for (key, action) in config.keys.video {
+            keymap.video.insert(Key::parse(&key)?, action);
+        }
+        Ok(keymap)
+    }
+
+    /// The editor action bound to a key press.
+    pub fn editor_action(&self, key: KeyEvent) -> Option<Action> {
+        Self::lookup(&self.editor, key)
+    }
+
+    /// The video player action bound to a key press.
+    pub fn video_action(&self, key: KeyEvent) -> Option<Action> {
+        Self::lookup(&self.video, key)
+    }
+
+    /// Finds the binding, falling back to the key without Shift so Shift+arrows still
+    /// reach the arrow bindings.
+    fn lookup(bindings: &HashMap<Key, Action>, key: KeyEvent) -> Option<Action> {
+        bindings
+            .get(&Key::new(key.code, key.modifiers))
+            .or_else(|| bindings.get(&Key::new(key.code, key.modifiers - KeyModifiers::SHIFT)))
+            .copied()
+    }
+
+    /// Formats the active bindings in the config file's format.
+    pub fn dump(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let names = |bindings: &HashMap<Key, Action>| {
+            bindings
+                .iter()
+                .map(|(key, &action)| (key.to_string(), action))
+                .collect()
+        };
+        let config = ConfigFile {
+            keys: KeysFile {
+                editor: names(&self.editor),
+                video: names(&self.video),
+            },
+        };
+        Ok(toml::to_string_pretty(&config)?)
+    }
+}
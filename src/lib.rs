@@ -0,0 +1,67 @@
+//! Converts images and video frames to ASCII art.
+//!
+//! ```
+//! use ascii_gen::converter::{AsciiOptions, ImageConverter, ToAsciiArt};
+//!
+//! let image = image::DynamicImage::new_rgb8(64, 32);
+//! let options = AsciiOptions::builder().size(16, 8).build()?;
+//! let art = ImageConverter::from_image(image).to_ascii_art(Some(options))?;
+//! assert_eq!(art.lines().count(), 8);
+//! # Ok::<(), ascii_gen::error::AsciiGenError>(())
+//! ```
+//!
+//! Video decoding needs the `video` feature and the ratatui widgets the `tui` feature, both
+//! on by default through `cli`, while `async_stream` needs the `async` feature. Without them
+//! the converter builds for wasm32, where the `wasm` feature exports it to JavaScript. QR
+//! codes are encoded with the `qr` feature.
+
+pub mod ansi;
+pub mod art;
+#[cfg(feature = "async")]
+pub mod async_stream;
+pub mod batch;
+pub mod caps;
+pub mod charset;
+pub mod color;
+pub mod converter;
+pub mod curve;
+#[cfg(feature = "video")]
+pub mod deinterlace;
+pub mod describe;
+pub mod diff;
+pub mod effects;
+pub mod error;
+pub mod exposure;
+pub mod histogram;
+pub mod metadata;
+pub mod metrics;
+pub mod output;
+pub mod palette;
+pub mod pattern;
+pub mod plan;
+pub mod progress;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod random;
+pub mod raster;
+pub mod rotation;
+#[cfg(feature = "video")]
+pub mod scenes;
+#[cfg(feature = "script")]
+pub mod script;
+#[cfg(feature = "video")]
+pub mod sheet;
+pub mod spectrum;
+#[cfg(feature = "video")]
+pub mod stream;
+pub mod temporal;
+pub mod tone;
+#[cfg(feature = "video")]
+pub mod track;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "tui")]
+pub mod widget;
+
+#[cfg(feature = "video")]
+pub use ffmpeg_next as ffmpeg;
@@ -0,0 +1,71 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    sync::{atomic::Ordering, Mutex},
+};
+use tracing::Level;
+use tracing_subscriber::{
+    filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt, Layer, Registry,
+};
+
+/// Another layer to install next to the log output, such as the `--profile-stages` timings.
+pub type ExtraLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Log output written while the TUI has the terminal, printed once it is restored.
+static HELD: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+/// Writes to stderr, holding the output back while the alternate screen would hide it.
+struct Stderr;
+
+impl Write for Stderr {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if crate::TERMINAL_ACTIVE.load(Ordering::Relaxed) {
+            HELD.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        } else {
+            io::stderr().write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// Logs at warnings and above, with each `-v` adding info, debug and then per-frame traces.
+pub fn init(verbose: u8, log_file: Option<&Path>, extra: Option<ExtraLayer>) -> io::Result<()> {
+    let level = match verbose {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    let output = tracing_subscriber::fmt::layer();
+    let output = match log_file {
+        Some(path) => output
+            .with_ansi(false)
+            .with_writer(Mutex::new(File::create(path)?))
+            .with_filter(LevelFilter::from_level(level))
+            .boxed(),
+        None => output
+            .with_writer(|| Stderr)
+            .with_filter(LevelFilter::from_level(level))
+            .boxed(),
+    };
+
+    tracing_subscriber::registry()
+        .with(extra)
+        .with(output)
+        .init();
+    Ok(())
+}
+
+/// Prints the log output held back while the TUI was running.
+pub fn flush_held() {
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = io::stderr().write_all(&held);
+    held.clear();
+}
@@ -1,17 +1,72 @@
-use crate::converter::ToAsciiArt;
-use clap::Parser;
-use ffmpeg_next as ffmpeg;
-use image::{ImageBuffer, Luma};
-use rodio::{self};
+use crate::browser::{BrowserAction, FileBrowser};
+use crate::cli::{Args, Cli, Command, OnEnd};
+use crate::config::Defaults;
+use crate::control::{ControlCommand, ControlServer, Transport, SPEED_BOUNDS};
+use crate::keymap::{Action, KeyMap};
+use crate::preset::{Preset, PresetStore};
+use crate::recorder::SessionWriter;
+use crate::stats::PlaybackStats;
+use crate::tap::Tap;
+use crate::watch::FileWatcher;
+use crate::worker::{ConversionJob, ConversionResult, ConversionWorker, SampleCache};
+use ascii_gen::ansi;
+use ascii_gen::art::ArtBuffer;
+use ascii_gen::batch;
+use ascii_gen::caps::ColorSupport;
+use ascii_gen::charset::{Charset, Ramp, SharedMapper};
+use ascii_gen::color::ColorMode;
+use ascii_gen::converter::PixelSource;
+use ascii_gen::curve::{ToneCurve, CONTROL_POINTS};
+use ascii_gen::describe;
+use ascii_gen::diff::ArtDiff;
+use ascii_gen::effects::{Effect, EffectCanvas};
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::histogram::HistogramWidget;
+use ascii_gen::metadata::ImageMetadata;
+use ascii_gen::metrics::{self, Fidelity};
+use ascii_gen::output::OutputFormat;
+use ascii_gen::palette::{self, Palette, Swatch};
+use ascii_gen::plan::Upscale;
+use ascii_gen::progress::ConversionHandle;
+use ascii_gen::qr::{QrImage, QrLevel};
+use ascii_gen::random;
+use ascii_gen::raster::{self, Rasterizer};
+use ascii_gen::scenes;
+use ascii_gen::script::ScriptMapper;
+use ascii_gen::sheet::{self, ContactSheet};
+use ascii_gen::spectrum::Spectrum;
+use ascii_gen::stream::{self, AsciiFrame, VideoAsciiStream, VideoConverter};
+use ascii_gen::temporal::TemporalFilter;
+use ascii_gen::widget::{AsciiArtWidget, SpectrumWidget};
+use ascii_gen::{color, converter, output};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use image::DynamicImage;
+use rodio::{self, Source};
 
 use std::{
-    io::{self, stdout, BufReader, Stdout},
-    sync::{Arc, Mutex},
+    io::{self, stdout, BufReader, IsTerminal},
+    net::SocketAddr,
+    ops::{Deref, DerefMut},
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
 
 use crossterm::{
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
     ExecutableCommand,
 };
 
@@ -20,179 +75,2739 @@ use ratatui::{
     widgets::{canvas::*, *},
 };
 
-mod converter;
+mod audio;
+mod bench;
+mod browser;
+mod charset_edit;
+mod cli;
+mod clipboard;
+mod compare;
+mod config;
+mod control;
+mod doctor;
+mod grid;
+mod keymap;
+mod logging;
+mod now_playing;
+mod pager;
+mod preset;
+#[cfg(feature = "profiling")]
+mod profile;
+mod recorder;
+mod screenshot;
+#[cfg(feature = "serve")]
+mod serve;
+mod serve_movie;
+mod stats;
+mod tap;
+mod watch;
+mod webhook;
+mod worker;
 
-#[derive(Parser, Clone, Debug)]
-#[command(author,version,about,long_about = None)]
-pub struct Args {
-    /// The path to an image file
-    #[arg(long, default_value = "")]
-    file: String,
-    /// The width of the ASCII art
-    #[arg(long, default_value = "160")]
+/// Set on Ctrl+C or a panic so the editor and the playback threads wind down.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the TUI has the terminal, so Ctrl+C should stop it rather than exit right away.
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The name of the thread playing a video's sound, whose panics leave the video playing.
+const AUDIO_THREAD: &str = "audio";
+
+/// Wait before joining a dropped live stream again, doubled with every try up to the longest.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// How long a stream has to play for the next drop to start counting tries from one again.
+const RECONNECT_RESET: Duration = Duration::from_secs(60);
+
+/// Number of edits that can be undone.
+const MAX_HISTORY: usize = 100;
+
+/// Largest magnification of the live preview.
+const MAX_ZOOM: u32 = 64;
+
+/// Columns printed art takes when neither the terminal nor `$COLUMNS` says.
+const DEFAULT_COLUMNS: u32 = 80;
+
+/// The cells `palette` colors to show a swatch.
+const SWATCH: &str = "      ";
+
+/// What the video player plays.
+#[derive(Clone)]
+pub enum VideoInput {
+    /// A video file, played with its audio.
+    File(String),
+    /// A capture device, played without audio.
+    Camera(String),
+}
+
+impl VideoInput {
+    /// Whether the input is read over the network.
+    fn is_url(&self) -> bool {
+        matches!(self, VideoInput::File(file) if stream::is_url(file))
+    }
+
+    /// Opens the input with the playback flags' deinterlacing, rotation, frame rate and
+    /// network timeout.
+    fn open(&self, playback: &cli::PlaybackArgs) -> Result<VideoAsciiStream, AsciiGenError> {
+        let autorotate = !playback.no_autorotate;
+        let mut stream = match self {
+            VideoInput::File(url) if stream::is_url(url) => {
+                VideoAsciiStream::open_url(url, playback.timeout, playback.deinterlace, autorotate)?
+            }
+            VideoInput::File(file) => {
+                VideoAsciiStream::open(file, playback.deinterlace, autorotate)?
+            }
+            VideoInput::Camera(device) => VideoAsciiStream::open_camera(device)?,
+        };
+        stream.set_frame_rate(Some(f64::from(playback.frame_rate)));
+        if let Some(speed) = playback.timelapse {
+            stream.set_speed(speed);
+        }
+        Ok(stream)
+    }
+
+    /// Converts the input with the flags given on the command line, for viewers that are
+    /// not this terminal.
+    fn converter(&self, args: &Args) -> Result<VideoConverter, AsciiGenError> {
+        let stream = self.open(&args.playback)?;
+        let mut converter = VideoConverter::new(stream, App::new(args).options())
+            .with_color_mode(args.convert.color)
+            .with_temporal_smooth(args.playback.temporal_smooth)
+            .with_auto_expose(args.playback.auto_expose);
+        if let Some(mapper) = args.mapper.clone() {
+            converter = converter.with_mapper(mapper);
+        }
+        Ok(converter)
+    }
+}
+
+/// How the live editor was left.
+pub enum EditorOutcome {
+    /// Finished with the art to print.
+    Saved(String),
+    /// Quit without printing anything.
+    Cancelled,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::command().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ascii-gen: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run(matches: ArgMatches) -> Result<(), AsciiGenError> {
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    #[cfg(feature = "profiling")]
+    let stages = cli.profile_stages.then(profile::StageTimings::default);
+    #[cfg(feature = "profiling")]
+    let extra = stages
+        .as_ref()
+        .map(|stages| Box::new(stages.layer()) as logging::ExtraLayer);
+    #[cfg(not(feature = "profiling"))]
+    let extra = None;
+    if let Some(path) = &cli.log_file {
+        logging::init(cli.verbose, Some(path), extra).map_err(|source| AsciiGenError::Write {
+            path: path.clone(),
+            source,
+        })?;
+    } else {
+        logging::init(cli.verbose, None, extra)?;
+    }
+    install_interrupt_handlers().map_err(io::Error::other)?;
+    // Windows consoles take escape codes only once asked to, and conhost before Windows 10
+    // not at all; crossterm falls back to the console API for the editor and the player
+    #[cfg(windows)]
+    if !crossterm::ansi_support::supports_ansi() {
+        tracing::warn!("this console does not take ANSI escape codes, so art is not colored");
+    }
+    let config = cli.config.clone();
+    let command = cli.into_command();
+    let mut args = command.args(config);
+
+    if command
+        .files()
+        .is_some_and(|files| files.paths().is_empty())
+    {
+        return Err(missing_files(matches.subcommand_name()));
+    }
+
+    if let Command::Keys = command {
+        let keys = KeyMap::load(args.config.as_deref())
+            .and_then(|keys| keys.dump())
+            .map_err(|e| AsciiGenError::Config(format!("cannot load key bindings: {}", e)))?;
+        print!("{}", keys);
+        return Ok(());
+    }
+    if let Command::Doctor = command {
+        print!("{}", doctor::run(args.config.as_deref()));
+        return Ok(());
+    }
+
+    // Deterministic art cannot depend on whoever's config file happens to be on the machine,
+    // so only one given with --config is read
+    let user_config = !args.convert.deterministic || args.config.is_some();
+    let defaults = if user_config {
+        Defaults::load(args.config.as_deref())
+            .map_err(|e| AsciiGenError::Config(format!("cannot load the config file: {}", e)))?
+    } else {
+        Defaults::default()
+    };
+    let command_matches = matches
+        .subcommand()
+        .map_or(&matches, |(_, matches)| matches);
+    args.apply_defaults(&defaults, command_matches);
+    if let Some(name) = args.convert.profile.clone() {
+        let profile = if user_config {
+            config::profile(args.config.as_deref(), &name)
+        } else {
+            config::built_in_profile(&name)
+        }
+        .map_err(|e| AsciiGenError::Config(e.to_string()))?;
+        args.apply_profile(&profile, command_matches);
+    }
+
+    if let Some(name) = &args.convert.preset {
+        let presets = PresetStore::load()
+            .map_err(|e| AsciiGenError::Config(format!("cannot load presets: {}", e)))?;
+        let preset = presets
+            .get(name)
+            .ok_or_else(|| AsciiGenError::Config(format!("unknown preset: {}", name)))?
+            .clone();
+        args.apply_preset(&preset);
+    }
+    // Only art drawn in this terminal is held to its colors: files, images and the clients of
+    // the servers keep the mode asked for, and `--color always` trusts this terminal
+    let forced_color = command_matches
+        .try_get_raw("color")
+        .ok()
+        .flatten()
+        .is_some_and(|mut values| values.any(|value| value == "always"));
+    let printing = stdout().is_terminal() && args.convert.format == OutputFormat::Ansi;
+    let in_terminal = command.draws_in_terminal(printing);
+    // Deterministic art cannot depend on the terminal it happens to be made in
+    if args.convert.deterministic && args.convert.fit {
+        return Err(AsciiGenError::Config(
+            "--fit sizes the art to the terminal, which --deterministic rules out".to_string(),
+        ));
+    }
+    // `auto` was resolved against this terminal while parsing, so it is fixed here instead
+    let auto_color = command_matches
+        .try_get_raw("color")
+        .ok()
+        .flatten()
+        .is_some_and(|mut values| values.any(|value| value.eq_ignore_ascii_case("auto")));
+    if args.convert.deterministic && auto_color {
+        args.convert.color = ColorMode::Truecolor;
+    }
+    if !forced_color && in_terminal && !args.convert.deterministic {
+        args.color_support = ColorSupport::detect();
+        args.convert.color = args.convert.color.downgrade(args.color_support);
+        tracing::debug!(support = ?args.color_support, color = args.convert.color.name());
+    }
+    if args.convert.ascii_only {
+        args.convert.charset = args.convert.charset.ascii();
+        if args.convert.color == ColorMode::Halfblock {
+            args.convert.color = ColorMode::Truecolor.downgrade(args.color_support);
+        }
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.convert.threads)
+        .build_global()
+        .map_err(|e| AsciiGenError::Config(format!("cannot start threads: {}", e)))?;
+    if let Some(path) = &args.convert.script {
+        args.mapper = Some(Arc::new(ScriptMapper::load(path)?));
+    }
+    if let Some(name) = &args.convert.ramp {
+        if !user_config {
+            return Err(AsciiGenError::Config(format!(
+                "the charset {} is saved in the config file, which --deterministic reads only \
+                 when given with --config",
+                name
+            )));
+        }
+        let ramp = config::ramp(args.config.as_deref(), name)
+            .map_err(|e| AsciiGenError::Config(e.to_string()))?;
+        if args.convert.ascii_only && !ramp.is_ascii() {
+            return Err(AsciiGenError::Config(format!(
+                "the charset {} is not ASCII only, as --ascii-only asks",
+                name
+            )));
+        }
+        args.mapper = Some(Arc::new(ramp));
+    }
+    // Screen readers get words instead of escape codes and a wall of punctuation
+    if args.convert.accessible {
+        args.convert.color = ColorMode::None;
+        args.convert.format = OutputFormat::Text;
+        let levels = Ramp::new(describe::LEVELS).expect("the accessible charset is valid");
+        args.mapper = Some(Arc::new(levels));
+    }
+
+    let post_to = args.convert.post_to.clone();
+    let no_pager = args.convert.no_pager || args.convert.accessible;
+    let art = match command {
+        Command::Image { files, .. } => {
+            let files = files.paths();
+            let mut arts = Vec::with_capacity(files.len());
+            for file in &files {
+                let image = converter::open_image_with(file, args.convert.max_memory)?;
+                let source = converter::ImageConverter::from_image(image);
+                arts.push(App::convert_image(
+                    &source,
+                    &args,
+                    &ConversionHandle::default(),
+                )?);
+                if args.convert.score {
+                    eprintln!("{}: {}", file, App::score_image(&source, &args)?);
+                }
+                if args.convert.show_meta {
+                    let metadata = ImageMetadata::read(file).unwrap_or_default();
+                    let lines = metadata.lines(source.dimensions());
+                    eprintln!("{}: {}", file, lines.join(", "));
+                }
+            }
+            Some(arts.join("\n"))
+        }
+        Command::Live { files, watch, .. } => {
+            let files = files.paths();
+            let mut images = Vec::with_capacity(files.len());
+            for file in files {
+                let image = converter::open_image_with(&file, args.convert.max_memory)?;
+                images.push((file, image));
+            }
+            match App::run(images, args, watch)? {
+                EditorOutcome::Saved(art) => Some(art),
+                EditorOutcome::Cancelled => None,
+            }
+        }
+        Command::Video {
+            files,
+            scenes,
+            scene_threshold,
+            ..
+        } => {
+            let mut files = files.paths();
+            if files.len() > 1 {
+                return Err(AsciiGenError::Config(
+                    "video plays a single file".to_string(),
+                ));
+            }
+            let input = VideoInput::File(files.remove(0));
+            if scenes {
+                Some(scene_report(&input, scene_threshold, &args)?)
+            } else {
+                App::run_video(input, args)?
+            }
+        }
+        Command::Cam { device, .. } => App::run_video(VideoInput::Camera(device), args)?,
+        Command::Grid { sources, cols, .. } => {
+            grid::run(&sources, cols, &args)?;
+            None
+        }
+        Command::Audio { file, .. } => {
+            now_playing::run(&file, &args)?;
+            None
+        }
+        Command::Batch {
+            files,
+            output,
+            jobs,
+            sizes,
+            ..
+        } => {
+            run_batch(&files.paths(), &output, jobs, &sizes, &args)?;
+            None
+        }
+        Command::Analyze { files, apply, .. } => {
+            let mut lines = Vec::new();
+            for file in files.paths() {
+                let image = converter::open_image_with(&file, args.convert.max_memory)?;
+                let source = converter::ImageConverter::from_image(image);
+                let bounds = (args.convert.width, args.convert.height);
+                let analysis = converter::analyze(&source, bounds);
+                lines.push(describe_analysis(&file, &analysis));
+
+                let mut suggested = args.clone();
+                suggested.convert.width = analysis.suggested.width();
+                suggested.convert.height = analysis.suggested.height();
+                suggested.convert.gamma = analysis.suggested.gamma();
+                suggested.convert.charset = analysis.suggested.charset();
+                if args.convert.score {
+                    // Every charset at the suggested size, to see how the suggestion compares
+                    for charset in Charset::all() {
+                        let mut args = suggested.clone();
+                        args.convert.charset = charset;
+                        let fidelity = App::score_image(&source, &args)?;
+                        lines.push(format!("  --charset {}: {}", charset.name(), fidelity));
+                    }
+                }
+                if apply {
+                    lines.push(App::convert_image(
+                        &source,
+                        &suggested,
+                        &ConversionHandle::default(),
+                    )?);
+                }
+            }
+            Some(lines.join("\n"))
+        }
+        Command::Bench {
+            input,
+            widths,
+            frames,
+            ..
+        } => Some(bench::run(input.as_deref(), &widths, frames, &args)?),
+        #[cfg(feature = "serve")]
+        Command::Serve {
+            port,
+            host,
+            videos,
+            cameras,
+            ..
+        } => {
+            serve::run(SocketAddr::new(host, port), videos, cameras, args)?;
+            None
+        }
+        Command::ServeMovie {
+            file,
+            port,
+            host,
+            max_clients,
+            ..
+        } => {
+            serve_movie::run(file, SocketAddr::new(host, port), max_clients, args)?;
+            None
+        }
+        Command::Fx {
+            effect,
+            duration,
+            logo,
+            ..
+        } => {
+            App::run_fx(effect, duration, logo.as_deref(), args)?;
+            None
+        }
+        Command::TestPattern { pattern, .. } => {
+            let image = pattern.image(args.convert.width, args.convert.height);
+            let source = converter::ImageConverter::from_image(image);
+            Some(App::convert_image(
+                &source,
+                &args,
+                &ConversionHandle::default(),
+            )?)
+        }
+        Command::Qr { text, level, .. } => Some(render_qr(&text, level, &args)?),
+        Command::Motd {
+            file,
+            cols,
+            lines,
+            hostname,
+            banner,
+            output,
+            ..
+        } => {
+            let banner = banner.or_else(|| hostname.then(machine_name));
+            let motd = render_motd(&file, (cols, lines), banner.as_deref(), &args)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, motd).map_err(|source| AsciiGenError::Write {
+                        path: path.clone(),
+                        source,
+                    })?;
+                    eprintln!("Saved {}", path.display());
+                    None
+                }
+                None => Some(motd),
+            }
+        }
+        Command::Diff {
+            before,
+            after,
+            threshold,
+            ..
+        } => {
+            let open = |path: &str| {
+                converter::open_image_with(path, args.convert.max_memory)
+                    .map(converter::ImageConverter::from_image)
+            };
+            let (before, after) = (open(&before)?, open(&after)?);
+            let app = App::new(&args);
+            let options = app.options();
+            let diff = ArtDiff::new(
+                &converter::crop(&before, &options)?,
+                &converter::crop(&after, &options)?,
+                &options,
+                app.color_mode,
+                threshold,
+            );
+            eprintln!("{} of {} cells changed", diff.changed, diff.cells);
+            Some(output::render(
+                &diff.art,
+                &diff.colors,
+                app.color_mode,
+                args.convert.format,
+            ))
+        }
+        Command::Sheet {
+            file, cols, rows, ..
+        } => Some(contact_sheet(&file, cols, rows, &args)?),
+        Command::CompareCharsets { file, .. } => {
+            let image = converter::open_image_with(&file, args.convert.max_memory)?;
+            compare::run(&converter::ImageConverter::from_image(image), &args)?
+        }
+        Command::CharsetEdit { name, image, .. } => {
+            let source = image
+                .map(|file| converter::open_image_with(&file, args.convert.max_memory))
+                .transpose()?
+                .map(converter::ImageConverter::from_image);
+            if let Some(path) = charset_edit::run(&name, source.as_ref(), &args)? {
+                eprintln!(
+                    "Saved charset {} to {}; draw with it using --ramp {}",
+                    name,
+                    path.display(),
+                    name
+                );
+            }
+            None
+        }
+        Command::Rasterize {
+            file,
+            output,
+            font,
+            font_size,
+            fg,
+            bg,
+        } => {
+            rasterize_file(&file, &output, font.as_deref(), font_size, fg, bg)?;
+            eprintln!("Saved {}", output.display());
+            None
+        }
+        Command::Palette {
+            file,
+            colors,
+            apply,
+            ..
+        } => {
+            let image = converter::open_image_with(&file, args.convert.max_memory)?;
+            let source = converter::ImageConverter::from_image(image);
+            let seed = args.convert.seed.unwrap_or(random::DEFAULT_SEED);
+            let swatches = palette::extract_seeded(&source, colors, seed);
+            // Without a --color the swatches show in the most colors this terminal does, and
+            // in none when piped
+            if !args.convert.color.is_enabled() && cli::is_unset(command_matches, "color") {
+                args.convert.color = if in_terminal {
+                    ColorMode::Truecolor.downgrade(args.color_support)
+                } else {
+                    ColorMode::None
+                };
+            }
+            let mut text = palette_swatches(&swatches, args.convert.color);
+            if apply {
+                args.convert.palette = Palette::new(swatches.iter().map(|s| s.rgb).collect());
+                text.push('\n');
+                text.push_str(&App::convert_image(
+                    &source,
+                    &args,
+                    &ConversionHandle::default(),
+                )?);
+            }
+            Some(text)
+        }
+        Command::Keys | Command::Doctor => None,
+    };
+    if let Some(art) = art.filter(|art| !art.is_empty()) {
+        if no_pager || !pager::page(&art) {
+            println!("{}", art);
+        }
+        if let Some(url) = &post_to {
+            webhook::post(url, &art)?;
+        }
+    }
+    #[cfg(feature = "profiling")]
+    if let Some(stages) = stages {
+        eprint!("{}", stages.report());
+    }
+    Ok(())
+}
+
+/// Draws a QR code module for module, whatever `--width` and `--height` say, as scanners
+/// need every module intact.
+fn render_qr(text: &str, level: QrLevel, args: &Args) -> Result<String, AsciiGenError> {
+    let code = QrImage::encode(text, level)?;
+    let color_mode = args.convert.color;
+    if !color_mode.is_enabled() {
+        return Ok(code.to_blocks(args.convert.invert));
+    }
+    // Colored half blocks are black on white on any terminal theme
+    let options = converter::AsciiOptions::new(code.width(), code.rows() / 2, 1.0);
+    let (art, colors) = code.to_half_blocks(&options);
+    Ok(output::render(
+        &art,
+        &colors,
+        color_mode,
+        args.convert.format,
+    ))
+}
+
+/// Converts the logo to fit the terminal, or `size` where given, with the banner spelled in
+/// big letters under it. Room is left for the shell prompt after the message.
+fn render_motd(
+    file: &str,
+    size: (Option<u32>, Option<u32>),
+    banner: Option<&str>,
+    args: &Args,
+) -> Result<String, AsciiGenError> {
+    let terminal = terminal::size().ok();
+    let cols = size
+        .0
+        .or_else(|| env_size("COLUMNS"))
+        .or(terminal.map(|(cols, _)| cols as u32))
+        .unwrap_or(DEFAULT_COLUMNS);
+    let lines = size
+        .1
+        .or_else(|| env_size("LINES"))
+        .or(terminal.map(|(_, lines)| lines as u32))
+        .unwrap_or(24);
+
+    let banner = banner.map(raster::banner).unwrap_or_default();
+    let banner_lines = banner.lines().count() as u32;
+    let image = converter::open_image_with(file, args.convert.max_memory)?;
+    let source = converter::ImageConverter::from_image(image);
+    let (width, height) = converter::fit_dimensions(
+        source.dimensions(),
+        (cols, lines.saturating_sub(banner_lines + 1)),
+    );
+
+    let mut args = args.clone();
+    args.convert.width = width;
+    args.convert.height = height;
+    args.convert.fit = false;
+    let mut motd = App::convert_image(&source, &args, &ConversionHandle::default())?;
+    if !motd.ends_with('\n') {
+        motd.push('\n');
+    }
+    // A banner too wide for the terminal would wrap into noise
+    let banner_width = banner.lines().map(|line| line.chars().count()).max();
+    if let Some(banner_width) = banner_width.filter(|&width| width <= cols as usize) {
+        let indent = " ".repeat((width as usize).saturating_sub(banner_width) / 2);
+        for line in banner.lines() {
+            motd.push_str(&format!("{}{}\n", indent, line));
+        }
+    }
+    Ok(motd)
+}
+
+/// A terminal size from an environment variable such as `$COLUMNS`, which shells set but
+/// rarely export.
+fn env_size(name: &str) -> Option<u32> {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse::<u32>().ok())
+        .filter(|&size| size > 0)
+}
+
+/// The grid art printed with `--fit` takes: the terminal less a line for the prompt, or when
+/// printing into a pipe or file, `$COLUMNS` or 80 columns wide and as tall as the image needs.
+fn print_dimensions(source: (u32, u32)) -> (u32, u32) {
+    if stdout().is_terminal() {
+        if let Ok((cols, rows)) = terminal::size() {
+            let bounds = (u32::from(cols), u32::from(rows).saturating_sub(1));
+            return converter::fit_dimensions(source, bounds);
+        }
+    }
+    let cols = converter::AsciiOptions::clamp_size(env_size("COLUMNS").unwrap_or(DEFAULT_COLUMNS));
+    (cols, converter::height_for_width(source, cols))
+}
+
+/// The machine's name, from `$HOSTNAME`, `%COMPUTERNAME%` or `/etc/hostname`.
+fn machine_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+/// Lists the first frame of every scene of the video under its number and timestamp.
+fn scene_report(input: &VideoInput, threshold: u8, args: &Args) -> Result<String, AsciiGenError> {
+    let mut stream = input.open(&args.playback)?;
+    let color_mode = args.convert.color;
+    let found = scenes::detect(
+        &mut stream,
+        &App::new(args).options(),
+        color_mode,
+        threshold,
+    )?;
+
+    let mut report = Vec::with_capacity(found.len());
+    for (index, scene) in found.iter().enumerate() {
+        let art = output::render(&scene.art, &scene.colors, color_mode, args.convert.format);
+        match scene.timestamp {
+            Some(timestamp) => report.push(format!(
+                "Scene {} at {}\n{}",
+                index + 1,
+                stream::format_timestamp(timestamp),
+                art
+            )),
+            None => report.push(format!("Scene {}\n{}", index + 1, art)),
+        }
+    }
+    Ok(report.join("\n"))
+}
+
+/// Prints `cols` x `rows` thumbnails of the video, as large as `--width` and `--height` fit
+/// with a label over each and space between them.
+fn contact_sheet(file: &str, cols: u32, rows: u32, args: &Args) -> Result<String, AsciiGenError> {
+    let (cols, rows) = (cols.max(1), rows.max(1));
+    let mut stream = VideoInput::File(file.to_string()).open(&args.playback)?;
+    // Every frame is sought, none skipped for the frame rate
+    stream.set_frame_rate(None);
+    let dimensions = stream.dimensions();
+
+    let mut thumbnail = args.clone();
+    let width = args.convert.width.saturating_sub(sheet::GAP * (cols - 1)) / cols;
+    let mut height = converter::height_for_width(dimensions, width);
+    // A label over each row of thumbnails and a blank line between rows
+    let max_height = args.convert.height.saturating_sub(2 * rows - 1) / rows;
+    thumbnail.convert.width = width;
+    if height > max_height {
+        height = max_height;
+        thumbnail.convert.width = converter::width_for_height(dimensions, height).min(width);
+    }
+    thumbnail.convert.height = height;
+
+    let color_mode = args.convert.color;
+    let options = App::new(&thumbnail).options();
+    let storyboard = ContactSheet::new(&mut stream, cols, rows, &options, color_mode)?;
+    Ok(output::render(
+        &storyboard.art,
+        &storyboard.colors,
+        color_mode,
+        args.convert.format,
+    ))
+}
+
+/// Lists the swatches as blocks of their color in `color_mode` with their hex codes and
+/// shares, or as the codes and shares alone without colors, then as a `--palette` argument
+/// converting with them.
+fn palette_swatches(swatches: &[Swatch], color_mode: ColorMode) -> String {
+    let mut text = String::new();
+    for swatch in swatches {
+        if color_mode.is_enabled() {
+            let block = color::CellColor {
+                fg: swatch.rgb,
+                bg: Some(swatch.rgb),
+            };
+            text.push_str(&color::to_ansi(SWATCH, &[block; SWATCH.len()], color_mode));
+            text.push(' ');
+        }
+        text.push_str(&format!(
+            "{}  {:5.1}%\n",
+            palette::to_hex(swatch.rgb),
+            swatch.share * 100.0
+        ));
+    }
+    let codes: Vec<String> = swatches.iter().map(|s| palette::to_hex(s.rgb)).collect();
+    text.push_str(&format!("--palette '{}'\n", codes.join(",")));
+    text
+}
+
+/// Draws the art of a text file into an image, keeping the colors of ANSI art.
+fn rasterize_file(
+    file: &str,
+    output: &std::path::Path,
+    font: Option<&std::path::Path>,
+    font_size: f32,
+    fg: [u8; 3],
+    bg: [u8; 3],
+) -> Result<(), AsciiGenError> {
+    let text = std::fs::read_to_string(file).map_err(|source| AsciiGenError::Read {
+        path: file.into(),
+        source,
+    })?;
+    let rasterizer = match font {
+        Some(font) => {
+            let data = std::fs::read(font).map_err(|source| AsciiGenError::Read {
+                path: font.to_path_buf(),
+                source,
+            })?;
+            Rasterizer::with_font(data, font_size)?
+        }
+        None => Rasterizer::default(),
+    }
+    .colors(fg, bg);
+
+    let parsed = ansi::parse(&text, fg, bg);
+    rasterizer
+        .draw(&parsed.art, &parsed.colors)
+        .save(output)
+        .map_err(|e| AsciiGenError::Write {
+            path: output.to_path_buf(),
+            source: io::Error::other(e),
+        })
+}
+
+/// Reports what `analyze` found, with the suggestion as flags to copy.
+fn describe_analysis(file: &str, analysis: &converter::Analysis) -> String {
+    let (width, height) = analysis.dimensions;
+    let (dark, bright) = analysis.luminance_range;
+    let suggested = &analysis.suggested;
+    format!(
+        "{}: {}x{} ({:.2}:1), luminance mean {}, median {}, 5-95% {}-{}\n  \
+         --width {} --height {} --gamma {:.2} --charset {}",
+        file,
+        width,
+        height,
+        analysis.aspect_ratio,
+        analysis.mean_luminance,
+        analysis.median_luminance,
+        dark,
+        bright,
+        suggested.width(),
+        suggested.height(),
+        suggested.gamma(),
+        suggested.charset().name(),
+    )
+}
+
+/// Prints the help of the subcommand that was given no files, and fails.
+fn missing_files(subcommand: Option<&str>) -> AsciiGenError {
+    let mut command = Cli::command();
+    command.build();
+    if let Some(subcommand) = subcommand.and_then(|name| command.find_subcommand(name)) {
+        command = subcommand.clone();
+    }
+    eprintln!("{}", command.render_help());
+    AsciiGenError::Config("no file given".to_string())
+}
+
+/// Converts every image into a file in `output` named after it on `jobs` threads, reporting
+/// failures once all are done and returning the first one.
+fn run_batch(
+    files: &[String],
+    output: &std::path::Path,
+    jobs: usize,
+    sizes: &[(u32, u32)],
+    args: &Args,
+) -> Result<(), AsciiGenError> {
+    // Every output path up front, so two inputs never overwrite each other's art
+    let extension = args.convert.format.extension();
+    let paths: Vec<Vec<PathBuf>> = files
+        .iter()
+        .map(|file| match sizes {
+            [] => vec![batch_output(output, file, &format!(".{}", extension))],
+            sizes => sizes
+                .iter()
+                .map(|(width, height)| {
+                    let suffix = format!("-{}x{}.{}", width, height, extension);
+                    batch_output(output, file, &suffix)
+                })
+                .collect(),
+        })
+        .collect();
+    let mut written_by = std::collections::HashMap::new();
+    for (file, paths) in files.iter().zip(&paths) {
+        for path in paths {
+            if let Some(other) = written_by.insert(path, file) {
+                return Err(AsciiGenError::Config(format!(
+                    "{} and {} would both be written to {}",
+                    other,
+                    file,
+                    path.display()
+                )));
+            }
+        }
+    }
+
+    std::fs::create_dir_all(output).map_err(|source| AsciiGenError::Write {
+        path: output.to_path_buf(),
+        source,
+    })?;
+
+    // Show how far the whole batch is when someone is watching
+    let show_progress = io::stderr().is_terminal() && !args.convert.accessible;
+    // One conversion of each file, or one per size of each file
+    let per_file = sizes.len().max(1);
+    let progress = Arc::new(Mutex::new(vec![0.0; files.len() * per_file]));
+    let handle = |index: usize| {
+        let mut handle = ConversionHandle::new();
+        if show_progress {
+            let progress = progress.clone();
+            handle = handle.on_progress(move |file_progress| {
+                let mut progress = progress.lock().unwrap();
+                progress[index] = file_progress.fraction;
+                let total = progress.iter().sum::<f32>() / progress.len() as f32;
+                eprint!("\rconverting: {:3.0}%", total * 100.0);
+            });
+        }
+        handle
+    };
+
+    let inputs: Vec<_> = files.iter().enumerate().collect();
+    let results = batch::convert_many_with(&inputs, jobs, |&(index, file)| {
+        let image = converter::open_image_with(file, args.convert.max_memory)?;
+        let source = converter::ImageConverter::from_image(image);
+        if sizes.is_empty() {
+            let path = paths[index][0].clone();
+            return write_art(&source, args, &handle(index), path).map(|path| vec![path]);
+        }
+
+        // Every size comes from the image decoded above, converted at the same time
+        let sized: Vec<_> = sizes.iter().enumerate().collect();
+        batch::convert_many_with(&sized, sized.len(), |&(size_index, &(width, height))| {
+            let mut args = args.clone();
+            args.convert.width = width;
+            args.convert.height = height;
+            args.convert.fit = false;
+            write_art(
+                &source,
+                &args,
+                &handle(index * per_file + size_index),
+                paths[index][size_index].clone(),
+            )
+        })
+        .into_iter()
+        .collect()
+    });
+    if show_progress {
+        eprint!("\r\x1b[2K");
+    }
+
+    let mut first_error = None;
+    for (file, result) in files.iter().zip(results) {
+        match result {
+            Ok(paths) => {
+                for path in paths {
+                    println!("{} -> {}", file, path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("ascii-gen: {}: {}", file, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// The file in `output` holding the art of `file`, named after its stem followed by `suffix`.
+fn batch_output(output: &std::path::Path, file: &str, suffix: &str) -> PathBuf {
+    let mut name = std::path::Path::new(file)
+        .file_stem()
+        .unwrap_or_default()
+        .to_os_string();
+    // Pushed rather than set as the extension, which would cut at dots in the stem
+    name.push(suffix);
+    output.join(name)
+}
+
+/// Converts an image and writes its art to `path`, returning the path.
+fn write_art(
+    source: &converter::ImageConverter,
+    args: &Args,
+    handle: &ConversionHandle,
+    path: PathBuf,
+) -> Result<PathBuf, AsciiGenError> {
+    let art = App::convert_image(source, args, handle)?;
+    std::fs::write(&path, art).map_err(|source| AsciiGenError::Write {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Restores the terminal on a panic and stops every thread on a panic or Ctrl+C.
+fn install_interrupt_handlers() -> Result<(), ctrlc::Error> {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // A video whose sound fails plays on silently
+        if std::thread::current().name() == Some(AUDIO_THREAD) {
+            tracing::warn!("audio playback panicked: {}", info);
+            return;
+        }
+        INTERRUPTED.store(true, Ordering::Relaxed);
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
+
+    // In raw mode Ctrl+C arrives as a key press instead, which is bound to quitting
+    ctrlc::set_handler(|| {
+        if !TERMINAL_ACTIVE.load(Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+        // The editor and the player restore the terminal themselves once they stop
+        INTERRUPTED.store(true, Ordering::Relaxed);
+    })
+}
+
+fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+struct App {
+    frame: ArtBuffer,
+    color_mode: ColorMode,
+    /// The colors the terminal shows, limiting the modes the editor cycles through.
+    color_support: ColorSupport,
+    /// Whether only ASCII may be drawn, from `--ascii-only`.
+    ascii_only: bool,
     width: u32,
-    /// The height of the ASCII art
-    #[arg(long, default_value = "90")]
     height: u32,
-    /// The gamma of the ASCII art
-    #[arg(long, default_value = "1.0")]
     gamma: f32,
-    /// The target frame rate
-    #[arg(long, default_value = "60.0")]
-    frame_rate: Option<f32>,
-    /// Whether or not to live edit the ASCII art
-    #[arg(long, default_value = "false")]
-    live: bool,
-}
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-    let result = App::run_video(args.file.clone(), args); // Call video run method
-    println!("{}", result.unwrap());
-    Ok(())
+    charset: Charset,
+    invert: bool,
+    /// Whether the size follows the preview pane.
+    fit: bool,
+    /// Whether editing the width or height updates the other to keep the aspect ratio.
+    aspect_lock: bool,
+    source_dimensions: (u32, u32),
+    crop: Option<(u32, u32, u32, u32)>,
+    curve: ToneCurve,
+    /// Colors the art is snapped to, from `--palette`.
+    palette: Option<Palette>,
+    /// How art larger than the source is filled, from `--upscale`.
+    upscale: Upscale,
+    /// Control point of the curve being dragged, with the settings from before the drag.
+    curve_drag: Option<(usize, EditState)>,
+    /// Script picking the characters instead of the charset, from `--script`.
+    mapper: Option<SharedMapper>,
+    /// Memory images opened in the file browser may take once decoded.
+    max_memory: u64,
+    /// Corners of the crop rectangle being dragged with the mouse, in terminal cells.
+    selection: Option<((u16, u16), (u16, u16))>,
+    terminal_size: Rect,
+    selected_field: Fields,
+    /// Number being typed into the selected field, if any.
+    input: Option<String>,
+    /// Text popup currently open, if any.
+    prompt: Option<Prompt>,
+    presets: PresetStore,
+    keys: KeyMap,
+    preset_name: Option<String>,
+    undo_history: Vec<EditState>,
+    redo_history: Vec<EditState>,
+    /// Image being edited in the live editor.
+    source: Option<Arc<converter::ImageConverter>>,
+    /// Every image open in the live editor; the current one's settings live in `App` itself.
+    tabs: Vec<Tab>,
+    current_tab: usize,
+    /// Whether the art is out of date with the source or settings.
+    dirty: bool,
+    worker: Option<ConversionWorker>,
+    /// Sampled colors of the last conversion done without a worker.
+    samples: SampleCache,
+    /// Generation of the last conversion requested from the worker and of the one shown.
+    requested_generation: u64,
+    shown_generation: u64,
+    browser: Option<FileBrowser>,
+    video_to_play: Option<PathBuf>,
+    /// Whether the user quit the editor instead of confirming.
+    cancelled: bool,
+    /// Whether the source is shown next to the art.
+    compare: bool,
+    original: ArtBuffer,
+    /// How long the art on screen took to convert.
+    conversion_time: Duration,
+    format: OutputFormat,
+    copy_format: OutputFormat,
+    status: Option<String>,
+    stats: PlaybackStats,
+    show_stats: bool,
+    show_metadata: bool,
+    /// Whether video playback is paused, shared with the audio thread.
+    paused: Arc<Mutex<bool>>,
+    /// Seeks and speed changes, shared with the audio thread.
+    transport: Arc<Mutex<Transport>>,
+    /// Frequencies of the audio playing, fed by the audio thread.
+    spectrum: Arc<Mutex<Spectrum>>,
+    /// A message for the status bar from the audio thread.
+    audio_notice: Arc<Mutex<Option<String>>>,
+    /// Whether the video playing is a live stream, known once it opens.
+    live: Option<bool>,
+    /// Tries in a row at joining a dropped live stream again.
+    reconnects: u32,
+    show_spectrum: bool,
+    /// Commands from other processes, with `--control`.
+    control: Option<ControlServer>,
+    /// Where to seek to before the next frame, in seconds as recorded.
+    seek_to: Option<f64>,
+    /// Speed to play at from the next frame on.
+    speed_to: Option<f64>,
+    scroll: (u16, u16),
+    /// Magnification of the preview, re-converting only the visible part of the source.
+    zoom: u32,
+    pan: (u32, u32),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Fields {
+    Width,
+    Height,
+    Gamma,
+    Charset,
+    Invert,
+    Color,
+    Fit,
+    Finish,
+}
+
+/// Snapshot of the editor settings kept for undo and redo.
+type EditState = (converter::AsciiOptions, ColorMode);
+
+/// An image open in the live editor, with its own settings and history.
+struct Tab {
+    name: String,
+    source: Arc<converter::ImageConverter>,
+    /// EXIF tags of the file, if it has any.
+    metadata: Option<ImageMetadata>,
+    state: EditState,
+    undo_history: Vec<EditState>,
+    redo_history: Vec<EditState>,
+}
+
+/// What the text typed into a popup is used for.
+#[derive(Clone, Copy, PartialEq)]
+enum PromptKind {
+    SaveArt,
+    SavePreset,
+}
+
+impl PromptKind {
+    fn title(self) -> &'static str {
+        match self {
+            PromptKind::SaveArt => "Save as",
+            PromptKind::SavePreset => "Preset name",
+        }
+    }
+}
+
+/// A small popup asking for a line of text.
+struct Prompt {
+    kind: PromptKind,
+    text: String,
+}
+
+impl Prompt {
+    fn new(kind: PromptKind) -> Self {
+        Self {
+            kind,
+            text: String::new(),
+        }
+    }
+}
+
+impl Fields {
+    const ALL: [Fields; 8] = [
+        Fields::Width,
+        Fields::Height,
+        Fields::Gamma,
+        Fields::Charset,
+        Fields::Invert,
+        Fields::Color,
+        Fields::Fit,
+        Fields::Finish,
+    ];
+
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&f| f == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|&f| f == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether the field accepts typed numbers.
+    fn is_numeric(self) -> bool {
+        matches!(self, Fields::Width | Fields::Height | Fields::Gamma)
+    }
 }
 
-struct App {
-    art: String,
-    width: u32,
-    height: u32,
-    gamma: f32,
-    selected_field: Fields,
-}
+impl App {
+    fn new(args: &Args) -> App {
+        App {
+            frame: ArtBuffer::default(),
+            color_mode: args.convert.color,
+            color_support: args.color_support,
+            ascii_only: args.convert.ascii_only,
+            width: args.convert.width,
+            height: args.convert.height,
+            gamma: args.convert.gamma,
+            charset: args.convert.charset,
+            invert: args.convert.invert,
+            fit: args.convert.fit,
+            aspect_lock: false,
+            source_dimensions: (1, 1),
+            crop: args.convert.crop,
+            curve: args.convert.curve.clone().unwrap_or_default(),
+            palette: args.convert.palette.clone(),
+            upscale: args.convert.upscale,
+            curve_drag: None,
+            mapper: args.mapper.clone(),
+            max_memory: args.convert.max_memory,
+            selection: None,
+            terminal_size: Rect::default(),
+            selected_field: Fields::Width,
+            input: None,
+            prompt: None,
+            presets: PresetStore::default(),
+            keys: KeyMap::default(),
+            preset_name: args.convert.preset.clone(),
+            undo_history: Vec::new(),
+            redo_history: Vec::new(),
+            source: None,
+            tabs: Vec::new(),
+            current_tab: 0,
+            dirty: true,
+            worker: None,
+            samples: SampleCache::default(),
+            requested_generation: 0,
+            shown_generation: 0,
+            browser: None,
+            video_to_play: None,
+            cancelled: false,
+            compare: false,
+            original: ArtBuffer::default(),
+            conversion_time: Duration::ZERO,
+            format: args.convert.format,
+            copy_format: args.tui.copy_format,
+            status: None,
+            stats: PlaybackStats::default(),
+            show_stats: false,
+            show_metadata: args.convert.show_meta,
+            paused: Arc::new(Mutex::new(false)),
+            transport: Arc::new(Mutex::new(Transport::default())),
+            spectrum: Arc::new(Mutex::new(Spectrum::default())),
+            audio_notice: Arc::new(Mutex::new(None)),
+            live: None,
+            reconnects: 0,
+            show_spectrum: false,
+            control: None,
+            seek_to: None,
+            speed_to: None,
+            scroll: (0, 0),
+            zoom: 1,
+            pan: (0, 0),
+        }
+    }
+
+    /// The conversion options currently selected in the editor.
+    fn options(&self) -> converter::AsciiOptions {
+        converter::AsciiOptions::new(self.width, self.height, self.gamma)
+            .with_charset(self.charset)
+            .with_invert(self.invert)
+            .with_crop(self.crop)
+            .with_curve(self.curve.clone())
+            .with_palette(self.palette.clone())
+            .with_upscale(self.upscale)
+    }
+
+    /// The options given on the command line for printing the source, sized to the terminal
+    /// with `--fit`.
+    fn for_print(source: &converter::ImageConverter, args: &Args) -> App {
+        let mut app = App::new(args);
+        let dimensions = app.crop.map_or(source.dimensions(), |(_, _, w, h)| (w, h));
+        if app.fit {
+            (app.width, app.height) = print_dimensions(dimensions);
+        }
+        let rows = match app.color_mode {
+            ColorMode::Halfblock => app.height * 2,
+            _ => app.height,
+        };
+        if app.width > dimensions.0 || rows > dimensions.1 {
+            tracing::warn!(
+                "the art samples {}x{} pixels of a {}x{} image, so it is upscaled",
+                app.width,
+                rows,
+                dimensions.0,
+                dimensions.1
+            );
+        }
+        app
+    }
+
+    /// Converts an image once with the options given on the command line.
+    pub fn convert_image(
+        source: &converter::ImageConverter,
+        args: &Args,
+        handle: &ConversionHandle,
+    ) -> Result<String, AsciiGenError> {
+        let mut app = App::for_print(source, args);
+        let options = app.options();
+        app.frame.convert_tracked(
+            &converter::crop(source, &options)?,
+            &options,
+            app.color_mode,
+            &mut TemporalFilter::new(0),
+            handle,
+        )?;
+        if let Some(mapper) = &app.mapper {
+            app.frame.remap(mapper.as_ref(), &options, app.color_mode);
+        }
+        let _span = tracing::trace_span!("render").entered();
+        if args.convert.accessible {
+            let rows = describe::rows(&app.frame.art);
+            if !args.convert.alt_text {
+                return Ok(rows);
+            }
+            let summary = describe::alt_text(&app.frame.art, source.dimensions());
+            return Ok(format!("{}\n{}", summary, rows));
+        }
+        Ok(app.rendered())
+    }
+
+    /// Scores how closely the art `convert_image` makes resembles the image.
+    pub fn score_image(
+        source: &converter::ImageConverter,
+        args: &Args,
+    ) -> Result<Fidelity, AsciiGenError> {
+        let mut app = App::for_print(source, args);
+        let options = app.options();
+        let region = converter::crop(source, &options)?;
+        app.frame.convert(
+            &region,
+            &options,
+            app.color_mode,
+            &mut TemporalFilter::new(0),
+        );
+        if let Some(mapper) = &app.mapper {
+            app.frame.remap(mapper.as_ref(), &options, app.color_mode);
+        }
+        Ok(metrics::score(
+            &region,
+            &app.frame.art,
+            &app.frame.colors,
+            app.color_mode,
+        ))
+    }
+
+    /// Lets the user tweak the conversion options of named images, each in its own tab,
+    /// returning the confirmed art of all of them.
+    pub fn run(
+        images: Vec<(String, DynamicImage)>,
+        args: Args,
+        watch: bool,
+    ) -> Result<EditorOutcome, AsciiGenError> {
+        let watcher = if watch {
+            let files: Vec<String> = images.iter().map(|(name, _)| name.clone()).collect();
+            Some(FileWatcher::new(&files)?)
+        } else {
+            None
+        };
+        let mut terminal = init_terminal(args.tui.record.as_deref())?;
+        let mut app = App::new(&args);
+
+        app.resize(terminal.size()?);
+        for (name, image) in images {
+            let source = Arc::new(converter::ImageConverter::from_image(image));
+            app.set_source(source.clone());
+            app.tabs.push(Tab {
+                metadata: ImageMetadata::read(&name),
+                name,
+                source,
+                state: app.edit_state(),
+                undo_history: Vec::new(),
+                redo_history: Vec::new(),
+            });
+        }
+        if let Some(tab) = app.tabs.first() {
+            app.set_source(tab.source.clone());
+        }
+        app.worker = Some(ConversionWorker::spawn());
+
+        match PresetStore::load() {
+            Ok(presets) => app.presets = presets,
+            Err(e) => app.status = Some(format!("Loading presets failed: {}", e)),
+        }
+        app.load_keys(args.config.as_deref());
+
+        loop {
+            if interrupted() {
+                app.cancelled = true;
+                break;
+            }
+            if let Some(watcher) = &watcher {
+                let changed = watcher.changed();
+                if !changed.is_empty() {
+                    app.reload_changed(&changed);
+                }
+            }
+            if app.dirty {
+                app.request_conversion();
+                app.dirty = false;
+            }
+            if let Some(result) = app.worker.as_ref().and_then(|w| w.latest_result()) {
+                app.show_conversion(result);
+            }
+            terminal.draw(|frame| app.ui(frame))?;
+
+            if event::poll(Duration::from_millis(33))? {
+                match event::read()? {
+                    Event::Key(key)
+                        if key.kind == KeyEventKind::Press && app.handle_editor_key(key) =>
+                    {
+                        break
+                    }
+                    Event::Resize(width, height) => app.resize(Rect::new(0, 0, width, height)),
+                    Event::Mouse(mouse) => app.handle_mouse(mouse),
+                    _ => {}
+                }
+            }
+        }
+
+        terminal.restore()?;
+
+        if app.cancelled {
+            return Ok(EditorOutcome::Cancelled);
+        }
+
+        // Videos picked in the file browser play with the settings chosen so far
+        if let Some(video) = app.video_to_play.take() {
+            let mut args = args;
+            args.apply_preset(&app.preset());
+            let video = VideoInput::File(video.to_string_lossy().into_owned());
+            let art = App::run_video(video, args)?;
+            return Ok(art.map_or(EditorOutcome::Cancelled, EditorOutcome::Saved));
+        }
+
+        // Zooming is only for inspection, so the result always covers the whole image
+        app.zoom = 1;
+        app.worker = None;
+        let mut arts = Vec::with_capacity(app.tabs.len());
+        for index in 0..app.tabs.len() {
+            app.switch_tab(index);
+            app.request_conversion();
+            arts.push(app.rendered());
+        }
+        Ok(EditorOutcome::Saved(arts.join("\n")))
+    }
+
+    /// Stashes the settings of the current tab and brings up another one, dropping a number
+    /// half typed into the current tab's field.
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.current_tab || index >= self.tabs.len() {
+            return;
+        }
+        self.input = None;
+        self.status = None;
+
+        let state = self.edit_state();
+        let current = &mut self.tabs[self.current_tab];
+        current.state = state;
+        current.undo_history = std::mem::take(&mut self.undo_history);
+        current.redo_history = std::mem::take(&mut self.redo_history);
+
+        let tab = &mut self.tabs[index];
+        let (source, state) = (tab.source.clone(), tab.state.clone());
+        self.undo_history = std::mem::take(&mut tab.undo_history);
+        self.redo_history = std::mem::take(&mut tab.redo_history);
+        self.current_tab = index;
+
+        self.restore_edit_state(state);
+        self.set_source(source);
+    }
+
+    /// Replaces the image being edited, refitting the art to it.
+    fn set_source(&mut self, source: Arc<converter::ImageConverter>) {
+        self.source_dimensions = source.dimensions();
+        self.source = Some(source);
+        self.zoom = 1;
+        self.pan = (0, 0);
+        self.scroll = (0, 0);
+        if self.fit {
+            self.fit_to_preview();
+        }
+        self.dirty = true;
+    }
+
+    /// Describes a conversion of the current image, or only of the visible part while zoomed.
+    fn conversion_job(&mut self) -> Option<ConversionJob> {
+        let source = self.source.clone()?;
+        self.requested_generation += 1;
+
+        // Render the source itself next to the art when comparing
+        let original_size = self.compare.then(|| {
+            let pane = compare_layout(art_pane(self.terminal_size))[0];
+            let bounds = (
+                pane.width.saturating_sub(2) as u32,
+                pane.height.saturating_sub(2) as u32,
+            );
+            converter::fit_dimensions(self.view_dimensions(), bounds)
+        });
+
+        Some(ConversionJob {
+            generation: self.requested_generation,
+            source,
+            options: self.options(),
+            color_mode: self.color_mode,
+            mapper: self.mapper.clone(),
+            region: (self.zoom > 1).then(|| self.zoom_region()),
+            original_size,
+        })
+    }
+
+    /// Hands the current settings to the background worker, converting in place without one.
+    fn request_conversion(&mut self) {
+        let Some(job) = self.conversion_job() else {
+            return;
+        };
+        match &mut self.worker {
+            Some(worker) => worker.submit(job),
+            None => {
+                if let Ok(result) =
+                    worker::convert(job, &ConversionHandle::default(), &mut self.samples)
+                {
+                    self.show_conversion(result);
+                }
+            }
+        }
+    }
+
+    /// Shows a finished conversion unless a newer one is already on screen.
+    fn show_conversion(&mut self, result: ConversionResult) {
+        if result.generation <= self.shown_generation {
+            return;
+        }
+        self.shown_generation = result.generation;
+        self.frame = result.art;
+        self.conversion_time = result.elapsed;
+        if let Some(original) = result.original {
+            self.original = original;
+        }
+    }
+
+    /// Whether a requested conversion has not been shown yet.
+    fn is_converting(&self) -> bool {
+        self.shown_generation < self.requested_generation
+    }
+
+    /// Opens a file picked in the file browser.
+    fn open_file(&mut self, path: PathBuf) {
+        if cli::is_video_file(&path) {
+            self.video_to_play = Some(path);
+            return;
+        }
+
+        match converter::open_image_with(&path, self.max_memory) {
+            Ok(image) => {
+                let source = Arc::new(converter::ImageConverter::from_image(image));
+                if let Some(tab) = self.tabs.get_mut(self.current_tab) {
+                    tab.name = path.display().to_string();
+                    tab.source = source.clone();
+                    tab.metadata = ImageMetadata::read(&path);
+                }
+                self.crop = None;
+                self.set_source(source);
+                self.status = Some(format!("Opened {}", path.display()));
+            }
+            Err(e) => self.status = Some(format!("Open failed: {}", e)),
+        }
+    }
+
+    /// Converts the images of the tabs again once they changed on disk, keeping the view of
+    /// the current one as it was unless its size changed.
+    fn reload_changed(&mut self, changed: &[PathBuf]) {
+        for index in 0..self.tabs.len() {
+            let name = self.tabs[index].name.clone();
+            if !watch::contains(changed, &name) {
+                continue;
+            }
+            match converter::open_image_with(&name, self.max_memory) {
+                Ok(image) => {
+                    let source = Arc::new(converter::ImageConverter::from_image(image));
+                    self.tabs[index].source = source.clone();
+                    self.tabs[index].metadata = ImageMetadata::read(&name);
+                    if index == self.current_tab {
+                        let view = (self.zoom, self.pan, self.scroll);
+                        let dimensions = self.source_dimensions;
+                        self.set_source(source);
+                        if self.source_dimensions == dimensions {
+                            (self.zoom, self.pan, self.scroll) = view;
+                        } else {
+                            self.crop = None;
+                        }
+                    }
+                    self.status = Some(format!("Reloaded {}", name));
+                }
+                Err(e) => self.status = Some(format!("Reload failed: {}", e)),
+            }
+        }
+    }
+
+    /// The part of the source being converted after cropping, as `(x, y, width, height)`.
+    fn view_region(&self) -> (u32, u32, u32, u32) {
+        let (source_width, source_height) = self.source_dimensions;
+        let Some((x, y, width, height)) = self.crop else {
+            return (0, 0, source_width, source_height);
+        };
+        // Nothing is converted until a frame arrives
+        if source_width == 0 || source_height == 0 {
+            return (0, 0, source_width, source_height);
+        }
+
+        // Same clamping as `converter::crop`
+        let x = x.min(source_width.saturating_sub(1));
+        let y = y.min(source_height.saturating_sub(1));
+        (
+            x,
+            y,
+            width.clamp(1, source_width - x),
+            height.clamp(1, source_height - y),
+        )
+    }
+
+    fn view_dimensions(&self) -> (u32, u32) {
+        let (_, _, width, height) = self.view_region();
+        (width, height)
+    }
+
+    /// The part of the source shown while zoomed, as `(x, y, width, height)` in pixels.
+    fn zoom_region(&self) -> (u32, u32, u32, u32) {
+        let (source_width, source_height) = self.view_dimensions();
+        let width = (source_width / self.zoom).max(1);
+        let height = (source_height / self.zoom).max(1);
+        let x = self.pan.0.min(source_width - width);
+        let y = self.pan.1.min(source_height - height);
+        (x, y, width, height)
+    }
+
+    /// Moves the view by a fraction of its size while zoomed, or scrolls the art otherwise.
+    fn pan_by(&mut self, dx: i32, dy: i32) {
+        if self.zoom <= 1 {
+            self.scroll.0 = self.scroll.0.saturating_add_signed(dy as i16);
+            self.scroll.1 = self.scroll.1.saturating_add_signed(dx as i16);
+            return;
+        }
+
+        let (x, y, width, height) = self.zoom_region();
+        let step_x = (width / 8).max(1) as i64 * dx as i64;
+        let step_y = (height / 8).max(1) as i64 * dy as i64;
+        let (view_width, view_height) = self.view_dimensions();
+        let max_x = view_width - width;
+        let max_y = view_height - height;
+        self.pan.0 = (x as i64 + step_x).clamp(0, max_x as i64) as u32;
+        self.pan.1 = (y as i64 + step_y).clamp(0, max_y as i64) as u32;
+    }
+
+    /// Zooms in or out around the center of the current view.
+    fn zoom_by(&mut self, zoom_in: bool) {
+        let (x, y, width, height) = self.zoom_region();
+        let center = (x + width / 2, y + height / 2);
+
+        self.zoom = if zoom_in {
+            (self.zoom * 2).min(MAX_ZOOM)
+        } else {
+            (self.zoom / 2).max(1)
+        };
+
+        let (_, _, width, height) = self.zoom_region();
+        self.pan = (
+            center.0.saturating_sub(width / 2),
+            center.1.saturating_sub(height / 2),
+        );
+    }
+
+    /// Records the new terminal size, refitting the art when `Fit` is on.
+    fn resize(&mut self, terminal_size: Rect) {
+        self.terminal_size = terminal_size;
+        if self.fit {
+            self.fit_to_preview();
+        }
+        self.dirty = true;
+    }
+
+    /// Sizes the art to fill the preview pane.
+    fn fit_to_preview(&mut self) {
+        let pane = art_pane(self.terminal_size);
+        let bounds = (
+            pane.width.saturating_sub(2) as u32,
+            pane.height.saturating_sub(2) as u32,
+        );
+        (self.width, self.height) = converter::fit_dimensions(self.view_dimensions(), bounds);
+    }
+
+    /// Applies a key press, returning true once the user confirms or quits.
+    fn handle_editor_key(&mut self, key: KeyEvent) -> bool {
+        let view_before = (self.zoom, self.pan, self.compare);
+
+        let action = (!self.is_typing())
+            .then(|| self.keys.editor_action(key))
+            .flatten();
+
+        let finished = match action {
+            Some(Action::Undo) => {
+                self.undo();
+                false
+            }
+            Some(Action::Redo) => {
+                self.redo();
+                false
+            }
+            _ => {
+                // Remember the previous settings whenever a key changes them
+                let before = self.edit_state();
+                let finished = self.apply_editor_key(key, action);
+                self.record_edit(before);
+                finished
+            }
+        };
+
+        if (self.zoom, self.pan, self.compare) != view_before {
+            self.dirty = true;
+        }
+        finished
+    }
+
+    /// Pushes the settings from before an edit onto the undo history if they changed.
+    fn record_edit(&mut self, before: EditState) {
+        if self.edit_state() == before {
+            return;
+        }
+        self.undo_history.push(before);
+        if self.undo_history.len() > MAX_HISTORY {
+            self.undo_history.remove(0);
+        }
+        self.redo_history.clear();
+        self.dirty = true;
+    }
+
+    /// Drags out a crop rectangle over the art with the left mouse button.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.is_typing() {
+            return;
+        }
+        let position = (mouse.column, mouse.row);
+
+        let clicked = Rect::new(mouse.column, mouse.row, 1, 1);
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if self.art_area().intersects(clicked) {
+                    self.selection = Some((position, position));
+                } else if self.curve_area().intersects(clicked) {
+                    let point = self.curve_point_at(mouse.column);
+                    self.curve_drag = Some((point, self.edit_state()));
+                    self.drag_curve(point, mouse.row);
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some((_, end)) = self.selection.as_mut() {
+                    *end = position;
+                }
+                if let Some((point, _)) = self.curve_drag {
+                    self.drag_curve(point, mouse.row);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some((_, before)) = self.curve_drag.take() {
+                    self.record_edit(before);
+                }
+                if let Some((start, _)) = self.selection.take() {
+                    let before = self.edit_state();
+                    self.crop_to_cells(start, position);
+                    self.record_edit(before);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The curve pane without its border.
+    fn curve_area(&self) -> Rect {
+        let options_pane = main_layout(self.terminal_size)[1];
+        options_layout(options_pane)[2].inner(&Margin::new(1, 1))
+    }
+
+    /// The control point closest to a column of the curve pane.
+    fn curve_point_at(&self, column: u16) -> usize {
+        let area = self.curve_area();
+        let offset = column.saturating_sub(area.x) as usize;
+        let span = area.width.saturating_sub(1).max(1) as usize;
+        ((offset * (CONTROL_POINTS - 1) + span / 2) / span).min(CONTROL_POINTS - 1)
+    }
+
+    /// Moves a control point of the curve to the level under a row of the curve pane.
+    fn drag_curve(&mut self, point: usize, row: u16) {
+        let area = self.curve_area();
+        let span = area.height.saturating_sub(1).max(1) as u32;
+        let from_bottom = (area.bottom().saturating_sub(1).saturating_sub(row) as u32).min(span);
+        self.curve
+            .set_level(point, (from_bottom * 255 / span) as u8);
+        self.dirty = true;
+    }
+
+    /// The art pane without its border, where the art is drawn.
+    fn art_area(&self) -> Rect {
+        let pane = art_pane(self.terminal_size);
+        if self.compare {
+            return compare_layout(pane)[1].inner(&Margin::new(1, 1));
+        }
+        pane.inner(&Margin::new(1, 1))
+    }
+
+    /// Crops the source to the pixels under a rectangle of terminal cells.
+    fn crop_to_cells(&mut self, start: (u16, u16), end: (u16, u16)) {
+        let area = self.art_area();
+        let (columns, rows) = self.art_size();
+        if columns == 0 || rows == 0 {
+            return;
+        }
+
+        // Terminal cells to art cells, clamped to the art
+        let to_art = |(column, row): (u16, u16)| {
+            let x = column.saturating_sub(area.x) as u32 + self.scroll.1 as u32;
+            let y = row.saturating_sub(area.y) as u32 + self.scroll.0 as u32;
+            (x.min(columns - 1), y.min(rows - 1))
+        };
+        let (start, end) = (to_art(start), to_art(end));
+        let (left, right) = (start.0.min(end.0), start.0.max(end.0) + 1);
+        let (top, bottom) = (start.1.min(end.1), start.1.max(end.1) + 1);
+
+        // Art cells to pixels of the view, then of the whole source
+        let (view_x, view_y, view_width, view_height) = match self.zoom {
+            zoom if zoom > 1 => self.zoom_region(),
+            _ => {
+                let (width, height) = self.view_dimensions();
+                (0, 0, width, height)
+            }
+        };
+        let (crop_x, crop_y, _, _) = self.view_region();
+        let x = crop_x + view_x + left * view_width / columns;
+        let y = crop_y + view_y + top * view_height / rows;
+        let width = ((right - left) * view_width / columns).max(1);
+        let height = ((bottom - top) * view_height / rows).max(1);
+
+        self.crop = Some((x, y, width, height));
+        self.zoom = 1;
+        self.pan = (0, 0);
+        self.scroll = (0, 0);
+        if self.fit {
+            self.fit_to_preview();
+        }
+    }
+
+    /// Columns and rows of the art on screen.
+    fn art_size(&self) -> (u32, u32) {
+        let art = &self.frame.art;
+        let columns = art.lines().next().map_or(0, |line| line.chars().count());
+        (columns as u32, art.lines().count() as u32)
+    }
+
+    /// Replaces the default key bindings with those from the config file.
+    fn load_keys(&mut self, config: Option<&std::path::Path>) {
+        match KeyMap::load(config) {
+            Ok(keys) => self.keys = keys,
+            Err(e) => self.status = Some(format!("Loading key bindings failed: {}", e)),
+        }
+    }
+
+    /// Whether keys currently go into a text field rather than being hotkeys.
+    fn is_typing(&self) -> bool {
+        self.prompt.is_some() || self.input.is_some() || self.browser.is_some()
+    }
+
+    /// The settings tracked by undo and redo.
+    fn edit_state(&self) -> EditState {
+        (self.options(), self.color_mode)
+    }
+
+    fn restore_edit_state(&mut self, (options, color_mode): EditState) {
+        self.width = options.width();
+        self.height = options.height();
+        self.gamma = options.gamma();
+        self.charset = options.charset();
+        self.invert = options.invert();
+        self.crop = options.crop();
+        self.curve = options.curve().clone();
+        self.palette = options.palette().cloned();
+        self.upscale = options.upscale();
+        self.color_mode = color_mode;
+        self.fit = false;
+    }
+
+    fn undo(&mut self) {
+        if let Some(state) = self.undo_history.pop() {
+            self.redo_history.push(self.edit_state());
+            self.restore_edit_state(state);
+            self.dirty = true;
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(state) = self.redo_history.pop() {
+            self.undo_history.push(self.edit_state());
+            self.restore_edit_state(state);
+            self.dirty = true;
+        }
+    }
+
+    fn apply_editor_key(&mut self, key: KeyEvent, action: Option<Action>) -> bool {
+        if let Some(browser) = self.browser.as_mut() {
+            match browser.handle_key(key.code) {
+                BrowserAction::None => {}
+                BrowserAction::Close => self.browser = None,
+                BrowserAction::Open(path) => {
+                    self.browser = None;
+                    self.open_file(path);
+                    // Leave the editor so the video can start playing
+                    return self.video_to_play.is_some();
+                }
+            }
+            return false;
+        }
+
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key.code);
+            return false;
+        }
+
+        // Alt and a digit picks a tab, leaving plain digits to type into numeric fields
+        if let KeyCode::Char(c @ '1'..='9') = key.code {
+            if self.tabs.len() > 1 && key.modifiers.contains(KeyModifiers::ALT) {
+                self.switch_tab(c as usize - '1' as usize);
+                return false;
+            }
+        }
+
+        match action {
+            Some(Action::ToggleCompare) => self.compare = !self.compare,
+            Some(Action::ToggleMetadata) => self.show_metadata = !self.show_metadata,
+            Some(Action::OpenFile) => match FileBrowser::open(std::path::Path::new(".")) {
+                Ok(browser) => self.browser = Some(browser),
+                Err(e) => self.status = Some(format!("Listing files failed: {}", e)),
+            },
+            Some(Action::SaveArt) => self.prompt = Some(Prompt::new(PromptKind::SaveArt)),
+            Some(Action::SavePreset) => self.prompt = Some(Prompt::new(PromptKind::SavePreset)),
+            Some(Action::NextPreset) => self.next_preset(),
+            Some(Action::Copy) => self.copy_art(),
+            Some(Action::PanLeft) => self.pan_by(-1, 0),
+            Some(Action::PanDown) => self.pan_by(0, 1),
+            Some(Action::PanUp) => self.pan_by(0, -1),
+            Some(Action::PanRight) => self.pan_by(1, 0),
+            Some(Action::ZoomIn) => self.zoom_by(true),
+            Some(Action::ZoomOut) => self.zoom_by(false),
+            Some(Action::Finish) => return true,
+            Some(Action::Quit) => {
+                self.cancelled = true;
+                return true;
+            }
+            Some(Action::ClearCrop) => {
+                self.crop = None;
+                self.zoom = 1;
+                self.pan = (0, 0);
+                if self.fit {
+                    self.fit_to_preview();
+                }
+            }
+            _ => return self.handle_option_key(key, action),
+        }
+        false
+    }
+
+    /// Edits the conversion options, in both the live editor and the video player.
+    /// Returns true once the user confirms.
+    fn handle_option_key(&mut self, key: KeyEvent, action: Option<Action>) -> bool {
+        if self.input.is_some() {
+            self.handle_input_key(key.code);
+            return false;
+        }
+
+        // Shift makes arrow keys take bigger steps
+        let large_step = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        // Digits start typing into numeric fields regardless of the bindings
+        if let KeyCode::Char(c) = key.code {
+            if self.selected_field.is_numeric() && c.is_ascii_digit() {
+                self.input = Some(c.to_string());
+                return false;
+            }
+        }
+
+        match action {
+            Some(Action::Select) if self.selected_field.is_numeric() => {
+                self.input = Some(String::new())
+            }
+            Some(Action::Select) => return self.selected_field == Fields::Finish,
+            Some(Action::PreviousField) => self.selected_field = self.selected_field.previous(),
+            Some(Action::NextField) => self.selected_field = self.selected_field.next(),
+            Some(Action::Decrease) => self.adjust_selected_field(false, large_step),
+            Some(Action::Increase) => self.adjust_selected_field(true, large_step),
+            Some(Action::ToggleAspectLock) => {
+                self.aspect_lock = !self.aspect_lock;
+                if self.aspect_lock {
+                    self.link_size(Fields::Width);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Edits the text in the open popup, acting on it on Enter.
+    fn handle_prompt_key(&mut self, code: KeyCode) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c) => prompt.text.push(c),
+            KeyCode::Backspace => {
+                prompt.text.pop();
+            }
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                let Some(prompt) = self.prompt.take().filter(|p| !p.text.is_empty()) else {
+                    return;
+                };
+                match prompt.kind {
+                    PromptKind::SaveArt => self.save_art(&prompt.text),
+                    PromptKind::SavePreset => self.save_preset(prompt.text),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Copies the art to the clipboard in the `--copy-format`.
+    fn copy_art(&mut self) {
+        let art = output::render(
+            &self.frame.art,
+            &self.frame.colors,
+            self.color_mode,
+            self.copy_format,
+        );
+        self.status = Some(match clipboard::copy(&art) {
+            Ok(()) => "Copied to the clipboard".to_string(),
+            Err(e) => format!("Copy failed: {}", e),
+        });
+    }
+
+    /// Writes the art to a file in the selected output format.
+    fn save_art(&mut self, file_name: &str) {
+        self.status = Some(match std::fs::write(file_name, self.rendered()) {
+            Ok(()) => format!("Saved {}", file_name),
+            Err(e) => format!("Save failed: {}", e),
+        });
+    }
+
+    /// The current settings as a preset.
+    fn preset(&self) -> Preset {
+        Preset {
+            width: self.width,
+            height: self.height,
+            gamma: self.gamma,
+            charset: self.charset,
+            invert: self.invert,
+            color: self.color_mode,
+            curve: self.curve.clone(),
+        }
+    }
+
+    fn apply_preset(&mut self, preset: &Preset) {
+        self.width = converter::AsciiOptions::clamp_size(preset.width);
+        self.height = converter::AsciiOptions::clamp_size(preset.height);
+        self.gamma = converter::AsciiOptions::clamp_gamma(preset.gamma);
+        self.charset = if self.ascii_only {
+            preset.charset.ascii()
+        } else {
+            preset.charset
+        };
+        self.invert = preset.invert;
+        self.color_mode = match preset.color {
+            ColorMode::Halfblock if self.ascii_only => ColorMode::Truecolor,
+            color => color,
+        }
+        .downgrade(self.color_support);
+        self.curve = preset.curve.clone();
+        self.fit = false;
+    }
+
+    /// Stores the current settings under `name` in the presets file.
+    fn save_preset(&mut self, name: String) {
+        self.presets.insert(name.clone(), self.preset());
+        self.status = Some(match self.presets.save() {
+            Ok(_) => format!("Saved preset {}", name),
+            Err(e) => format!("Saving preset failed: {}", e),
+        });
+        self.preset_name = Some(name);
+    }
+
+    /// Switches to the next saved preset.
+    fn next_preset(&mut self) {
+        let Some((name, preset)) = self.presets.next_after(self.preset_name.as_deref()) else {
+            self.status = Some("No presets saved yet".to_string());
+            return;
+        };
 
-#[derive(PartialEq)]
-enum Fields {
-    Width,
-    Height,
-    Gamma,
-    Finish,
-}
+        let (name, preset) = (name.clone(), preset.clone());
+        self.apply_preset(&preset);
+        self.preset_name = Some(name);
+    }
 
-impl App {
-    fn new() -> App {
-        App {
-            art: String::new(),
-            width: 160,
-            height: 90,
-            gamma: 1.0,
-            selected_field: Fields::Width,
+    /// The art in the selected output format.
+    fn rendered(&self) -> String {
+        output::render(
+            &self.frame.art,
+            &self.frame.colors,
+            self.color_mode,
+            self.format,
+        )
+    }
+
+    /// Edits the number being typed into the selected field.
+    fn handle_input_key(&mut self, code: KeyCode) {
+        let Some(input) = self.input.as_mut() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                let input = self.input.take().unwrap_or_default();
+                if let Err(e) = self.set_selected_field(&input) {
+                    self.status = Some(e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sets the selected numeric field from typed text.
+    fn set_selected_field(&mut self, input: &str) -> Result<(), String> {
+        let invalid = || format!("Invalid value: {:?}", input);
+
+        match self.selected_field {
+            Fields::Width | Fields::Height => {
+                let value: u32 = input.parse().map_err(|_| invalid())?;
+                let (min, max) = converter::AsciiOptions::SIZE_BOUNDS;
+                if !(min..=max).contains(&value) {
+                    return Err(format!("Size must be between {} and {}", min, max));
+                }
+                if self.selected_field == Fields::Width {
+                    self.width = value;
+                } else {
+                    self.height = value;
+                }
+                self.link_size(self.selected_field);
+                self.fit = false;
+            }
+            Fields::Gamma => {
+                let value: f32 = input.parse().map_err(|_| invalid())?;
+                let (min, max) = converter::AsciiOptions::GAMMA_BOUNDS;
+                if !(min..=max).contains(&value) {
+                    return Err(format!("Gamma must be between {} and {}", min, max));
+                }
+                self.gamma = value;
+            }
+            _ => {}
+        }
+
+        self.status = None;
+        Ok(())
+    }
+
+    /// Increments or decrements the selected field, staying within the option bounds.
+    fn adjust_selected_field(&mut self, increase: bool, large_step: bool) {
+        let size_step: i64 = if large_step { 10 } else { 1 };
+        let gamma_step = if large_step { 0.5 } else { 0.1 };
+        let (size_step, gamma_step) = if increase {
+            (size_step, gamma_step)
+        } else {
+            (-size_step, -gamma_step)
+        };
+        let step_size = |size: u32| {
+            let size = (size as i64 + size_step).clamp(0, u32::MAX as i64) as u32;
+            converter::AsciiOptions::clamp_size(size)
+        };
+
+        match self.selected_field {
+            Fields::Width => {
+                self.width = step_size(self.width);
+                self.link_size(Fields::Width);
+                self.fit = false;
+            }
+            Fields::Height => {
+                self.height = step_size(self.height);
+                self.link_size(Fields::Height);
+                self.fit = false;
+            }
+            Fields::Gamma => {
+                self.gamma = converter::AsciiOptions::clamp_gamma(self.gamma + gamma_step)
+            }
+            Fields::Charset => {
+                let step = if increase {
+                    Charset::next
+                } else {
+                    Charset::previous
+                };
+                self.charset = step(self.charset);
+                while self.ascii_only && !self.charset.is_ascii() {
+                    self.charset = step(self.charset);
+                }
+            }
+            Fields::Invert => self.invert = !self.invert,
+            Fields::Color => {
+                let step = if increase {
+                    ColorMode::next
+                } else {
+                    ColorMode::previous
+                };
+                // Skip the modes the terminal would show as something else
+                self.color_mode = step(self.color_mode);
+                while !self.color_support.allows(self.color_mode)
+                    || (self.ascii_only && self.color_mode == ColorMode::Halfblock)
+                {
+                    self.color_mode = step(self.color_mode);
+                }
+            }
+            Fields::Fit => {
+                self.fit = !self.fit;
+                if self.fit {
+                    self.fit_to_preview();
+                }
+            }
+            Fields::Finish => {}
         }
     }
 
-    pub fn run_video(file: String, args: Args) -> io::Result<String> {
-        // Initialize ffmpeg and open the video file
-        ffmpeg::init().unwrap();
+    /// Follows an edit of the width or height with the other when the aspect ratio is locked.
+    fn link_size(&mut self, changed: Fields) {
+        if !self.aspect_lock {
+            return;
+        }
+        match changed {
+            Fields::Width => {
+                self.height = converter::height_for_width(self.view_dimensions(), self.width)
+            }
+            Fields::Height => {
+                self.width = converter::width_for_height(self.view_dimensions(), self.height)
+            }
+            _ => {}
+        }
+    }
 
+    /// Plays the video with audio, returning the final frame when `--on-end hold` is used.
+    pub fn run_video(input: VideoInput, args: Args) -> Result<Option<String>, AsciiGenError> {
         let running = Arc::new(Mutex::new(true));
+        let on_end = args.playback.on_end;
+        let timelapse = args.playback.timelapse;
 
-        let mut terminal = init_terminal()?;
-        let mut app = App::new();
+        // Bound before the terminal is taken over, so a bad path fails like any other flag
+        let control = args
+            .playback
+            .control
+            .as_deref()
+            .map(ControlServer::bind)
+            .transpose()?;
+        let mut terminal = init_terminal(args.tui.record.as_deref())?;
+        let mut app = App::new(&args);
+        app.load_keys(args.config.as_deref());
+        app.terminal_size = terminal.size()?;
+        app.control = control;
+        let paused = app.paused.clone();
+        let transport = app.transport.clone();
+        let spectrum = app.spectrum.clone();
+        let audio_notice = app.audio_notice.clone();
 
         // Video playback thread
-        let video_file = file.clone();
+        let video_input = input.clone();
         let video_args = args.clone();
-        let video_thread = std::thread::spawn(move || {
-            let result =
-                Self::play_video(&video_file, &video_args, &mut terminal, &mut app, &running);
-            if let Err(e) = result {
-                eprintln!("Video playback error: {}", e);
+        let video_running = running.clone();
+        let video_thread = std::thread::spawn(move || -> Result<_, AsciiGenError> {
+            loop {
+                app.live = None;
+                let started = Instant::now();
+                let played = Self::play_video(
+                    &video_input,
+                    &video_args,
+                    &mut terminal,
+                    &mut app,
+                    &video_running,
+                );
+
+                if !*video_running.lock().unwrap() {
+                    return played.map(|()| None);
+                }
+
+                // A live stream that stops, or a network video that does not open, is joined
+                // again after a wait that doubles with every try
+                let dropped = app.live.unwrap_or(played.is_err());
+                if dropped && video_input.is_url() {
+                    if started.elapsed() > RECONNECT_RESET {
+                        app.reconnects = 0;
+                    }
+                    if app.reconnects < video_args.playback.retries {
+                        if let Err(e) = &played {
+                            tracing::warn!(error = %e, "the stream dropped");
+                        }
+                        app.reconnects += 1;
+                        let retries = video_args.playback.retries;
+                        if Self::wait_to_reconnect(
+                            &mut terminal,
+                            &mut app,
+                            &video_running,
+                            retries,
+                        )? {
+                            continue;
+                        }
+                        return Ok(None);
+                    }
+                }
+                played?;
+
+                match on_end {
+                    OnEnd::Loop => continue,
+                    OnEnd::Hold => {
+                        let _ = wait_for_key();
+                        return Ok(Some(app.frame.art));
+                    }
+                    OnEnd::Exit | OnEnd::Clear => return Ok(None),
+                }
             }
         });
-        // Audio playback thread
-        let audio_thread = std::thread::spawn(move || {
-            let result = Self::play_audio(&file);
-            if let Err(e) = result {
-                eprintln!("Audio playback error: {}", e);
+        // Audio playback thread, for files with sound only and not for timelapses, where it
+        // would be noise. Without an audio device the video plays on silently, as do URLs.
+        let audio_thread = match input {
+            VideoInput::File(file)
+                if timelapse.is_none()
+                    && !stream::is_url(&file)
+                    && stream::has_audio(&file).unwrap_or(true) =>
+            {
+                let audio_running = running.clone();
+                let thread = std::thread::Builder::new()
+                    .name(AUDIO_THREAD.to_string())
+                    .spawn(move || {
+                        let looping = on_end == OnEnd::Loop;
+                        let played = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                            Self::play_audio(
+                                &file,
+                                &audio_running,
+                                &paused,
+                                &transport,
+                                &spectrum,
+                                looping,
+                            )
+                        }))
+                        .unwrap_or_else(|_| {
+                            Err(AsciiGenError::Audio("the audio output failed".to_string()))
+                        });
+                        if let Err(e) = &played {
+                            *audio_notice.lock().unwrap() = Some(format!("No sound: {}", e));
+                        }
+                        played
+                    })?;
+                Some(thread)
             }
-        });
+            VideoInput::File(_) | VideoInput::Camera(_) => None,
+        };
 
-        // Wait for both threads to finish
-        video_thread.join().unwrap();
-        audio_thread.join().unwrap();
+        // Wait for the video to finish, then stop the audio along with it, even after a panic
+        let final_frame = video_thread
+            .join()
+            .unwrap_or(Err(AsciiGenError::Panicked("video")));
+        *running.lock().unwrap() = false;
+        let audio_result = audio_thread.map_or(Ok(Ok(())), |thread| thread.join());
 
         let _ = restore_terminal();
 
-        Ok("Video and audio playback finished".to_string())
+        let final_frame = final_frame?;
+        match audio_result {
+            // A video without sound still plays, so this is only a warning
+            Ok(Err(e)) => tracing::warn!("{}", e),
+            Ok(Ok(())) => {}
+            // The video played on silently, but a bug is still a failure
+            Err(_) => return Err(AsciiGenError::Panicked("audio")),
+        }
+
+        if on_end == OnEnd::Clear {
+            stdout()
+                .execute(terminal::Clear(ClearType::All))?
+                .execute(cursor::MoveTo(0, 0))?;
+        }
+
+        Ok(final_frame)
+    }
+
+    /// Plays a procedural effect until it is quit or `duration` has passed, not counting
+    /// pauses. The same keys as for videos tune it while it runs.
+    pub fn run_fx(
+        effect: Effect,
+        duration: Option<Duration>,
+        logo: Option<&str>,
+        args: Args,
+    ) -> Result<(), AsciiGenError> {
+        let logo = logo
+            .map(|path| converter::open_image_with(path, args.convert.max_memory))
+            .transpose()?;
+        let seed = args.convert.seed;
+        let frame_interval = match args.playback.frame_rate {
+            frame_rate if frame_rate > 0.0 => Duration::from_secs_f64(1.0 / f64::from(frame_rate)),
+            _ => Duration::ZERO,
+        };
+
+        let control = args
+            .playback
+            .control
+            .as_deref()
+            .map(ControlServer::bind)
+            .transpose()?;
+        let mut terminal = init_terminal(args.tui.record.as_deref())?;
+        let mut app = App::new(&args);
+        app.load_keys(args.config.as_deref());
+        app.terminal_size = terminal.size()?;
+        app.control = control;
+        let running = Arc::new(Mutex::new(true));
+        let mut temporal_filter = TemporalFilter::new(args.playback.temporal_smooth);
+        let mut canvas: Option<EffectCanvas> = None;
+        let mut time = Duration::ZERO;
+        let mut last_frame = Instant::now();
+
+        while *running.lock().unwrap() && duration.is_none_or(|duration| time < duration) {
+            let frame_start = Instant::now();
+            if !*app.paused.lock().unwrap() {
+                time += frame_start - last_frame;
+            }
+            last_frame = frame_start;
+
+            // One canvas pixel per cell, starting over whenever the size is edited
+            let options = app.options();
+            let rows = match app.color_mode {
+                ColorMode::Halfblock => options.height() * 2,
+                _ => options.height(),
+            };
+            let mut current = match canvas.take() {
+                Some(canvas) if canvas.dimensions() == (options.width(), rows) => canvas,
+                _ => {
+                    let mut canvas = EffectCanvas::new(effect, options.width(), rows);
+                    if let Some(seed) = seed {
+                        canvas = canvas.with_seed(seed);
+                    }
+                    match &logo {
+                        Some(logo) => canvas.with_logo(logo),
+                        None => canvas,
+                    }
+                }
+            };
+            current.draw(time.as_secs_f64());
+            app.source_dimensions = current.dimensions();
+
+            let convert_start = Instant::now();
+            app.frame
+                .convert(&current, &options, app.color_mode, &mut temporal_filter);
+            if let Some(mapper) = &app.mapper {
+                app.frame.remap(mapper.as_ref(), &options, app.color_mode);
+            }
+            app.conversion_time = convert_start.elapsed();
+            canvas = Some(current);
+
+            if let Err(e) = terminal.draw(|frame| app.ui(frame)) {
+                tracing::warn!(error = %e, "drawing a frame failed");
+            }
+            app.handle_video_events(None, &running)?;
+            std::thread::sleep(frame_interval.saturating_sub(frame_start.elapsed()));
+        }
+
+        terminal.restore()?;
+        Ok(())
     }
 
     fn play_video(
-        file: &str,
+        input: &VideoInput,
         args: &Args,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        terminal: &mut Tui,
         app: &mut App,
         running: &Arc<Mutex<bool>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut ictx = ffmpeg::format::input(&file)?;
-
-        // Find the best video stream
-        let video_stream = ictx
-            .streams()
-            .best(ffmpeg::media::Type::Video)
-            .ok_or(ffmpeg::Error::StreamNotFound)?;
-        let video_stream_index = video_stream.index();
-        let video_context_decoder =
-            ffmpeg::codec::context::Context::from_parameters(video_stream.parameters())?;
-        let mut video_decoder = video_context_decoder.decoder().video()?;
-
-        // Create a scaler to convert the video frames to RGB format
-        let mut scaler = ffmpeg::software::scaling::context::Context::get(
-            video_decoder.format(),
-            video_decoder.width(),
-            video_decoder.height(),
-            ffmpeg::format::Pixel::RGB24,
-            video_decoder.width(),
-            video_decoder.height(),
-            ffmpeg::software::scaling::flag::Flags::BILINEAR,
-        )?;
+    ) -> Result<(), AsciiGenError> {
+        let playback = &args.playback;
+        let mut stream = input.open(playback)?;
+        app.source_dimensions = stream.dimensions();
+        app.live = Some(stream.duration().is_none());
+        if app.reconnects > 0 {
+            app.status = Some("Reconnected".to_string());
+        }
+        // Keep a speed set over `--control` when the video loops
+        let speed = app.transport.lock().unwrap().speed;
+        if speed != 1.0 {
+            stream.set_speed(speed);
+        }
 
-        // Get the video's time base
-        let video_time_base: f64 = video_stream.time_base().into();
-        let start_time = Instant::now();
+        let mut converter = VideoConverter::new(stream, app.options())
+            .with_color_mode(app.color_mode)
+            .with_temporal_smooth(playback.temporal_smooth)
+            .with_auto_expose(playback.auto_expose);
+        if let Some(mapper) = app.mapper.clone() {
+            converter = converter.with_mapper(mapper);
+        }
+        let mut start_time = Instant::now();
+        converter.set_start_time(Some(start_time));
+        let dropped_frames = app.stats.dropped_frames;
 
-        // Process each packet in the video
-        for (stream, packet) in ictx.packets() {
-            if !*running.lock().unwrap() {
+        while *running.lock().unwrap() {
+            converter.set_options(app.options(), app.color_mode);
+            let Some(frame) = converter.next() else {
                 break;
+            };
+            let frame = frame?;
+            app.stats.dropped_frames = dropped_frames + converter.dropped_frames();
+
+            let frame_timestamp = frame.timestamp;
+            if let Some(timestamp) = frame_timestamp {
+                std::thread::sleep(timestamp.saturating_sub(start_time.elapsed()));
             }
 
-            if stream.index() == video_stream_index {
-                video_decoder.send_packet(&packet)?;
+            app.stats.decode = frame.decode_time;
+            app.stats.convert = frame.convert_time;
+            converter.recycle(app.show_frame(frame));
+            if let Some(notice) = app.audio_notice.lock().unwrap().take() {
+                app.status = Some(notice);
+            }
 
-                let mut decoded = ffmpeg::frame::Video::empty();
+            // Draw the updated ASCII art in the terminal
+            let draw_start = Instant::now();
+            let span = tracing::trace_span!("render").entered();
+            if let Err(e) = terminal.draw(|frame| app.ui(frame)) {
+                tracing::warn!(error = %e, "drawing a frame failed");
+            }
+            drop(span);
+            app.stats.draw = draw_start.elapsed();
 
-                while video_decoder.receive_frame(&mut decoded).is_ok() {
-                    if let Some(pts) = decoded.pts() {
-                        let frame_timestamp = pts as f64 * video_time_base;
+            if let Some(timestamp) = frame_timestamp {
+                app.stats.late = start_time.elapsed().as_secs_f64() - timestamp.as_secs_f64();
+            }
+            // Timestamps are sped up, while the audio position is as recorded
+            let transport = app.transport.lock().unwrap();
+            app.stats.desync = frame_timestamp
+                .zip(transport.audio)
+                .map(|(timestamp, audio)| timestamp.as_secs_f64() - audio / transport.speed);
+            drop(transport);
 
-                        let elapsed = Instant::now().duration_since(start_time);
-                        let elapsed_secs = elapsed.as_secs_f64();
+            app.handle_video_events(Some(converter.stream()), running)?;
+            app.apply_transport(&mut converter, &mut start_time)?;
 
-                        if frame_timestamp > elapsed_secs {
-                            let sleep_duration =
-                                Duration::from_secs_f64(frame_timestamp - elapsed_secs);
-                            std::thread::sleep(sleep_duration);
-                        }
+            // Hold this frame while paused, moving the playback clock past the pause
+            if *app.paused.lock().unwrap() {
+                let mut pause_start = Instant::now();
+                // What the held frame was last converted with, `None` to convert it again
+                let mut converted_with = None;
+                while *app.paused.lock().unwrap() && *running.lock().unwrap() {
+                    // Show option edits made while paused, converting only once they change
+                    let wanted = (app.options(), app.color_mode);
+                    if converted_with.as_ref() != Some(&wanted) {
+                        converter.set_options(wanted.0.clone(), wanted.1);
+                        let frame = converter.reconvert()?;
+                        converter.recycle(app.show_frame(frame));
+                        converted_with = Some(wanted);
+                    }
+                    let _ = terminal.draw(|frame| app.ui(frame));
+                    app.handle_video_events(Some(converter.stream()), running)?;
+                    // A seek while paused starts the clock over from its frame
+                    if app.apply_transport(&mut converter, &mut start_time)? {
+                        pause_start = Instant::now();
+                        converted_with = None;
                     }
+                    std::thread::sleep(Duration::from_millis(33));
+                }
+                start_time += pause_start.elapsed();
+                converter.set_start_time(Some(start_time));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counts down to the next try at joining a dropped stream on the status bar. Returns false
+    /// if playback is quit meanwhile.
+    fn wait_to_reconnect(
+        terminal: &mut Tui,
+        app: &mut App,
+        running: &Arc<Mutex<bool>>,
+        retries: u32,
+    ) -> Result<bool, AsciiGenError> {
+        let wait = RECONNECT_DELAY
+            .saturating_mul(1 << (app.reconnects - 1).min(16))
+            .min(MAX_RECONNECT_DELAY);
+        let deadline = Instant::now() + wait;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            if !*running.lock().unwrap() {
+                return Ok(false);
+            }
+            app.status = Some(format!(
+                "Stream lost, reconnecting in {}s (try {} of {})",
+                left.as_secs() + 1,
+                app.reconnects,
+                retries
+            ));
+            let _ = terminal.draw(|frame| app.ui(frame));
+            app.handle_video_events(None, running)?;
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        app.status = Some(format!(
+            "Reconnecting (try {} of {})",
+            app.reconnects, retries
+        ));
+        let _ = terminal.draw(|frame| app.ui(frame));
+        Ok(*running.lock().unwrap())
+    }
 
-                    // Convert the frame to RGB
-                    let mut rgb_frame = ffmpeg::frame::Video::empty();
-                    scaler.run(&decoded, &mut rgb_frame)?;
+    /// Carries out a seek or speed change asked for over `--control`, moving the playback
+    /// clock to the current frame. Returns whether anything changed.
+    fn apply_transport(
+        &mut self,
+        converter: &mut VideoConverter,
+        start_time: &mut Instant,
+    ) -> Result<bool, AsciiGenError> {
+        let (seek_to, speed_to) = (self.seek_to.take(), self.speed_to.take());
+        if seek_to.is_none() && speed_to.is_none() {
+            return Ok(false);
+        }
+        let stream = converter.stream();
+        let mut transport = self.transport.lock().unwrap();
+        if let Some(speed) = speed_to {
+            stream.set_speed(speed);
+            transport.speed = speed;
+        }
+        if let Some(seconds) = seek_to {
+            if !stream.seek(seconds)? {
+                tracing::warn!(seconds, "cannot seek past the end of the video");
+            }
+            transport.seek = Some(seconds);
+        }
+        drop(transport);
 
-                    // Convert the frame to a grayscale ImageBuffer
-                    let image: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_raw(
-                        rgb_frame.width(),
-                        rgb_frame.height(),
-                        rgb_frame.data(0).iter().step_by(3).copied().collect(),
-                    )
-                    .unwrap();
+        // Timestamps are sped up, so the clock restarts as far back as the frame is
+        let elapsed = Duration::from_secs_f64(stream.timestamp().unwrap_or(0.0).max(0.0));
+        *start_time = Instant::now()
+            .checked_sub(elapsed)
+            .unwrap_or_else(Instant::now);
+        converter.set_start_time(Some(*start_time));
+        Ok(true)
+    }
+
+    /// Carries out the commands received over `--control`.
+    fn handle_control(&mut self, stream: Option<&VideoAsciiStream>, running: &Arc<Mutex<bool>>) {
+        let commands = self.control.as_ref().map(ControlServer::commands);
+        let Some(commands) = commands.filter(|commands| !commands.is_empty()) else {
+            return;
+        };
+        // Recorded seconds of the current frame, for relative seeks
+        let position = stream.and_then(|stream| stream.timestamp()).unwrap_or(0.0)
+            * self.transport.lock().unwrap().speed;
+        // Live streams without a length can only go back
+        let end = stream
+            .and_then(VideoAsciiStream::duration)
+            .unwrap_or(position)
+            .max(0.0);
+        for command in commands {
+            tracing::debug!(?command, "control command");
+            match command {
+                ControlCommand::Pause => *self.paused.lock().unwrap() = true,
+                ControlCommand::Play => *self.paused.lock().unwrap() = false,
+                ControlCommand::TogglePause => {
+                    let mut paused = self.paused.lock().unwrap();
+                    *paused = !*paused;
+                }
+                ControlCommand::Seek { seconds, relative } => {
+                    let target = if relative {
+                        position + seconds
+                    } else {
+                        seconds
+                    };
+                    self.seek_to = Some(target.clamp(0.0, end));
+                }
+                ControlCommand::Speed(speed) => {
+                    self.speed_to = Some(speed.clamp(SPEED_BOUNDS.0, SPEED_BOUNDS.1))
+                }
+                ControlCommand::Quit => *running.lock().unwrap() = false,
+            }
+        }
+    }
 
-                    // Convert the image to ASCII art
-                    let options = converter::AsciiOptions::new(args.width, args.width, args.gamma); // Adjust options as needed
-                    app.art = converter::ImageConverter::from_image_buffer(image)
-                        .to_ascii_art(Some(options));
+    /// Shows a frame, returning the one it replaces so that its buffers can be reused.
+    fn show_frame(&mut self, frame: AsciiFrame) -> AsciiFrame {
+        self.conversion_time = frame.convert_time;
+        AsciiFrame {
+            art: std::mem::replace(&mut self.frame.art, frame.art),
+            colors: std::mem::replace(&mut self.frame.colors, frame.colors),
+            ..AsciiFrame::default()
+        }
+    }
 
-                    // Draw the updated ASCII art in the terminal
-                    let _ = terminal.draw(|frame| app.ui(frame));
+    /// Handles the keys of video playback. Saving the frame needs the `stream` it came from.
+    fn handle_video_events(
+        &mut self,
+        mut stream: Option<&mut VideoAsciiStream>,
+        running: &Arc<Mutex<bool>>,
+    ) -> io::Result<()> {
+        if interrupted() {
+            *running.lock().unwrap() = false;
+        }
+        self.handle_control(stream.as_deref(), running);
+
+        while event::poll(Duration::ZERO)? {
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                Event::Resize(width, height) => {
+                    self.resize(Rect::new(0, 0, width, height));
+                    continue;
+                }
+                _ => continue,
+            };
+
+            // Keys go into the number being typed, if any
+            let action = match self.input {
+                Some(_) => None,
+                None => self.keys.video_action(key),
+            };
+
+            match action {
+                Some(Action::Quit) => *running.lock().unwrap() = false,
+                Some(Action::Screenshot) => {
+                    let art = color::to_ansi(&self.frame.art, &self.frame.colors, self.color_mode);
+                    self.status = Some(match screenshot::save_ascii(&art) {
+                        Ok(path) => format!("Saved {}", path.display()),
+                        Err(e) => format!("Save failed: {}", e),
+                    });
+                }
+                Some(Action::ToggleStats) => self.show_stats = !self.show_stats,
+                Some(Action::ToggleSpectrum) => self.show_spectrum = !self.show_spectrum,
+                Some(Action::TogglePause) => {
+                    let mut paused = self.paused.lock().unwrap();
+                    *paused = !*paused;
+                }
+                Some(Action::Copy) if *self.paused.lock().unwrap() => self.copy_art(),
+                Some(Action::ScrollUp) => self.scroll.0 = self.scroll.0.saturating_sub(1),
+                Some(Action::ScrollDown) => self.scroll.0 = self.scroll.0.saturating_add(1),
+                Some(Action::ScrollLeft) => self.scroll.1 = self.scroll.1.saturating_sub(1),
+                Some(Action::ScrollRight) => self.scroll.1 = self.scroll.1.saturating_add(1),
+                Some(Action::SaveFrame) => {
+                    let Some(stream) = stream.as_deref_mut() else {
+                        continue;
+                    };
+                    let saved = stream
+                        .rgb_frame()
+                        .map_err(Into::into)
+                        .and_then(screenshot::save_frame);
+                    self.status = Some(match saved {
+                        Ok(path) => format!("Saved {}", path.display()),
+                        Err(e) => format!("Save failed: {}", e),
+                    });
+                }
+                // The options apply from the next frame on
+                _ => {
+                    if self.handle_option_key(key, action) {
+                        *running.lock().unwrap() = false;
+                    }
                 }
             }
         }
@@ -200,64 +2815,217 @@ impl App {
         Ok(())
     }
 
-    fn play_audio(file: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let music_file = std::fs::File::open(file).unwrap();
-        let decoder = rodio::Decoder::new(BufReader::new(music_file)).unwrap();
-        let (_stream, stream_handle) = rodio::OutputStream::try_default()?;
-        let sink = rodio::Sink::try_new(&stream_handle)?;
+    fn play_audio(
+        file: &str,
+        running: &Arc<Mutex<bool>>,
+        paused: &Arc<Mutex<bool>>,
+        transport: &Arc<Mutex<Transport>>,
+        spectrum: &Arc<Mutex<Spectrum>>,
+        looping: bool,
+    ) -> Result<(), AsciiGenError> {
+        let music_file = std::fs::File::open(file).map_err(|source| AsciiGenError::Read {
+            path: PathBuf::from(file),
+            source,
+        })?;
+        let audio_error = |e: &dyn std::fmt::Display| AsciiGenError::Audio(e.to_string());
+        let decoder =
+            rodio::Decoder::new(BufReader::new(music_file)).map_err(|e| audio_error(&e))?;
+        let (_stream, stream_handle) = audio::output()?;
+        let sink = rodio::Sink::try_new(&stream_handle).map_err(|e| audio_error(&e))?;
+        let length = decoder.total_duration().map(|length| length.as_secs_f64());
+
+        // Tapped after repeating, as repeats play from a buffer rather than the decoder
+        if looping {
+            sink.append(Tap::new(decoder.repeat_infinite(), spectrum.clone()));
+        } else {
+            sink.append(Tap::new(decoder, spectrum.clone()));
+        }
 
-        sink.append(decoder);
+        // The sink counts time played rather than recorded, so the recorded position adds up
+        // what it played at each speed, starting over from the seconds of every seek
+        let (mut position, mut played) = (0.0, Duration::ZERO);
 
-        sink.sleep_until_end();
+        // Keep playing until the audio ends or the video stops, following pauses and seeks
+        while !sink.empty() && *running.lock().unwrap() && !interrupted() {
+            if *paused.lock().unwrap() {
+                sink.pause();
+            } else {
+                sink.play();
+            }
+            let mut transport = transport.lock().unwrap();
+            let now = sink.get_pos();
+            position += now.saturating_sub(played).as_secs_f64() * transport.speed;
+            played = now;
+            if let Some(seconds) = transport.seek.take() {
+                match sink.try_seek(Duration::from_secs_f64(seconds)) {
+                    Ok(()) => (position, played) = (seconds, Duration::from_secs_f64(seconds)),
+                    Err(e) => tracing::warn!(error = %e, "cannot seek the audio"),
+                }
+            }
+            sink.set_speed(transport.speed as f32);
+            // Repeats keep counting past the end, while the video starts over
+            transport.audio = Some(match length {
+                Some(length) if looping && length > 0.0 => position % length,
+                _ => position,
+            });
+            drop(transport);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        sink.stop();
+        transport.lock().unwrap().audio = None;
         Ok(())
     }
 
     fn ui(&self, frame: &mut Frame) {
-        let main_layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(80), Constraint::Percentage(20)].as_ref())
-            .split(frame.size());
+        let main_layout = main_layout(frame.size());
+
+        if self.compare {
+            let compare_layout = compare_layout(main_layout[0]);
+            frame.render_widget(
+                AsciiArtWidget::new(&self.original.art)
+                    .colors(&self.original.colors, ColorMode::Truecolor)
+                    .block(Block::default().borders(Borders::ALL).title("Original")),
+                compare_layout[0],
+            );
+            frame.render_widget(self.boxes_canvas(), compare_layout[1]);
+        } else {
+            frame.render_widget(self.boxes_canvas(), main_layout[0]);
+        }
+        if self.source.is_some() {
+            let options_layout = options_layout(main_layout[1]);
+            frame.render_widget(self.boxes_options(options_layout[0]), options_layout[0]);
+            if let Some(histogram) = &self.frame.histogram {
+                frame.render_widget(
+                    HistogramWidget::new(histogram, self.charset)
+                        .block(Block::default().borders(Borders::ALL).title("Luminance")),
+                    options_layout[1],
+                );
+            }
+            frame.render_widget(self.boxes_curve(), options_layout[2]);
+        } else if self.show_spectrum {
+            let areas = spectrum_layout(main_layout[1]);
+            frame.render_widget(self.boxes_options(areas[0]), areas[0]);
+            let mut spectrum = self.spectrum.lock().unwrap();
+            let bands = spectrum.bands(areas[1].width.saturating_sub(2).max(1) as usize / 2);
+            frame.render_widget(
+                SpectrumWidget::new(bands)
+                    .block(Block::default().borders(Borders::ALL).title("Spectrum")),
+                areas[1],
+            );
+        } else {
+            frame.render_widget(self.boxes_options(main_layout[1]), main_layout[1]);
+        }
+        frame.render_widget(self.status_bar(), main_layout[2]);
+
+        if let Some((start, end)) = self.selection {
+            let selected = Rect::new(
+                start.0.min(end.0),
+                start.1.min(end.1),
+                start.0.abs_diff(end.0) + 1,
+                start.1.abs_diff(end.1) + 1,
+            );
+            frame.buffer_mut().set_style(
+                selected.intersection(self.art_area()),
+                Style::default().add_modifier(Modifier::REVERSED),
+            );
+        }
+
+        if let Some(browser) = &self.browser {
+            let area = main_layout[0];
+            let mut state = ListState::default();
+            state.select(Some(browser.selected()));
+
+            frame.render_widget(Clear, area);
+            frame.render_stateful_widget(browser.widget(), area, &mut state);
+        }
+
+        if let Some(prompt) = &self.prompt {
+            let width = 40.min(frame.size().width);
+            let area = Rect::new(
+                (frame.size().width - width) / 2,
+                frame.size().height.saturating_sub(3) / 2,
+                width,
+                3.min(frame.size().height),
+            );
+
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(format!("{}_", prompt.text)).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(prompt.kind.title()),
+                ),
+                area,
+            );
+        }
+
+        if self.show_metadata && self.source.is_some() {
+            let metadata = self
+                .tabs
+                .get(self.current_tab)
+                .and_then(|tab| tab.metadata.clone())
+                .unwrap_or_default();
+            let lines = metadata.lines(self.source_dimensions);
+            let pane = main_layout[0];
+            let width = lines
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0) as u16;
+            let width = (width + 2).min(pane.width.saturating_sub(2));
+            let area = Rect::new(
+                (pane.x + pane.width).saturating_sub(width + 1),
+                pane.y + 1,
+                width,
+                (lines.len() as u16 + 2).min(pane.height.saturating_sub(2)),
+            );
+            let lines: Vec<text::Line> = lines.into_iter().map(text::Line::from).collect();
 
-        frame.render_widget(self.boxes_canvas(main_layout[0]), main_layout[0]);
-        frame.render_widget(self.boxes_options(main_layout[1]), main_layout[1]);
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Photo")),
+                area,
+            );
+        }
+
+        if self.show_stats {
+            let area = Rect::new(
+                main_layout[0].x + 1,
+                main_layout[0].y + 1,
+                24.min(main_layout[0].width.saturating_sub(2)),
+                8.min(main_layout[0].height.saturating_sub(2)),
+            );
+            let lines: Vec<text::Line> = self
+                .stats
+                .lines()
+                .into_iter()
+                .map(text::Line::from)
+                .collect();
+
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Stats")),
+                area,
+            );
+        }
     }
 
     fn boxes_options(&self, area: Rect) -> impl Widget {
         let (left, right, bottom, top) =
             (0.0, area.width as f64, 0.0, area.height as f64 * 2.0 - 4.0);
 
-        let width = self.width.to_string();
-        let width_text = format!(
-            "Width: {} {}",
-            width,
-            if self.selected_field == Fields::Width {
-                "<"
-            } else {
-                ""
-            }
-        );
-
-        let height = self.height.to_string();
-        let height_text = format!(
-            "Height: {} {}",
-            height,
-            if self.selected_field == Fields::Height {
-                "<"
-            } else {
-                ""
-            }
-        );
+        let width_text = self.field_text(Fields::Width, "Width", self.width.to_string());
+        let height_text = self.field_text(Fields::Height, "Height", self.height.to_string());
+        let gamma_text = self.field_text(Fields::Gamma, "Gamma", format!("{:.1}", self.gamma));
+        let charset_text = self.field_text(Fields::Charset, "Charset", self.charset.name());
+        let invert_text = self.field_text(Fields::Invert, "Invert", self.invert.to_string());
+        let color_text = self.field_text(Fields::Color, "Color", self.color_mode.name());
+        let fit_text = self.field_text(Fields::Fit, "Fit", self.fit.to_string());
+        let lock_text = format!("Aspect lock: {}", self.aspect_lock);
+        let zoom_text = format!("Zoom: {}x", self.zoom);
+        let preset_text = format!("Preset: {}", self.preset_name.as_deref().unwrap_or("-"));
 
-        let gamma = self.gamma.to_string();
-        let gamma_text = format!(
-            "Gamma: {} {}",
-            gamma,
-            if self.selected_field == Fields::Gamma {
-                "<"
-            } else {
-                ""
-            }
-        );
+        let status_text = self.status.clone().unwrap_or_default();
 
         let confirm_text = format!(
             "Confirm {}",
@@ -284,52 +3052,278 @@ impl App {
                 ctx.print(2.0, top - 4.0, width_text.clone());
                 ctx.print(2.0, top - 6.0, height_text.clone());
                 ctx.print(2.0, top - 8.0, gamma_text.clone());
+                ctx.print(2.0, top - 10.0, charset_text.clone());
+                ctx.print(2.0, top - 12.0, invert_text.clone());
+                ctx.print(2.0, top - 14.0, color_text.clone());
+                ctx.print(2.0, top - 16.0, fit_text.clone());
+                ctx.print(2.0, top - 18.0, lock_text.clone());
+                ctx.print(2.0, top - 20.0, zoom_text.clone());
+                ctx.print(2.0, top - 22.0, preset_text.clone());
+                ctx.print(2.0, bottom + 3.0, status_text.clone());
                 ctx.print(2.0, bottom + 1.0, confirm_text.clone());
             })
     }
 
-    fn boxes_canvas(&self, area: Rect) -> impl Widget {
-        let (left, right, bottom, top) =
-            (0.0, area.width as f64, 0.0, area.height as f64 * 2.0 - 4.0);
-
-        let art = self.art.clone();
+    /// The tone curve, with its control points marked for dragging.
+    fn boxes_curve(&self) -> impl Widget {
+        let levels = self.curve.levels();
+        let dragged = self.curve_drag.as_ref().map(|(point, _)| *point);
 
         Canvas::default()
-            .block(Block::default().borders(Borders::ALL).title("Art"))
-            .x_bounds([left, right])
-            .y_bounds([bottom, top])
+            .block(Block::default().borders(Borders::ALL).title("Curve"))
+            .x_bounds([0.0, 255.0])
+            .y_bounds([0.0, 255.0])
             .paint(move |ctx| {
-                ctx.draw(&Rectangle {
-                    x: left,
-                    y: bottom,
-                    width: right - left,
-                    height: top - bottom,
-                    color: Color::White,
+                ctx.draw(&canvas::Line {
+                    x1: 0.0,
+                    y1: 0.0,
+                    x2: 255.0,
+                    y2: 255.0,
+                    color: Color::DarkGray,
                 });
-                let mut x = 1.0;
-                let mut y = top - 2.0;
-
-                for c in art.chars() {
-                    if c == '\n' {
-                        x = 1.0;
-                        y -= 1.0;
-                        continue;
-                    }
-                    ctx.print(x, y, c.to_string());
-                    x += 1.0;
+                for point in 1..CONTROL_POINTS {
+                    ctx.draw(&canvas::Line {
+                        x1: ToneCurve::input(point - 1) as f64,
+                        y1: levels[point - 1] as f64,
+                        x2: ToneCurve::input(point) as f64,
+                        y2: levels[point] as f64,
+                        color: Color::White,
+                    });
+                }
+                ctx.layer();
+                for (point, &level) in levels.iter().enumerate() {
+                    let color = if dragged == Some(point) {
+                        Color::Yellow
+                    } else {
+                        Color::Cyan
+                    };
+                    ctx.print(
+                        ToneCurve::input(point) as f64,
+                        level as f64,
+                        Span::styled("o", Style::default().fg(color)),
+                    );
                 }
             })
     }
+
+    /// One line of metrics about the art on screen.
+    fn status_bar(&self) -> impl Widget {
+        let (columns, rows) = self.art_size();
+        let (source_width, source_height) = self.source_dimensions;
+
+        Paragraph::new(format!(
+            " {:.1} ms | {}x{} chars | {} bytes | source {}x{}",
+            self.conversion_time.as_secs_f64() * 1000.0,
+            columns,
+            rows,
+            self.frame.art.len(),
+            source_width,
+            source_height
+        ))
+        .style(Style::default().add_modifier(Modifier::REVERSED))
+    }
+
+    /// Formats an option line, marking it when selected.
+    fn field_text(&self, field: Fields, label: &str, value: impl std::fmt::Display) -> String {
+        if let Some(input) = self.input.as_ref().filter(|_| self.selected_field == field) {
+            return format!("{}: {}_ <", label, input);
+        }
+
+        format!(
+            "{}: {} {}",
+            label,
+            value,
+            if self.selected_field == field {
+                "<"
+            } else {
+                ""
+            }
+        )
+    }
+
+    fn boxes_canvas(&self) -> impl Widget + '_ {
+        let mut title = String::from("Art");
+        if self.tabs.len() > 1 {
+            let name = &self.tabs[self.current_tab].name;
+            title = format!("Art {}/{}: {}", self.current_tab + 1, self.tabs.len(), name);
+        }
+        if self.is_converting() {
+            title.push_str(" (converting…)");
+        }
+
+        AsciiArtWidget::new(&self.frame.art)
+            .colors(&self.frame.colors, self.color_mode)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .scroll(self.scroll)
+    }
+}
+
+/// Splits the screen into the art pane, the options pane and the status bar.
+fn main_layout(area: Rect) -> [Rect; 3] {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(80), Constraint::Percentage(20)].as_ref())
+        .split(rows[0]);
+
+    [columns[0], columns[1], rows[1]]
+}
+
+/// Splits the options pane of the video player into the options and the audio spectrum.
+fn spectrum_layout(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(12)].as_ref())
+        .split(area)
+}
+
+/// Splits the options pane into the options, the luminance histogram and the tone curve.
+fn options_layout(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(8),
+                Constraint::Length(10),
+            ]
+            .as_ref(),
+        )
+        .split(area)
+}
+
+/// Splits the art pane into the original and the art when comparing.
+fn compare_layout(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area)
+}
+
+/// The area of the screen showing the art, including its border.
+fn art_pane(area: Rect) -> Rect {
+    main_layout(area)[0]
+}
+
+/// Terminal used by the TUI, optionally recording everything drawn.
+type Tui = Terminal<CrosstermBackend<SessionWriter>>;
+
+/// The TUI's terminal, given back to the shell when dropped so an error cannot leave it in
+/// raw mode.
+struct TerminalGuard {
+    terminal: Tui,
+}
+
+impl TerminalGuard {
+    /// Gives the terminal back now, reporting a failure that dropping would ignore.
+    fn restore(self) -> io::Result<()> {
+        // Dropping afterwards finds the terminal already restored
+        restore_terminal()
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Tui;
+
+    fn deref(&self) -> &Tui {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Tui {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+fn init_terminal(record: Option<&str>) -> io::Result<TerminalGuard> {
+    TERMINAL_ACTIVE.store(true, Ordering::Relaxed);
+    let entered = enable_raw_mode()
+        .and_then(|()| {
+            stdout()
+                .execute(EnterAlternateScreen)?
+                .execute(EnableMouseCapture)?;
+            Terminal::new(CrosstermBackend::new(SessionWriter::new(stdout(), record)?))
+        })
+        .map(|terminal| TerminalGuard { terminal });
+    if entered.is_err() {
+        let _ = restore_terminal();
+    }
+    entered
 }
 
-fn init_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    Terminal::new(CrosstermBackend::new(stdout()))
+/// Blocks until a key is pressed.
+fn wait_for_key() -> io::Result<()> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(());
+            }
+        }
+    }
 }
 
 fn restore_terminal() -> io::Result<()> {
+    // The panic hook and a dropped guard may both get here
+    if !TERMINAL_ACTIVE.swap(false, Ordering::Relaxed) {
+        return Ok(());
+    }
     disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
+    stdout()
+        .execute(DisableMouseCapture)?
+        .execute(LeaveAlternateScreen)?;
+    logging::flush_held();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// An editor with two tabs of blank images, showing the first.
+    fn editor_with_tabs() -> App {
+        let cli = Cli::try_parse_from(["ascii-gen", "live", "a.png", "b.png"]).unwrap();
+        let mut app = App::new(&cli.into_command().args(None));
+        for name in ["a.png", "b.png"] {
+            let image = DynamicImage::new_rgb8(8, 8);
+            let source = Arc::new(converter::ImageConverter::from_image(image));
+            app.set_source(source.clone());
+            app.tabs.push(Tab {
+                name: name.to_string(),
+                source,
+                metadata: None,
+                state: app.edit_state(),
+                undo_history: Vec::new(),
+                redo_history: Vec::new(),
+            });
+        }
+        app.set_source(app.tabs[0].source.clone());
+        app
+    }
+
+    #[test]
+    fn switching_tabs_drops_a_half_typed_number() {
+        let mut app = editor_with_tabs();
+        app.selected_field = Fields::Width;
+        let width = app.width;
+
+        app.handle_editor_key(KeyEvent::from(KeyCode::Char('4')));
+        assert_eq!(app.input.as_deref(), Some("4"));
+        app.handle_editor_key(KeyEvent::new(KeyCode::Char('2'), KeyModifiers::ALT));
+        assert_eq!(app.current_tab, 1);
+        assert_eq!(app.input, None);
+
+        app.handle_editor_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.width, width);
+    }
+}
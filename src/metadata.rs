@@ -0,0 +1,116 @@
+use exif::{In, Tag, Value};
+use image::DynamicImage;
+use std::path::Path;
+
+/// How a photo was taken, from its EXIF tags.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImageMetadata {
+    /// Make and model, e.g. `Apple iPhone 13`.
+    pub camera: Option<String>,
+    /// When the photo was taken, as `YYYY-MM-DD HH:MM:SS` in the camera's local time.
+    pub captured: Option<String>,
+    pub orientation: Orientation,
+}
+
+/// How the stored pixels are turned relative to the scene, EXIF orientations 1 to 8.
+/// Phones store portrait photos sideways and set this instead of rotating the pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Orientation {
+    #[default]
+    Normal,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    Transpose,
+    Rotate90,
+    Transverse,
+    Rotate270,
+}
+
+impl Orientation {
+    /// The orientation of an EXIF `Orientation` value, where out-of-range values are normal.
+    pub fn from_exif(value: u32) -> Self {
+        match value {
+            2 => Orientation::FlipHorizontal,
+            3 => Orientation::Rotate180,
+            4 => Orientation::FlipVertical,
+            5 => Orientation::Transpose,
+            6 => Orientation::Rotate90,
+            7 => Orientation::Transverse,
+            8 => Orientation::Rotate270,
+            _ => Orientation::Normal,
+        }
+    }
+
+    /// Turns the stored image upright, rotations being clockwise.
+    ///
+    /// ```
+    /// use ascii_gen::metadata::Orientation;
+    ///
+    /// let sideways = image::DynamicImage::new_rgb8(4, 3);
+    /// assert_eq!(Orientation::from_exif(6).apply(sideways).width(), 3);
+    /// ```
+    pub fn apply(self, image: DynamicImage) -> DynamicImage {
+        match self {
+            Orientation::Normal => image,
+            Orientation::FlipHorizontal => image.fliph(),
+            Orientation::Rotate180 => image.rotate180(),
+            Orientation::FlipVertical => image.flipv(),
+            Orientation::Transpose => image.rotate90().fliph(),
+            Orientation::Rotate90 => image.rotate90(),
+            Orientation::Transverse => image.rotate270().fliph(),
+            Orientation::Rotate270 => image.rotate270(),
+        }
+    }
+}
+
+impl ImageMetadata {
+    /// Reads the EXIF tags of a JPEG, TIFF, PNG, WebP or HEIF file, or `None` if it has
+    /// none.
+    pub fn read(path: impl AsRef<Path>) -> Option<Self> {
+        let file = std::fs::File::open(path).ok()?;
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::BufReader::new(file))
+            .ok()?;
+        let ascii = |tag: Tag| match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Ascii(values) => {
+                let text = String::from_utf8_lossy(values.first()?);
+                let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+                (!text.is_empty()).then(|| text.to_string())
+            }
+            _ => None,
+        };
+
+        // Models often repeat the make, as in `Canon` and `Canon EOS R5`
+        let camera = match (ascii(Tag::Make), ascii(Tag::Model)) {
+            (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+            (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+            (make, model) => make.or(model),
+        };
+        let captured = ascii(Tag::DateTimeOriginal)
+            .or_else(|| ascii(Tag::DateTime))
+            .map(|text| match exif::DateTime::from_ascii(text.as_bytes()) {
+                Ok(date) => date.to_string(),
+                Err(_) => text,
+            });
+        let orientation = exif
+            .get_field(Tag::Orientation, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .map_or(Orientation::Normal, Orientation::from_exif);
+
+        Some(Self {
+            camera,
+            captured,
+            orientation,
+        })
+    }
+
+    /// Lines describing the photo, with the `dimensions` it is shown at.
+    pub fn lines(&self, dimensions: (u32, u32)) -> Vec<String> {
+        vec![
+            format!("Camera: {}", self.camera.as_deref().unwrap_or("unknown")),
+            format!("Size: {}x{}", dimensions.0, dimensions.1),
+            format!("Taken: {}", self.captured.as_deref().unwrap_or("unknown")),
+        ]
+    }
+}
@@ -0,0 +1,124 @@
+use crate::color::{CellColor, ColorMode};
+use crate::converter::PixelSource;
+use crate::raster;
+use image::{imageops, imageops::FilterType, GrayImage, Rgb, RgbImage};
+use std::fmt;
+
+/// Side of the square windows the structural similarity is averaged over.
+const WINDOW: u32 = 8;
+// Keep the similarity stable in flat windows, as in the original SSIM paper
+const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// How closely art resembles its source once drawn, higher being closer for both.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fidelity {
+    /// Structural similarity of the luminance, 1.0 for identical images.
+    pub ssim: f64,
+    /// Peak signal-to-noise ratio of the luminance in decibels, infinite for identical
+    /// images.
+    pub psnr: f64,
+}
+
+impl fmt::Display for Fidelity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SSIM {:.3}, PSNR {:.2} dB", self.ssim, self.psnr)
+    }
+}
+
+/// Rasterizes the art, downscales `source` to the same size and compares the two, so
+/// charsets, color modes and sizes can be weighed against each other.
+///
+/// ```
+/// use ascii_gen::{color::ColorMode, metrics};
+/// use ascii_gen::converter::{AsciiOptions, ImageConverter, ToAsciiArt};
+///
+/// let source = ImageConverter::from_image(image::DynamicImage::new_rgb8(320, 320));
+/// let art = source.to_ascii_art(Some(AsciiOptions::new(40, 20, 1.0)))?;
+/// // Black is drawn as '#', which is far from black once drawn
+/// let fidelity = metrics::score(&source, &art, &[], ColorMode::None);
+/// assert!(fidelity.psnr < 10.0);
+/// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+/// ```
+pub fn score(
+    source: &impl PixelSource,
+    art: &str,
+    colors: &[CellColor],
+    color_mode: ColorMode,
+) -> Fidelity {
+    let rendered = raster::rasterize(art, colors, color_mode);
+    let (width, height) = source.dimensions();
+    let original = RgbImage::from_fn(width, height, |x, y| Rgb(source.rgb(x, y)));
+    let original = imageops::resize(
+        &original,
+        rendered.width(),
+        rendered.height(),
+        FilterType::Triangle,
+    );
+    compare(
+        &imageops::grayscale(&original),
+        &imageops::grayscale(&rendered),
+    )
+}
+
+/// Compares two grayscale images of the same size.
+pub fn compare(reference: &GrayImage, rendered: &GrayImage) -> Fidelity {
+    Fidelity {
+        ssim: ssim(reference, rendered),
+        psnr: psnr(reference, rendered),
+    }
+}
+
+fn psnr(reference: &GrayImage, rendered: &GrayImage) -> f64 {
+    let pixels = reference.len().max(1) as f64;
+    let squared_error: f64 = reference
+        .iter()
+        .zip(rendered.iter())
+        .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+        .sum();
+    let mse = squared_error / pixels;
+    if mse == 0.0 {
+        return f64::INFINITY;
+    }
+    10.0 * (255.0 * 255.0 / mse).log10()
+}
+
+/// Mean SSIM over non-overlapping windows, the last ones cut short at the edges.
+fn ssim(reference: &GrayImage, rendered: &GrayImage) -> f64 {
+    let (width, height) = reference.dimensions();
+    let mut total = 0.0;
+    let mut windows = 0;
+
+    for top in (0..height).step_by(WINDOW as usize) {
+        for left in (0..width).step_by(WINDOW as usize) {
+            let pairs: Vec<(f64, f64)> = (top..(top + WINDOW).min(height))
+                .flat_map(|y| (left..(left + WINDOW).min(width)).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    let a = reference.get_pixel(x, y).0[0] as f64;
+                    let b = rendered.get_pixel(x, y).0[0] as f64;
+                    (a, b)
+                })
+                .collect();
+
+            let count = pairs.len() as f64;
+            let mean_a = pairs.iter().map(|&(a, _)| a).sum::<f64>() / count;
+            let mean_b = pairs.iter().map(|&(_, b)| b).sum::<f64>() / count;
+            let (mut var_a, mut var_b, mut covariance) = (0.0, 0.0, 0.0);
+            for &(a, b) in &pairs {
+                var_a += (a - mean_a).powi(2);
+                var_b += (b - mean_b).powi(2);
+                covariance += (a - mean_a) * (b - mean_b);
+            }
+            let (var_a, var_b, covariance) = (var_a / count, var_b / count, covariance / count);
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    if windows == 0 {
+        return 1.0;
+    }
+    total / windows as f64
+}
@@ -0,0 +1,196 @@
+use crate::cli::{Args, OnEnd};
+use crate::control::Transport;
+use crate::{compare, init_terminal, interrupted, App};
+use ascii_gen::art::ArtBuffer;
+use ascii_gen::converter::ImageConverter;
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::spectrum::Spectrum;
+use ascii_gen::stream;
+use ascii_gen::track::Track;
+use ascii_gen::widget::{AsciiArtWidget, SpectrumWidget};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{prelude::*, widgets::*};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Rows of the spectrum under the cover.
+const SPECTRUM_ROWS: u16 = 6;
+/// Rows of the title, artist and album under the spectrum.
+const INFO_ROWS: u16 = 3;
+
+/// Plays an audio file with its cover art, a spectrum of the music and its tags on screen
+/// until it ends or is quit.
+/// Space pauses; `--on-end loop` repeats it.
+pub fn run(file: &str, args: &Args) -> Result<(), AsciiGenError> {
+    let mut track = Track::open(file)?;
+    let cover = track.cover.take().map(ImageConverter::from_image);
+
+    let running = Arc::new(Mutex::new(true));
+    let paused = Arc::new(Mutex::new(false));
+    let spectrum = Arc::new(Mutex::new(Spectrum::default()));
+    let audio_thread = {
+        let (file, running, paused) = (file.to_string(), running.clone(), paused.clone());
+        let spectrum = spectrum.clone();
+        let looping = args.playback.on_end == OnEnd::Loop;
+        let transport = Arc::new(Mutex::new(Transport::default()));
+        std::thread::spawn(move || {
+            App::play_audio(&file, &running, &paused, &transport, &spectrum, looping)
+        })
+    };
+
+    let mut terminal = init_terminal(args.tui.record.as_deref())?;
+    let mut pane = ArtBuffer::default();
+    let mut converted_for = None;
+    let mut elapsed = Duration::ZERO;
+    let mut last_tick = Instant::now();
+
+    while *running.lock().unwrap() && !audio_thread.is_finished() {
+        if interrupted() {
+            break;
+        }
+        let now = Instant::now();
+        if !*paused.lock().unwrap() {
+            elapsed += now - last_tick;
+        }
+        last_tick = now;
+
+        // Convert the cover again only once its pane changes size
+        let area = terminal.size()?;
+        if converted_for != Some(area) {
+            if let Some(cover) = &cover {
+                let inner = Block::default()
+                    .borders(Borders::ALL)
+                    .inner(layout(area)[0]);
+                let options =
+                    compare::convert_pane(&mut pane, cover, args.convert.charset, inner, args)?;
+                if let Some(mapper) = &args.mapper {
+                    pane.remap(mapper.as_ref(), &options, args.convert.color);
+                }
+            }
+            converted_for = Some(area);
+        }
+
+        let is_paused = *paused.lock().unwrap();
+        terminal.draw(|frame| {
+            let areas = layout(frame.size());
+            let block = Block::default().borders(Borders::ALL);
+            match &cover {
+                Some(_) => frame.render_widget(
+                    AsciiArtWidget::new(&pane.art)
+                        .colors(&pane.colors, args.convert.color)
+                        .block(block),
+                    areas[0],
+                ),
+                None => frame.render_widget(
+                    Paragraph::new("No cover art")
+                        .alignment(Alignment::Center)
+                        .block(block),
+                    areas[0],
+                ),
+            }
+            let mut spectrum = spectrum.lock().unwrap();
+            frame.render_widget(
+                SpectrumWidget::new(spectrum.bands(areas[1].width as usize / 2)),
+                areas[1],
+            );
+            frame.render_widget(info(&track, file), areas[2]);
+            frame.render_widget(progress(&track, elapsed, is_paused), areas[3]);
+            frame.render_widget(
+                Paragraph::new(" space pauses | q quits")
+                    .style(Style::default().add_modifier(Modifier::REVERSED)),
+                areas[4],
+            );
+        })?;
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char(' ') => {
+                let mut paused = paused.lock().unwrap();
+                *paused = !*paused;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => break,
+            _ => {}
+        }
+    }
+
+    // Stop the audio along with the screen, even after a panic
+    *running.lock().unwrap() = false;
+    let audio_result = audio_thread
+        .join()
+        .unwrap_or(Err(AsciiGenError::Panicked("audio")));
+    terminal.restore()?;
+    audio_result
+}
+
+/// The title, falling back to the file name, over the artist and album.
+fn info<'a>(track: &'a Track, file: &'a str) -> Paragraph<'a> {
+    let title = track.title.as_deref().unwrap_or_else(|| {
+        std::path::Path::new(file)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(file)
+    });
+    let lines = vec![
+        Line::styled(title, Style::default().add_modifier(Modifier::BOLD)),
+        Line::raw(track.artist.as_deref().unwrap_or("Unknown artist")),
+        Line::styled(
+            track.album.as_deref().unwrap_or(""),
+            Style::default().add_modifier(Modifier::ITALIC),
+        ),
+    ];
+    Paragraph::new(lines).alignment(Alignment::Center)
+}
+
+/// How far into the track playback is, wrapping around when it loops.
+fn progress(track: &Track, elapsed: Duration, paused: bool) -> Gauge<'static> {
+    let elapsed = elapsed.as_secs_f64();
+    let (ratio, label) = match track.duration.filter(|&duration| duration > 0.0) {
+        Some(duration) => {
+            let position = elapsed % duration;
+            (
+                position / duration,
+                format!(
+                    "{} / {}",
+                    stream::format_timestamp(position),
+                    stream::format_timestamp(duration)
+                ),
+            )
+        }
+        None => (0.0, stream::format_timestamp(elapsed)),
+    };
+    let label = if paused {
+        format!("{} (paused)", label)
+    } else {
+        label
+    };
+    Gauge::default()
+        .gauge_style(Style::default().fg(Color::Cyan))
+        .ratio(ratio.clamp(0.0, 1.0))
+        .label(label)
+}
+
+/// Splits the screen into the cover, the spectrum, the tags, the progress bar and the help
+/// line.
+fn layout(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(3),
+                Constraint::Length(SPECTRUM_ROWS),
+                Constraint::Length(INFO_ROWS),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(area)
+}
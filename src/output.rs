@@ -0,0 +1,113 @@
+use crate::color::{self, CellColor, ColorMode};
+use serde::{Deserialize, Serialize};
+
+/// Format used when printing or saving the art.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Plain text without colors
+    Text,
+    /// Text with ANSI color escape codes when a color mode is selected
+    Ansi,
+    /// An HTML page with a colored `<pre>` block
+    Html,
+}
+
+/// Turns the art, one line per row, and the color of each cell into the final output.
+///
+/// `OutputFormat` picks one of the built-in renderers; implement this for other targets,
+/// such as a game engine's text layer.
+pub trait Renderer {
+    fn render(&self, art: &str, colors: &[CellColor], mode: ColorMode) -> String;
+}
+
+/// Plain text without colors.
+pub struct TextRenderer;
+
+/// Text with ANSI color escape codes.
+pub struct AnsiRenderer;
+
+/// A standalone HTML page with a colored `<pre>` block.
+pub struct HtmlRenderer;
+
+impl Renderer for TextRenderer {
+    fn render(&self, art: &str, _colors: &[CellColor], _mode: ColorMode) -> String {
+        art.to_string()
+    }
+}
+
+impl Renderer for AnsiRenderer {
+    fn render(&self, art: &str, colors: &[CellColor], mode: ColorMode) -> String {
+        color::to_ansi(art, colors, mode)
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, art: &str, colors: &[CellColor], mode: ColorMode) -> String {
+        to_html(art, colors, mode)
+    }
+}
+
+impl OutputFormat {
+    /// File extension for art saved in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Ansi => "ans",
+            OutputFormat::Html => "html",
+        }
+    }
+
+    /// The built-in renderer of the format.
+    pub fn renderer(self) -> &'static dyn Renderer {
+        match self {
+            OutputFormat::Text => &TextRenderer,
+            OutputFormat::Ansi => &AnsiRenderer,
+            OutputFormat::Html => &HtmlRenderer,
+        }
+    }
+}
+
+/// Renders the art in the given format.
+pub fn render(art: &str, colors: &[CellColor], mode: ColorMode, format: OutputFormat) -> String {
+    format.renderer().render(art, colors, mode)
+}
+
+/// Renders the art as a standalone HTML page.
+fn to_html(art: &str, colors: &[CellColor], mode: ColorMode) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<body style=\"background:#000;color:#fff\">\n\
+         <pre style=\"font-family:monospace;line-height:1\">",
+    );
+    let mut cells = colors.iter().filter(|_| mode.is_enabled());
+
+    for c in art.chars() {
+        if c == '\n' {
+            html.push('\n');
+            continue;
+        }
+
+        let escaped = match c {
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '&' => "&amp;".to_string(),
+            c => c.to_string(),
+        };
+
+        match cells.next() {
+            Some(cell) => {
+                let [r, g, b] = cell.fg;
+                html.push_str(&format!("<span style=\"color:#{:02x}{:02x}{:02x}", r, g, b));
+                if let Some([r, g, b]) = cell.bg {
+                    html.push_str(&format!(";background:#{:02x}{:02x}{:02x}", r, g, b));
+                }
+                html.push_str(&format!("\">{}</span>", escaped));
+            }
+            None => html.push_str(&escaped),
+        }
+    }
+
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
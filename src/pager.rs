@@ -0,0 +1,50 @@
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Pager run when `$PAGER` is not set, showing colors instead of their escape codes.
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Shows the text through `$PAGER`, or `less -R`, when it is printed to a terminal too short
+/// for it. Returns false when it was not paged, for the caller to print it as usual.
+pub fn page(text: &str) -> bool {
+    if !io::stdout().is_terminal() {
+        return false;
+    }
+    let Ok((_, rows)) = crossterm::terminal::size() else {
+        return false;
+    };
+    // The last line goes to the shell prompt
+    if text.lines().count() < rows as usize {
+        return false;
+    }
+
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut words = pager.split_whitespace();
+    let Some(program) = words.next() else {
+        return false;
+    };
+    let mut command = Command::new(program);
+    command.args(words).stdin(Stdio::piped());
+    // Like git: keep colors, and leave the text on screen after quitting
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::debug!(pager, error = %e, "cannot start the pager");
+            return false;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe, which is no error
+        if let Err(e) = writeln!(stdin, "{}", text) {
+            tracing::debug!(error = %e, "the pager closed early");
+        }
+    }
+    if let Err(e) = child.wait() {
+        tracing::warn!(error = %e, "waiting for the pager failed");
+    }
+    true
+}
@@ -0,0 +1,241 @@
+use crate::color::CellColor;
+use crate::converter::PixelSource;
+use crate::random::{XorShift, DEFAULT_SEED};
+use serde::{Deserialize, Serialize};
+
+/// Most pixels k-means looks at; larger images are sampled on an even grid.
+const MAX_SAMPLES: u64 = 20_000;
+/// Rounds of k-means before settling for the clusters as they are.
+const MAX_ROUNDS: usize = 20;
+/// Most colors a palette is extracted with.
+pub const MAX_COLORS: usize = 64;
+
+/// A fixed set of colors the art's colors are snapped to.
+///
+/// Stored in the config file as hex codes, e.g. `palette = ["#282828", "#cc241d"]`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub struct Palette {
+    colors: Vec<[u8; 3]>,
+}
+
+/// A dominant color and how much of the image it covers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Swatch {
+    pub rgb: [u8; 3],
+    /// Share of the pixels closest to the color, from 0 to 1.
+    pub share: f32,
+}
+
+impl Palette {
+    /// Creates a palette of the colors, or `None` if there are none.
+    pub fn new(colors: Vec<[u8; 3]>) -> Option<Self> {
+        (!colors.is_empty()).then_some(Self { colors })
+    }
+
+    pub fn colors(&self) -> &[[u8; 3]] {
+        &self.colors
+    }
+
+    /// The palette color closest to `rgb`.
+    ///
+    /// ```
+    /// use ascii_gen::palette::Palette;
+    ///
+    /// let palette = Palette::new(vec![[0, 0, 0], [255, 0, 0]]).unwrap();
+    /// assert_eq!(palette.nearest([200, 30, 20]), [255, 0, 0]);
+    /// ```
+    pub fn nearest(&self, rgb: [u8; 3]) -> [u8; 3] {
+        self.colors[nearest_index(&self.colors, rgb)]
+    }
+
+    /// Snaps the foreground and background of every cell to the palette.
+    pub fn quantize(&self, colors: &mut [CellColor]) {
+        for cell in colors {
+            cell.fg = self.nearest(cell.fg);
+            cell.bg = cell.bg.map(|bg| self.nearest(bg));
+        }
+    }
+}
+
+impl TryFrom<Vec<String>> for Palette {
+    type Error = String;
+
+    fn try_from(codes: Vec<String>) -> Result<Self, Self::Error> {
+        let colors = codes
+            .iter()
+            .map(|code| parse_hex(code).ok_or_else(|| format!("{} is not a #rrggbb color", code)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(colors).ok_or_else(|| "a palette needs at least one color".to_string())
+    }
+}
+
+impl From<Palette> for Vec<String> {
+    fn from(palette: Palette) -> Self {
+        palette.colors.iter().map(|&rgb| to_hex(rgb)).collect()
+    }
+}
+
+/// Parses a palette given as comma-separated hex codes, e.g. `#282828,#cc241d,#98971a`.
+pub fn parse_palette(text: &str) -> Result<Palette, String> {
+    let codes: Vec<String> = text
+        .split(',')
+        .map(|code| code.trim().to_string())
+        .collect();
+    Palette::try_from(codes)
+}
+
+/// Parses a color given as `#rrggbb`, the `#` being optional.
+pub fn parse_hex(text: &str) -> Option<[u8; 3]> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Formats a color as `#rrggbb`.
+pub fn to_hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+/// Finds the `count` dominant colors of the source with k-means, the most common first.
+/// Fewer come back when the source has fewer distinct colors. The same source always gives
+/// the same palette; `extract_seeded` picks the starting colors with another seed.
+///
+/// ```
+/// use ascii_gen::{converter::ImageConverter, palette};
+///
+/// let mut image = image::RgbImage::new(40, 10);
+/// for (x, _, pixel) in image.enumerate_pixels_mut() {
+///     pixel.0 = if x < 30 { [20, 20, 200] } else { [250, 250, 0] };
+/// }
+/// let source = ImageConverter::from_image(image::DynamicImage::ImageRgb8(image));
+/// let swatches = palette::extract(&source, 2);
+/// assert_eq!(swatches[0].rgb, [20, 20, 200]);
+/// assert_eq!(swatches[1].rgb, [250, 250, 0]);
+/// ```
+pub fn extract(source: &impl PixelSource, count: usize) -> Vec<Swatch> {
+    extract_seeded(source, count, DEFAULT_SEED)
+}
+
+/// Same as `extract`, with the k-means starting colors drawn with `seed`.
+pub fn extract_seeded(source: &impl PixelSource, count: usize, seed: u64) -> Vec<Swatch> {
+    let samples = sample(source);
+    if samples.is_empty() {
+        return Vec::new();
+    }
+    let mut centers = initial_centers(&samples, count.clamp(1, MAX_COLORS), seed);
+    let mut assignments = vec![usize::MAX; samples.len()];
+
+    for _ in 0..MAX_ROUNDS {
+        let mut moved = false;
+        for (assignment, &rgb) in assignments.iter_mut().zip(&samples) {
+            let nearest = nearest_index(&centers, rgb);
+            moved |= *assignment != nearest;
+            *assignment = nearest;
+        }
+        if !moved {
+            break;
+        }
+
+        let mut sums = vec![[0u64; 4]; centers.len()];
+        for (&assignment, &rgb) in assignments.iter().zip(&samples) {
+            let sum = &mut sums[assignment];
+            for (total, &value) in sum.iter_mut().zip(&rgb) {
+                *total += value as u64;
+            }
+            sum[3] += 1;
+        }
+        for (center, sum) in centers.iter_mut().zip(&sums) {
+            // A center nothing is closest to stays, and is dropped at the end
+            if sum[3] > 0 {
+                *center = std::array::from_fn(|channel| (sum[channel] / sum[3]) as u8);
+            }
+        }
+    }
+
+    let mut counts = vec![0usize; centers.len()];
+    for &assignment in &assignments {
+        counts[assignment] += 1;
+    }
+    let mut swatches: Vec<Swatch> = centers
+        .iter()
+        .zip(&counts)
+        .filter(|(_, &count)| count > 0)
+        .map(|(&rgb, &count)| Swatch {
+            rgb,
+            share: count as f32 / samples.len() as f32,
+        })
+        .collect();
+    swatches.sort_by(|a, b| b.share.total_cmp(&a.share));
+    swatches
+}
+
+/// The source's pixels, on a grid coarse enough to keep at most `MAX_SAMPLES` of them.
+fn sample(source: &impl PixelSource) -> Vec<[u8; 3]> {
+    let (width, height) = source.dimensions();
+    let pixels = width as u64 * height as u64;
+    let step = ((pixels as f64 / MAX_SAMPLES as f64).sqrt().ceil() as u32).max(1);
+
+    let mut samples = Vec::with_capacity((pixels / (step as u64 * step as u64)) as usize + 1);
+    for y in (0..height).step_by(step as usize) {
+        for x in (0..width).step_by(step as usize) {
+            samples.push(source.rgb(x, y));
+        }
+    }
+    samples
+}
+
+/// Picks starting centers the k-means++ way, with a given seed so palettes are repeatable:
+/// each next center is drawn with a chance growing with its distance to the ones so far.
+fn initial_centers(samples: &[[u8; 3]], count: usize, seed: u64) -> Vec<[u8; 3]> {
+    let mut random = XorShift::new(seed);
+    let mut centers = vec![samples[random.below(samples.len() as u64) as usize]];
+    let mut distances: Vec<u64> = samples
+        .iter()
+        .map(|&rgb| distance(rgb, centers[0]))
+        .collect();
+
+    while centers.len() < count {
+        let total: u64 = distances.iter().sum();
+        // Every sample is already a center
+        if total == 0 {
+            break;
+        }
+        let mut target = random.below(total);
+        let index = distances
+            .iter()
+            .position(|&weight| {
+                if target < weight {
+                    return true;
+                }
+                target -= weight;
+                false
+            })
+            .unwrap_or(samples.len() - 1);
+        let center = samples[index];
+        centers.push(center);
+        for (closest, &rgb) in distances.iter_mut().zip(samples) {
+            *closest = (*closest).min(distance(rgb, center));
+        }
+    }
+    centers
+}
+
+fn nearest_index(colors: &[[u8; 3]], rgb: [u8; 3]) -> usize {
+    colors
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, &color)| distance(color, rgb))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Squared distance between two colors.
+fn distance(a: [u8; 3], b: [u8; 3]) -> u64 {
+    a.iter()
+        .zip(&b)
+        .map(|(&a, &b)| (a as i64 - b as i64).pow(2) as u64)
+        .sum()
+}
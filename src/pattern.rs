@@ -0,0 +1,105 @@
+use image::{DynamicImage, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// The 75% color bars of a broadcast test card, from white to blue.
+const BARS: [[u8; 3]; 7] = [
+    [191, 191, 191],
+    [191, 191, 0],
+    [0, 191, 191],
+    [0, 191, 0],
+    [191, 0, 191],
+    [191, 0, 0],
+    [0, 0, 191],
+];
+/// Gray steps of the gradient from black to white, with half as many under the bars.
+const STEPS: u32 = 16;
+/// Squares across the checkerboard.
+const SQUARES: u32 = 8;
+/// Pixels drawn per cell of the art, about as tall as wide once shown in a terminal's cells.
+const CELL: (u32, u32) = (4, 8);
+
+/// A synthetic image for calibrating the charset and gamma against a terminal's font, and
+/// for looking at changes to the mapping without an image at hand.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum TestPattern {
+    /// A smooth gray ramp over a stepped one and a sweep through the hues
+    Gradient,
+    /// Color bars over gray steps
+    Bars,
+    /// A checkerboard of squares, square when the font's cells are twice as tall as wide
+    Checker,
+}
+
+impl TestPattern {
+    /// Draws the pattern for art of `width` x `height` characters, at a few pixels per
+    /// cell so it is sampled like any image.
+    ///
+    /// ```
+    /// use ascii_gen::converter::{ImageConverter, PixelSource};
+    /// use ascii_gen::pattern::TestPattern;
+    ///
+    /// let source = ImageConverter::from_image(TestPattern::Gradient.image(80, 24));
+    /// let (width, _) = source.dimensions();
+    /// assert_eq!(source.rgb(0, 0), [0, 0, 0]);
+    /// assert_eq!(source.rgb(width - 1, 0), [255, 255, 255]);
+    /// ```
+    pub fn image(self, width: u32, height: u32) -> DynamicImage {
+        let (width, height) = (width.max(1) * CELL.0, height.max(1) * CELL.1);
+        let image = match self {
+            TestPattern::Gradient => {
+                RgbImage::from_fn(width, height, |x, y| match y * 3 / height {
+                    0 => gray(ramp(x, width)),
+                    1 => gray(step(x, width, STEPS)),
+                    _ => hue(x as f32 / width as f32),
+                })
+            }
+            TestPattern::Bars => RgbImage::from_fn(width, height, |x, y| {
+                if y * 3 < height * 2 {
+                    image::Rgb(BARS[(x * BARS.len() as u32 / width) as usize])
+                } else {
+                    gray(step(x, width, STEPS / 2))
+                }
+            }),
+            TestPattern::Checker => {
+                let side = (width / SQUARES).max(1);
+                // As many pixels tall as wide, so half as many rows of cells as columns
+                RgbImage::from_fn(width, height, |x, y| {
+                    let white = (x / side + y / side).is_multiple_of(2);
+                    gray(if white { 255 } else { 0 })
+                })
+            }
+        };
+        DynamicImage::ImageRgb8(image)
+    }
+}
+
+/// Luminance rising smoothly from black at the left edge to white at the right.
+fn ramp(x: u32, width: u32) -> u8 {
+    (x * 255 / (width - 1).max(1)) as u8
+}
+
+/// Luminance rising from black to white in `steps` equally wide bands.
+fn step(x: u32, width: u32, steps: u32) -> u8 {
+    (x * steps / width * 255 / (steps - 1)) as u8
+}
+
+fn gray(level: u8) -> image::Rgb<u8> {
+    image::Rgb([level; 3])
+}
+
+/// A fully saturated color `position` of the way around the color wheel from red.
+fn hue(position: f32) -> image::Rgb<u8> {
+    let sector = position * 6.0;
+    let rising = ((sector % 1.0) * 255.0) as u8;
+    let falling = 255 - rising;
+    image::Rgb(match sector as u32 {
+        0 => [255, rising, 0],
+        1 => [falling, 255, 0],
+        2 => [0, 255, rising],
+        3 => [0, falling, 255],
+        4 => [rising, 0, 255],
+        _ => [255, 0, falling],
+    })
+}
@@ -0,0 +1,267 @@
+use crate::art::ArtBuffer;
+use crate::color::ColorMode;
+use crate::converter::{AsciiOptions, PixelSource};
+use crate::error::AsciiGenError;
+use crate::progress::ConversionHandle;
+use crate::temporal::TemporalFilter;
+use crate::tone::ToneTable;
+use serde::{Deserialize, Serialize};
+
+/// Everything about a conversion that only depends on the source size and the options,
+/// worked out once and reused for every frame of that size.
+///
+/// `ArtBuffer` keeps the plan of its last conversion and only makes a new one when the size,
+/// options or color mode change, so videos get this without asking.
+///
+/// ```
+/// use ascii_gen::{art::ArtBuffer, color::ColorMode, converter::{AsciiOptions, ImageConverter}};
+/// use ascii_gen::{plan::ConversionPlan, temporal::TemporalFilter};
+///
+/// let frame = ImageConverter::from_image(image::DynamicImage::new_rgb8(64, 32));
+/// let plan = ConversionPlan::new((64, 32), &AsciiOptions::new(16, 8, 1.0), ColorMode::None);
+/// let mut art = ArtBuffer::default();
+/// plan.convert(&frame, &mut art, &mut TemporalFilter::new(0));
+/// assert_eq!(art.art.lines().count(), 8);
+/// ```
+pub struct ConversionPlan {
+    source: (u32, u32),
+    options: AsciiOptions,
+    color_mode: ColorMode,
+    sampler: Sampler,
+    table: ToneTable,
+}
+
+impl ConversionPlan {
+    pub fn new(source: (u32, u32), options: &AsciiOptions, color_mode: ColorMode) -> Self {
+        // Half blocks show two pixels per cell
+        let rows = match color_mode {
+            ColorMode::Halfblock => options.height() * 2,
+            _ => options.height(),
+        };
+        Self {
+            source,
+            options: options.clone(),
+            color_mode,
+            sampler: Sampler::new(source, options.width(), rows, options.upscale()),
+            table: ToneTable::new(options),
+        }
+    }
+
+    /// Whether the plan fits a conversion of a source of this size with these settings.
+    pub fn is_for(
+        &self,
+        source: (u32, u32),
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+    ) -> bool {
+        self.source == source && self.color_mode == color_mode && &self.options == options
+    }
+
+    pub fn options(&self) -> &AsciiOptions {
+        &self.options
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    pub fn table(&self) -> &ToneTable {
+        &self.table
+    }
+
+    /// Converts a frame of the planned size into `art`.
+    pub fn convert(
+        &self,
+        frame: &impl PixelSource,
+        art: &mut ArtBuffer,
+        temporal_filter: &mut TemporalFilter,
+    ) {
+        // A fresh handle is never cancelled
+        let _ = art.convert_planned(frame, self, temporal_filter, &ConversionHandle::default());
+    }
+
+    pub(crate) fn sample(
+        &self,
+        frame: &(impl PixelSource + ?Sized),
+        grid: &mut Vec<[u8; 3]>,
+        handle: &ConversionHandle,
+    ) -> Result<(), AsciiGenError> {
+        self.sampler.sample(frame, grid, handle)
+    }
+}
+
+/// How cells are filled when the art has more of them than the source has pixels.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Upscale {
+    /// Repeat the nearest pixel, keeping pixel art crisp
+    #[default]
+    Nearest,
+    /// Blend the four nearest pixels, smoothing photos and emotes
+    Bilinear,
+}
+
+/// Where each cell's box of source pixels starts, and how large the boxes are.
+pub(crate) struct Sampler {
+    columns: Vec<u32>,
+    rows: Vec<u32>,
+    box_width: u32,
+    box_height: u32,
+    count: u32,
+    /// When upscaling bilinearly, the weight out of 256 of the pixel after each column and
+    /// row start, and the last column and row of the source.
+    blend: Option<Blend>,
+}
+
+struct Blend {
+    columns: Vec<u32>,
+    rows: Vec<u32>,
+    last: (u32, u32),
+}
+
+impl Sampler {
+    pub fn new(
+        source: (u32, u32),
+        target_width: u32,
+        target_height: u32,
+        upscale: Upscale,
+    ) -> Self {
+        let width_ratio = source.0 as f32 / target_width.max(1) as f32;
+        let height_ratio = source.1 as f32 / target_height.max(1) as f32;
+        let upscaling = width_ratio < 1.0 || height_ratio < 1.0;
+        if upscaling {
+            tracing::debug!(?source, target_width, target_height, ?upscale, "upscaling");
+        }
+
+        if upscaling && upscale == Upscale::Bilinear {
+            let last = (source.0.saturating_sub(1), source.1.saturating_sub(1));
+            // Cell centers line up with pixel centers, as in image resizers
+            let taps = |count: u32, ratio: f32, last: u32| -> (Vec<u32>, Vec<u32>) {
+                (0..count)
+                    .map(|i| {
+                        let center = ((i as f32 + 0.5) * ratio - 0.5).clamp(0.0, last as f32);
+                        (center as u32, (center.fract() * 256.0) as u32)
+                    })
+                    .unzip()
+            };
+            let (columns, column_weights) = taps(target_width, width_ratio, last.0);
+            let (rows, row_weights) = taps(target_height, height_ratio, last.1);
+            return Self {
+                columns,
+                rows,
+                box_width: 1,
+                box_height: 1,
+                count: 1,
+                blend: Some(Blend {
+                    columns: column_weights,
+                    rows: row_weights,
+                    last,
+                }),
+            };
+        }
+
+        // Art larger than the source repeats its pixels, each cell taking at least one
+        let (box_width, box_height) = ((width_ratio as u32).max(1), (height_ratio as u32).max(1));
+        Self {
+            columns: (0..target_width)
+                .map(|x| (x as f32 * width_ratio) as u32)
+                .collect(),
+            rows: (0..target_height)
+                .map(|y| (y as f32 * height_ratio) as u32)
+                .collect(),
+            box_width,
+            box_height,
+            count: box_width * box_height,
+            blend: None,
+        }
+    }
+
+    /// Averages every box of `source` into `grid`, row by row.
+    pub fn sample(
+        &self,
+        source: &(impl PixelSource + ?Sized),
+        grid: &mut Vec<[u8; 3]>,
+        handle: &ConversionHandle,
+    ) -> Result<(), AsciiGenError> {
+        let tracker = handle.start(self.rows.len() as u32);
+
+        // Each row is averaged on its own, so rows can be sampled on several threads
+        let sample_row = |y: usize, row: &mut [[u8; 3]]| {
+            let start_y = self.rows[y];
+            if let Some(blend) = &self.blend {
+                let (next_y, weight_y) = ((start_y + 1).min(blend.last.1), blend.rows[y]);
+                for ((&start_x, &weight_x), cell) in
+                    self.columns.iter().zip(&blend.columns).zip(row)
+                {
+                    let next_x = (start_x + 1).min(blend.last.0);
+                    let mix = |a: [u8; 3], b: [u8; 3], weight: u32| {
+                        [0, 1, 2]
+                            .map(|c| u32::from(a[c]) * (256 - weight) + u32::from(b[c]) * weight)
+                    };
+                    let top = mix(
+                        source.rgb(start_x, start_y),
+                        source.rgb(next_x, start_y),
+                        weight_x,
+                    );
+                    let bottom = mix(
+                        source.rgb(start_x, next_y),
+                        source.rgb(next_x, next_y),
+                        weight_x,
+                    );
+                    *cell = [0, 1, 2].map(|c| {
+                        let value = top[c] * (256 - weight_y) + bottom[c] * weight_y;
+                        ((value + (1 << 15)) >> 16) as u8
+                    });
+                }
+                return;
+            }
+
+            for (&start_x, cell) in self.columns.iter().zip(row) {
+                let mut total_r = 0;
+                let mut total_g = 0;
+                let mut total_b = 0;
+
+                for dy in 0..self.box_height {
+                    for dx in 0..self.box_width {
+                        let [r, g, b] = source.rgb(start_x + dx, start_y + dy);
+                        total_r += r as u32;
+                        total_g += g as u32;
+                        total_b += b as u32;
+                    }
+                }
+
+                let avg_r = (total_r / self.count) as u8;
+                let avg_g = (total_g / self.count) as u8;
+                let avg_b = (total_b / self.count) as u8;
+
+                *cell = [avg_r, avg_g, avg_b];
+            }
+        };
+
+        grid.clear();
+        grid.resize(self.columns.len() * self.rows.len(), [0; 3]);
+        let row_len = self.columns.len().max(1);
+
+        #[cfg(feature = "parallel")]
+        if rayon::current_num_threads() > 1 {
+            use rayon::prelude::*;
+            use std::sync::atomic::{AtomicU32, Ordering};
+            let done = AtomicU32::new(0);
+            return grid
+                .par_chunks_mut(row_len)
+                .take(self.rows.len())
+                .enumerate()
+                .try_for_each(|(y, row)| {
+                    sample_row(y, row);
+                    tracker.row_done(done.fetch_add(1, Ordering::Relaxed) + 1)
+                });
+        }
+
+        for (y, row) in grid.chunks_mut(row_len).take(self.rows.len()).enumerate() {
+            sample_row(y, row);
+            tracker.row_done(y as u32 + 1)?;
+        }
+        Ok(())
+    }
+}
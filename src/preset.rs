@@ -0,0 +1,88 @@
+use ascii_gen::charset::Charset;
+use ascii_gen::color::ColorMode;
+use ascii_gen::curve::ToneCurve;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// A named set of conversion settings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Preset {
+    pub width: u32,
+    pub height: u32,
+    pub gamma: f32,
+    pub charset: Charset,
+    pub invert: bool,
+    pub color: ColorMode,
+    /// Missing from presets saved before the curve editor existed.
+    #[serde(default)]
+    pub curve: ToneCurve,
+}
+
+/// The `ascii-gen` config directory, honoring `XDG_CONFIG_HOME`.
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("ascii-gen"))
+}
+
+/// Presets stored in `~/.config/ascii-gen/presets.toml`, keyed by name.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct PresetStore {
+    #[serde(default)]
+    presets: BTreeMap<String, Preset>,
+}
+
+impl PresetStore {
+    /// Location of the presets file.
+    pub fn path() -> Option<PathBuf> {
+        Some(config_dir()?.join("presets.toml"))
+    }
+
+    /// Loads the presets, returning an empty store when the file does not exist yet.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(toml::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the presets back to disk, creating the config directory if needed.
+    pub fn save(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let path = Self::path().ok_or("could not determine the config directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.get(name)
+    }
+
+    pub fn insert(&mut self, name: String, preset: Preset) {
+        self.presets.insert(name, preset);
+    }
+
+    /// Returns the preset following `current` in name order, wrapping around.
+    pub fn next_after(&self, current: Option<&str>) -> Option<(&String, &Preset)> {
+        let next = match current {
+            Some(current) => self
+                .presets
+                .range::<str, _>((
+                    std::ops::Bound::Excluded(current),
+                    std::ops::Bound::Unbounded,
+                ))
+                .next(),
+            None => None,
+        };
+        next.or_else(|| self.presets.iter().next())
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{span, Metadata, Subscriber};
+use tracing_subscriber::{
+    filter::{self, Filtered},
+    layer::Context,
+    registry::LookupSpan,
+    Layer,
+};
+
+/// How often a stage ran and for how long in total.
+#[derive(Clone, Copy, Default)]
+struct Stage {
+    count: u64,
+    total: Duration,
+}
+
+/// Time spent in each traced stage (`decode`, `sample`, `map`, `render`, ...), collected
+/// from the spans around them for `--profile-stages`.
+#[derive(Clone, Default)]
+pub struct StageTimings {
+    stages: Arc<Mutex<BTreeMap<&'static str, Stage>>>,
+}
+
+/// When the span was last entered, kept in its extensions.
+struct Entered(Instant);
+
+/// Adds up the time between entering and leaving each span.
+pub struct StageLayer {
+    stages: Arc<Mutex<BTreeMap<&'static str, Stage>>>,
+}
+
+impl StageTimings {
+    /// A layer recording into these timings, seeing spans at every level but no events.
+    pub fn layer<S>(&self) -> Filtered<StageLayer, filter::FilterFn, S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let is_span: fn(&Metadata<'_>) -> bool = |metadata| metadata.is_span();
+        let layer = StageLayer {
+            stages: Arc::clone(&self.stages),
+        };
+        layer.with_filter(filter::FilterFn::new(is_span))
+    }
+
+    /// A table of the stages with their runs, total and mean times. Nested stages are also
+    /// counted in the stages around them.
+    pub fn report(&self) -> String {
+        let stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let mut report = format!(
+            "{:<10} {:>8} {:>12} {:>10}\n",
+            "stage", "runs", "total ms", "mean ms"
+        );
+        for (name, stage) in stages.iter() {
+            let total = stage.total.as_secs_f64() * 1000.0;
+            report.push_str(&format!(
+                "{:<10} {:>8} {:>12.2} {:>10.3}\n",
+                name,
+                stage.count,
+                total,
+                total / stage.count.max(1) as f64
+            ));
+        }
+        report
+    }
+}
+
+impl<S> Layer<S> for StageLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().replace(Entered(Instant::now()));
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let Some(Entered(start)) = span.extensions_mut().remove::<Entered>() else {
+            return;
+        };
+        let mut stages = self.stages.lock().unwrap_or_else(|e| e.into_inner());
+        let stage = stages.entry(span.name()).or_default();
+        stage.count += 1;
+        stage.total += start.elapsed();
+    }
+}
@@ -0,0 +1,97 @@
+use crate::error::AsciiGenError;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+/// How far a conversion has come, reported after each sampled row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// Share of the rows done, from 0.0 to 1.0.
+    pub fraction: f32,
+    /// Time left at the pace so far.
+    pub eta: Duration,
+}
+
+impl Progress {
+    pub fn percent(&self) -> f32 {
+        self.fraction * 100.0
+    }
+}
+
+/// Cancels a conversion running on another thread and reports its progress.
+///
+/// Clones share the same cancellation, so one can be handed to the conversion and another
+/// kept to call `cancel`. Cancelled conversions fail with `AsciiGenError::Cancelled`.
+///
+/// ```
+/// use ascii_gen::converter::{ImageConverter, ToAsciiArt};
+/// use ascii_gen::progress::ConversionHandle;
+///
+/// let source = ImageConverter::from_image(image::DynamicImage::new_rgb8(64, 64));
+/// let handle = ConversionHandle::new().on_progress(|p| eprint!("\r{:.0}%", p.percent()));
+/// handle.cancel();
+/// assert!(source.to_ascii_art_tracked(None, &handle).is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct ConversionHandle {
+    cancelled: Arc<AtomicBool>,
+    on_progress: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+}
+
+impl ConversionHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `callback` after every row of the conversion.
+    pub fn on_progress(mut self, callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stops the conversion at its next row.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Starts tracking a conversion of `rows` rows.
+    pub(crate) fn start(&self, rows: u32) -> Tracker<'_> {
+        Tracker {
+            handle: self,
+            rows,
+            // Only read the clock when someone listens, as wasm32 has none
+            start: self.on_progress.as_ref().map(|_| Instant::now()),
+        }
+    }
+}
+
+/// Checks a handle between the rows of one conversion.
+pub(crate) struct Tracker<'a> {
+    handle: &'a ConversionHandle,
+    rows: u32,
+    start: Option<Instant>,
+}
+
+impl Tracker<'_> {
+    /// Reports `done` finished rows, failing once the conversion has been cancelled.
+    pub fn row_done(&self, done: u32) -> Result<(), AsciiGenError> {
+        if self.handle.is_cancelled() {
+            return Err(AsciiGenError::Cancelled);
+        }
+        if let (Some(callback), Some(start)) = (&self.handle.on_progress, self.start) {
+            let fraction = done as f32 / self.rows.max(1) as f32;
+            let elapsed = start.elapsed();
+            callback(Progress {
+                fraction,
+                eta: elapsed.mul_f32((1.0 - fraction) / fraction.max(f32::EPSILON)),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use crate::converter::PixelSource;
+use crate::error::AsciiGenError;
+use qrcode::{types::Color, EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+
+/// Light modules the QR specification asks for around a code, so scanners find its edges.
+pub const QUIET_ZONE: u32 = 4;
+
+/// How much of a QR code can be covered or misread while it still scans. Higher levels
+/// need more modules, making the code larger.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum QrLevel {
+    /// About 7% of the code
+    Low,
+    /// About 15% of the code
+    Medium,
+    /// About 25% of the code
+    Quartile,
+    /// About 30% of the code
+    High,
+}
+
+/// The modules of a QR code with its quiet zone, one pixel each: black for dark modules and
+/// white for light ones.
+///
+/// The height is rounded up to an even number of rows so half blocks, two rows per cell,
+/// show every module exactly once.
+///
+/// ```
+/// use ascii_gen::qr::{QrImage, QrLevel};
+///
+/// let code = QrImage::encode("https://example.com", QrLevel::Medium)?;
+/// let blocks = code.to_blocks(false);
+/// assert_eq!(blocks.lines().count() as u32, code.rows() / 2);
+/// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+/// ```
+pub struct QrImage {
+    width: u32,
+    rows: u32,
+    dark: Vec<bool>,
+}
+
+impl QrImage {
+    /// Encodes `text`, failing when it is too long for a QR code at `level`.
+    pub fn encode(text: &str, level: QrLevel) -> Result<Self, AsciiGenError> {
+        let level = match level {
+            QrLevel::Low => EcLevel::L,
+            QrLevel::Medium => EcLevel::M,
+            QrLevel::Quartile => EcLevel::Q,
+            QrLevel::High => EcLevel::H,
+        };
+        let code = QrCode::with_error_correction_level(text, level)
+            .map_err(|e| AsciiGenError::Qr(e.to_string()))?;
+
+        let modules = code.width() as u32;
+        let width = modules + QUIET_ZONE * 2;
+        let rows = width.div_ceil(2) * 2;
+        let mut dark = vec![false; (width * rows) as usize];
+        for (index, color) in code.to_colors().into_iter().enumerate() {
+            let (x, y) = (index as u32 % modules, index as u32 / modules);
+            let pixel = (y + QUIET_ZONE) * width + x + QUIET_ZONE;
+            dark[pixel as usize] = color == Color::Dark;
+        }
+        Ok(Self { width, rows, dark })
+    }
+
+    /// Modules across, including the quiet zone.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Rows of modules, including the quiet zone and the row evening it out.
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Whether the module at `x`, `y` is dark, the quiet zone never being.
+    pub fn is_dark(&self, x: u32, y: u32) -> bool {
+        self.dark[(y * self.width + x) as usize]
+    }
+
+    /// Draws the code without colors, one column and half a row per module so they come out
+    /// about square. Light modules are the blocks, as terminals are usually dark, unless
+    /// `invert` asks for dark ones on light themes.
+    pub fn to_blocks(&self, invert: bool) -> String {
+        let mut art = String::with_capacity((self.width * self.rows) as usize * 2);
+        for y in (0..self.rows).step_by(2) {
+            for x in 0..self.width {
+                let filled = |y| self.is_dark(x, y) == invert;
+                art.push(match (filled(y), filled(y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            art.push('\n');
+        }
+        art
+    }
+}
+
+impl PixelSource for QrImage {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.rows)
+    }
+
+    fn rgb(&self, x: u32, y: u32) -> [u8; 3] {
+        if self.is_dark(x, y) {
+            [0; 3]
+        } else {
+            [255; 3]
+        }
+    }
+}
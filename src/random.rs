@@ -0,0 +1,51 @@
+/// Seed of the randomness of effects and palettes unless another is given with `--seed`.
+pub const DEFAULT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A tiny deterministic xorshift generator, so runs with the same seed come out the same.
+#[derive(Clone, Debug)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// A generator drawing from `seed`, or from `DEFAULT_SEED` for 0, which xorshift never
+    /// leaves.
+    pub fn new(seed: u64) -> Self {
+        Self(if seed == 0 { DEFAULT_SEED } else { seed })
+    }
+
+    /// A number below `bound`, or 0 when `bound` is 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound.max(1)
+    }
+}
+
+impl Default for XorShift {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(mut random: XorShift) -> Vec<u64> {
+        (0..16).map(|_| random.below(1000)).collect()
+    }
+
+    #[test]
+    fn zero_seed_draws_like_the_default() {
+        assert_eq!(draws(XorShift::new(0)), draws(XorShift::default()));
+        assert_ne!(draws(XorShift::new(1)), draws(XorShift::default()));
+        assert!(draws(XorShift::new(0)).iter().any(|&draw| draw != 0));
+    }
+
+    #[test]
+    fn draws_stay_below_the_bound() {
+        let mut random = XorShift::new(7);
+        assert!((0..1000).all(|_| random.below(10) < 10));
+        assert_eq!(random.below(0), 0);
+    }
+}
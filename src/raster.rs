@@ -0,0 +1,232 @@
+use crate::color::{CellColor, ColorMode};
+use crate::error::AsciiGenError;
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use font8x8::{UnicodeFonts, BASIC_FONTS, BLOCK_FONTS, BOX_FONTS, LATIN_FONTS};
+use image::{Rgb, RgbImage};
+
+/// Pixels across a rasterized cell.
+pub const CELL_WIDTH: u32 = 8;
+/// Pixels down a rasterized cell, twice its width like most terminal fonts.
+pub const CELL_HEIGHT: u32 = 16;
+
+/// Draws the art the way a terminal would, with an 8x8 bitmap font stretched to the height
+/// of a cell. Without colors the glyphs are white on black.
+///
+/// ```
+/// use ascii_gen::{color::ColorMode, raster};
+///
+/// let image = raster::rasterize("#.\n.#\n", &[], ColorMode::None);
+/// assert_eq!(image.dimensions(), (2 * raster::CELL_WIDTH, 2 * raster::CELL_HEIGHT));
+/// ```
+pub fn rasterize(art: &str, colors: &[CellColor], color_mode: ColorMode) -> RgbImage {
+    let colors = if color_mode.is_enabled() { colors } else { &[] };
+    Rasterizer::default().draw(art, colors)
+}
+
+/// Draws text as an image, a cell per character, with the built-in bitmap font or a
+/// TrueType or OpenType font.
+///
+/// ```
+/// use ascii_gen::raster::Rasterizer;
+///
+/// let rasterizer = Rasterizer::default().colors([224; 3], [16; 3]);
+/// let image = rasterizer.draw(" \n", &[]);
+/// assert_eq!(image.get_pixel(0, 0).0, [16; 3]);
+/// ```
+pub struct Rasterizer {
+    font: Option<TrueType>,
+    cell_width: u32,
+    cell_height: u32,
+    foreground: [u8; 3],
+    background: [u8; 3],
+}
+
+/// A vector font scaled to the cell height.
+struct TrueType {
+    font: FontVec,
+    scale: PxScale,
+    ascent: f32,
+}
+
+impl Default for Rasterizer {
+    fn default() -> Self {
+        Self {
+            font: None,
+            cell_width: CELL_WIDTH,
+            cell_height: CELL_HEIGHT,
+            foreground: [255; 3],
+            background: [0; 3],
+        }
+    }
+}
+
+impl Rasterizer {
+    /// Draws with the font in `data` at `size` pixels per line, each cell as wide as its
+    /// `M`. Characters the font lacks fall back to the bitmap font.
+    pub fn with_font(data: Vec<u8>, size: f32) -> Result<Self, AsciiGenError> {
+        let font = FontVec::try_from_vec(data).map_err(|e| AsciiGenError::Font(e.to_string()))?;
+        let scale = PxScale::from(size.max(1.0));
+        let scaled = font.as_scaled(scale);
+        let cell_width = scaled.h_advance(font.glyph_id('M')).ceil().max(1.0) as u32;
+        let cell_height = (scaled.ascent() - scaled.descent()).ceil().max(1.0) as u32;
+        let ascent = scaled.ascent();
+
+        Ok(Self {
+            font: Some(TrueType {
+                font,
+                scale,
+                ascent,
+            }),
+            cell_width,
+            cell_height,
+            ..Self::default()
+        })
+    }
+
+    /// Colors for cells without colors of their own.
+    pub fn colors(mut self, foreground: [u8; 3], background: [u8; 3]) -> Self {
+        self.foreground = foreground;
+        self.background = background;
+        self
+    }
+
+    /// Pixels across and down each cell.
+    pub fn cell_size(&self) -> (u32, u32) {
+        (self.cell_width, self.cell_height)
+    }
+
+    /// Draws the art, coloring the cells with `colors` in order, or in the default colors
+    /// where it runs out.
+    pub fn draw(&self, art: &str, colors: &[CellColor]) -> RgbImage {
+        let lines: Vec<&str> = art.lines().collect();
+        let columns = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as u32;
+        let mut image = RgbImage::from_pixel(
+            columns * self.cell_width,
+            lines.len() as u32 * self.cell_height,
+            Rgb(self.background),
+        );
+
+        // Backgrounds first, so glyphs reaching past their cell are not painted over
+        let mut cells = colors.iter();
+        let mut glyphs = Vec::with_capacity(art.len());
+        for (row, line) in lines.iter().enumerate() {
+            for (column, glyph) in line.chars().enumerate() {
+                let cell = cells.next();
+                let fg = cell.map_or(self.foreground, |cell| cell.fg);
+                let bg = cell.and_then(|cell| cell.bg).unwrap_or(self.background);
+                let (left, top) = (
+                    column as u32 * self.cell_width,
+                    row as u32 * self.cell_height,
+                );
+                for y in top..top + self.cell_height {
+                    for x in left..left + self.cell_width {
+                        image.put_pixel(x, y, Rgb(bg));
+                    }
+                }
+                glyphs.push((glyph, fg, left, top));
+            }
+        }
+
+        for (glyph, fg, left, top) in glyphs {
+            let drawn = self
+                .font
+                .as_ref()
+                .is_some_and(|font| font.draw(&mut image, glyph, fg, left, top));
+            if !drawn {
+                self.draw_bitmap(&mut image, glyph, fg, left, top);
+            }
+        }
+        image
+    }
+
+    /// Draws the bitmap glyph stretched over the cell.
+    fn draw_bitmap(&self, image: &mut RgbImage, glyph: char, fg: [u8; 3], left: u32, top: u32) {
+        let bitmap = bitmap(glyph);
+        for y in 0..self.cell_height {
+            let bits = bitmap[(y * 8 / self.cell_height) as usize];
+            for x in 0..self.cell_width {
+                if bits >> (x * 8 / self.cell_width) & 1 == 1 {
+                    image.put_pixel(left + x, top + y, Rgb(fg));
+                }
+            }
+        }
+    }
+}
+
+impl TrueType {
+    /// Draws the glyph antialiased over the cell's background, or returns false if the
+    /// font lacks it.
+    fn draw(&self, image: &mut RgbImage, glyph: char, fg: [u8; 3], left: u32, top: u32) -> bool {
+        let id = self.font.glyph_id(glyph);
+        if id.0 == 0 {
+            return false;
+        }
+        let positioned =
+            id.with_scale_and_position(self.scale, point(left as f32, top as f32 + self.ascent));
+        // Spaces have nothing to outline
+        let Some(outlined) = self.font.outline_glyph(positioned) else {
+            return true;
+        };
+
+        let bounds = outlined.px_bounds();
+        outlined.draw(|x, y, coverage| {
+            let x = bounds.min.x as i64 + x as i64;
+            let y = bounds.min.y as i64 + y as i64;
+            if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(x as u32, y as u32);
+            for (channel, &fg) in pixel.0.iter_mut().zip(&fg) {
+                let blended = *channel as f32 + (fg as f32 - *channel as f32) * coverage;
+                *channel = blended.round().clamp(0.0, 255.0) as u8;
+            }
+        });
+        true
+    }
+}
+
+/// Spells the text out in big letters of the bitmap font, like a figlet banner, each line
+/// of half blocks drawing two rows of pixels.
+///
+/// ```
+/// let banner = ascii_gen::raster::banner("hi");
+/// assert_eq!(banner.lines().count(), 4);
+/// assert!(banner.lines().all(|line| line.chars().count() <= 16));
+/// ```
+pub fn banner(text: &str) -> String {
+    let glyphs: Vec<[u8; 8]> = text.chars().map(bitmap).collect();
+    let mut banner = String::new();
+    for row in (0..8).step_by(2) {
+        let mut line = String::with_capacity(glyphs.len() * 8 * 3);
+        for bitmap in &glyphs {
+            for x in 0..8 {
+                let top = bitmap[row] >> x & 1 == 1;
+                let bottom = bitmap[row + 1] >> x & 1 == 1;
+                line.push(match (top, bottom) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+        }
+        banner.push_str(line.trim_end());
+        banner.push('\n');
+    }
+    banner
+}
+
+/// The glyph's rows, the leftmost pixel in the lowest bit. Glyphs the font lacks, such as
+/// katakana from a custom ramp, are left blank.
+fn bitmap(glyph: char) -> [u8; 8] {
+    BASIC_FONTS
+        .get(glyph)
+        .or_else(|| LATIN_FONTS.get(glyph))
+        .or_else(|| BLOCK_FONTS.get(glyph))
+        .or_else(|| BOX_FONTS.get(glyph))
+        .unwrap_or([0; 8])
+}
@@ -0,0 +1,97 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Stdout, Write},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Writer used by the terminal backend, optionally teeing output into an asciinema v2 file.
+pub struct SessionWriter {
+    inner: Stdout,
+    recording: Option<Recording>,
+}
+
+/// An asciinema v2 recording in progress.
+struct Recording {
+    file: BufWriter<File>,
+    start: Instant,
+    pending: Vec<u8>,
+}
+
+impl SessionWriter {
+    /// Creates a writer that records everything drawn into `path` when given.
+    pub fn new(inner: Stdout, path: Option<&str>) -> io::Result<Self> {
+        let recording = match path {
+            Some(path) => {
+                let (width, height) = crossterm::terminal::size()?;
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+
+                let mut file = BufWriter::new(File::create(path)?);
+                writeln!(
+                    file,
+                    "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}}}",
+                    width, height, timestamp
+                )?;
+
+                Some(Recording {
+                    file,
+                    start: Instant::now(),
+                    pending: Vec::new(),
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self { inner, recording })
+    }
+}
+
+impl Write for SessionWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Some(recording) = &mut self.recording {
+            recording.pending.extend_from_slice(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+
+        // Each flush is one complete draw, so it becomes one output event
+        if let Some(recording) = &mut self.recording {
+            if !recording.pending.is_empty() {
+                let elapsed = recording.start.elapsed().as_secs_f64();
+                let data = String::from_utf8_lossy(&recording.pending);
+                writeln!(
+                    recording.file,
+                    "[{:.6}, \"o\", \"{}\"]",
+                    elapsed,
+                    escape_json(&data)
+                )?;
+                recording.pending.clear();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Escapes a string for use inside a JSON string literal.
+fn escape_json(data: &str) -> String {
+    let mut escaped = String::with_capacity(data.len());
+    for c in data.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,80 @@
+#[cfg(feature = "video")]
+use ffmpeg_next as ffmpeg;
+
+/// Clockwise rotation needed to display a video upright.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+impl Rotation {
+    /// Reads the rotation from the stream's display matrix side data.
+    #[cfg(feature = "video")]
+    pub fn from_stream(stream: &ffmpeg::Stream) -> Self {
+        stream
+            .side_data()
+            .find(|side_data| side_data.kind() == ffmpeg::packet::side_data::Type::DisplayMatrix)
+            .and_then(|side_data| display_matrix_rotation(side_data.data()))
+            .map(Self::from_degrees)
+            .unwrap_or(Rotation::None)
+    }
+
+    /// Snaps a clockwise angle in degrees to the nearest quarter turn.
+    #[cfg(feature = "video")]
+    fn from_degrees(degrees: f64) -> Self {
+        match (degrees / 90.0).round() as i64 % 4 {
+            1 | -3 => Rotation::Clockwise90,
+            2 | -2 => Rotation::Clockwise180,
+            3 | -1 => Rotation::Clockwise270,
+            _ => Rotation::None,
+        }
+    }
+
+    /// Returns the dimensions of a `width` x `height` frame once rotated.
+    pub fn dimensions(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Rotation::None | Rotation::Clockwise180 => (width, height),
+            Rotation::Clockwise90 | Rotation::Clockwise270 => (height, width),
+        }
+    }
+
+    /// Maps coordinates in the rotated frame back to the `width` x `height` source frame.
+    pub fn source_coords(self, x: u32, y: u32, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Rotation::None => (x, y),
+            Rotation::Clockwise90 => (y, height - 1 - x),
+            Rotation::Clockwise180 => (width - 1 - x, height - 1 - y),
+            Rotation::Clockwise270 => (width - 1 - y, x),
+        }
+    }
+}
+
+/// Computes the clockwise rotation needed for a 3x3 display matrix, as ffmpeg's autorotate does.
+#[cfg(feature = "video")]
+fn display_matrix_rotation(data: &[u8]) -> Option<f64> {
+    if data.len() < 9 * 4 {
+        return None;
+    }
+
+    let value = |index: usize| {
+        let bytes = [
+            data[index * 4],
+            data[index * 4 + 1],
+            data[index * 4 + 2],
+            data[index * 4 + 3],
+        ];
+        i32::from_ne_bytes(bytes) as f64
+    };
+
+    let scale_x = value(0).hypot(value(3));
+    let scale_y = value(1).hypot(value(4));
+    if scale_x == 0.0 || scale_y == 0.0 {
+        return None;
+    }
+
+    // The negated `av_display_rotation_get` angle, i.e. the clockwise correction
+    Some((value(1) / scale_y).atan2(value(0) / scale_x).to_degrees())
+}
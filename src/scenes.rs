@@ -0,0 +1,97 @@
+use crate::art::ArtBuffer;
+use crate::color::{CellColor, ColorMode};
+use crate::converter::{self, AsciiOptions};
+use crate::error::AsciiGenError;
+use crate::stream::VideoAsciiStream;
+use crate::temporal::TemporalFilter;
+
+/// Average luminance change of the cells, out of 255, that counts as a cut by default.
+pub const DEFAULT_THRESHOLD: u8 = 30;
+
+/// Finds cuts by how much the luminance of the sampled cells moved since the last frame,
+/// which fades and camera pans only do gradually.
+///
+/// ```
+/// use ascii_gen::scenes::SceneDetector;
+///
+/// let mut detector = SceneDetector::new(30);
+/// assert!(detector.is_cut(&[[0; 3]; 4]));
+/// assert!(!detector.is_cut(&[[10; 3]; 4]));
+/// assert!(detector.is_cut(&[[200; 3]; 4]));
+/// ```
+pub struct SceneDetector {
+    threshold: u8,
+    previous: Vec<u8>,
+}
+
+impl SceneDetector {
+    pub fn new(threshold: u8) -> Self {
+        Self {
+            threshold,
+            previous: Vec::new(),
+        }
+    }
+
+    /// Whether the frame whose cells sampled `pixels` starts a new scene. The first frame,
+    /// or one of another size, always does.
+    pub fn is_cut(&mut self, pixels: &[[u8; 3]]) -> bool {
+        let cut = pixels.len() != self.previous.len() || {
+            let total: u64 = pixels
+                .iter()
+                .zip(&self.previous)
+                .map(|(&rgb, &previous)| converter::base_luminance(rgb).abs_diff(previous) as u64)
+                .sum();
+            total > self.threshold as u64 * pixels.len() as u64
+        };
+
+        self.previous.clear();
+        self.previous
+            .extend(pixels.iter().map(|&rgb| converter::base_luminance(rgb)));
+        cut
+    }
+}
+
+/// The first frame of a scene.
+pub struct Scene {
+    /// When the scene starts, from the start of the video, if the container gives one.
+    pub timestamp: Option<f64>,
+    pub art: String,
+    /// Color of each cell, empty without a color mode.
+    pub colors: Vec<CellColor>,
+}
+
+/// Reads the rest of the video and converts the first frame of every scene, a cut being an
+/// average luminance change above `threshold`.
+pub fn detect(
+    stream: &mut VideoAsciiStream,
+    options: &AsciiOptions,
+    color_mode: ColorMode,
+    threshold: u8,
+) -> Result<Vec<Scene>, AsciiGenError> {
+    let mut detector = SceneDetector::new(threshold);
+    let mut sampled = ArtBuffer::default();
+    let mut scenes = Vec::new();
+
+    while stream.advance()? {
+        stream.convert(
+            &mut sampled,
+            options,
+            ColorMode::None,
+            &mut TemporalFilter::new(0),
+        )?;
+        if !detector.is_cut(sampled.sampled()) {
+            continue;
+        }
+
+        // Converted afresh, so no colors carry over from the scene before
+        let mut frame = ArtBuffer::default();
+        stream.convert(&mut frame, options, color_mode, &mut TemporalFilter::new(0))?;
+        tracing::debug!(timestamp = stream.timestamp(), "scene cut");
+        scenes.push(Scene {
+            timestamp: stream.timestamp(),
+            art: frame.art,
+            colors: frame.colors,
+        });
+    }
+    Ok(scenes)
+}
@@ -0,0 +1,43 @@
+use ffmpeg_next as ffmpeg;
+use image::RgbImage;
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Writes the currently displayed ASCII art to a timestamped text file.
+pub fn save_ascii(art: &str) -> std::io::Result<PathBuf> {
+    let path = timestamped_path("txt");
+    std::fs::write(&path, art)?;
+    Ok(path)
+}
+
+/// Writes a decoded RGB24 video frame to a timestamped PNG file.
+pub fn save_frame(frame: &ffmpeg::frame::Video) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let width = frame.width() as usize;
+    let height = frame.height() as usize;
+    let stride = frame.stride(0);
+    let data = frame.data(0);
+
+    // Copy row by row, since the plane may be padded beyond width * 3
+    let mut pixels = Vec::with_capacity(width * height * 3);
+    for row in data.chunks(stride).take(height) {
+        pixels.extend_from_slice(&row[..width * 3]);
+    }
+
+    let image = RgbImage::from_raw(width as u32, height as u32, pixels)
+        .ok_or("frame buffer does not match its dimensions")?;
+    let path = timestamped_path("png");
+    image.save(&path)?;
+    Ok(path)
+}
+
+/// Builds a file name in the current directory based on the current time.
+fn timestamped_path(extension: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    PathBuf::from(format!("ascii-gen-{}.{}", millis, extension))
+}
@@ -0,0 +1,211 @@
+use crate::charset::{self, CharMapper};
+use crate::error::AsciiGenError;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+/// Results kept before the cache starts over, enough for every luminance of a few hundred colors.
+const CACHE_LIMIT: usize = 1 << 16;
+
+/// A glyph and an optional color replacing the cell's own.
+type Cell = (char, Option<[u8; 3]>);
+
+/// Maps cells with a rhai script defining `fn map(luminance, r, g, b)`.
+///
+/// The function returns the character to draw, as a string or char, or a map like
+/// `#{ glyph: "*", color: [255, 0, 0] }` to recolor the cell as well. Scripts have no file
+/// or network access and are stopped after a bounded number of operations.
+///
+/// ```
+/// use ascii_gen::{charset::CharMapper, script::ScriptMapper};
+///
+/// let mapper = ScriptMapper::compile(r#"fn map(l, r, g, b) { if r > b { "~" } else { "." } }"#)?;
+/// assert_eq!(mapper.glyph(128, [200, 0, 0]), '~');
+/// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+/// ```
+pub struct ScriptMapper {
+    engine: Engine,
+    ast: AST,
+    /// Evaluated cells by luminance and color, as video frames repeat most of them.
+    cache: Mutex<HashMap<(u8, [u8; 3]), Cell>>,
+    warned: AtomicBool,
+}
+
+impl ScriptMapper {
+    /// Reads and compiles a script file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AsciiGenError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| AsciiGenError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::compile(&source).map_err(|e| {
+            AsciiGenError::Script(format!("{}: {}", path.display(), e.to_string().trim()))
+        })
+    }
+
+    /// Compiles a script, checking that it defines `map` with four parameters.
+    pub fn compile(source: &str) -> Result<Self, AsciiGenError> {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(10_000)
+            .set_max_call_levels(16)
+            .set_max_expr_depths(32, 32)
+            .set_max_string_size(256)
+            .set_max_array_size(256)
+            .set_max_map_size(64)
+            .on_print(|_| {})
+            .on_debug(|_, _, _| {});
+        engine.disable_symbol("eval");
+
+        let ast = engine
+            .compile(source)
+            .map_err(|e| AsciiGenError::Script(e.to_string()))?;
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == "map" && f.params.len() == 4)
+        {
+            return Err(AsciiGenError::Script(
+                "the script must define fn map(luminance, r, g, b)".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            engine,
+            ast,
+            cache: Mutex::new(HashMap::new()),
+            warned: AtomicBool::new(false),
+        })
+    }
+
+    /// The glyph and color for a cell, evaluating the script only for new inputs.
+    pub fn cell(&self, luminance: u8, rgb: [u8; 3]) -> Cell {
+        let key = (luminance, rgb);
+        if let Some(&cell) = self.cache.lock().unwrap().get(&key) {
+            return cell;
+        }
+
+        let cell = self.evaluate(luminance, rgb).unwrap_or_else(|e| {
+            // Report the first failure only, as it would repeat for every cell
+            if !self.warned.swap(true, Ordering::Relaxed) {
+                tracing::warn!("mapping script failed: {}", e);
+            }
+            (' ', None)
+        });
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(key, cell);
+        cell
+    }
+
+    fn evaluate(&self, luminance: u8, rgb: [u8; 3]) -> Result<Cell, String> {
+        let [r, g, b] = rgb.map(i64::from);
+        let result: Dynamic = self
+            .engine
+            .call_fn(
+                &mut Scope::new(),
+                &self.ast,
+                "map",
+                (luminance as i64, r, g, b),
+            )
+            .map_err(|e| e.to_string())?;
+
+        if !result.is::<Map>() {
+            return Ok((to_glyph(&result)?, None));
+        }
+        let map = result.cast::<Map>();
+        let glyph = map.get("glyph").ok_or("the returned map has no glyph")?;
+        let color = map.get("color").map(to_color).transpose()?;
+        Ok((to_glyph(glyph)?, color))
+    }
+}
+
+fn to_glyph(value: &Dynamic) -> Result<char, String> {
+    let glyph = match value.as_char() {
+        Ok(c) => c,
+        Err(_) => value
+            .clone()
+            .into_string()
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or_else(|| format!("expected a character, got {}", value.type_name()))?,
+    };
+    // A wider or narrower glyph would shift the rest of its row
+    match charset::glyph_width(glyph) {
+        1 => Ok(glyph),
+        width => Err(format!(
+            "{:?} takes {} columns instead of one",
+            glyph, width
+        )),
+    }
+}
+
+fn to_color(value: &Dynamic) -> Result<[u8; 3], String> {
+    let channels = value
+        .clone()
+        .try_cast::<Array>()
+        .filter(|channels| channels.len() == 3)
+        .ok_or("a color must be an array of three channels")?;
+
+    let mut color = [0; 3];
+    for (channel, value) in color.iter_mut().zip(&channels) {
+        *channel = value
+            .as_int()
+            .map_err(|_| "color channels must be integers")?
+            .clamp(0, 255) as u8;
+    }
+    Ok(color)
+}
+
+impl CharMapper for ScriptMapper {
+    fn glyph(&self, luminance: u8, rgb: [u8; 3]) -> char {
+        self.cell(luminance, rgb).0
+    }
+
+    fn color(&self, luminance: u8, rgb: [u8; 3]) -> Option<[u8; 3]> {
+        self.cell(luminance, rgb).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_result_maps_to_glyph() {
+        let mapper =
+            ScriptMapper::compile(r#"fn map(l, r, g, b) { if l > 127 { "@" } else { "." } }"#)
+                .unwrap();
+        assert_eq!(mapper.cell(200, [0, 0, 0]), ('@', None));
+        assert_eq!(mapper.cell(10, [0, 0, 0]), ('.', None));
+    }
+
+    #[test]
+    fn map_result_sets_glyph_and_color() {
+        let mapper =
+            ScriptMapper::compile(r#"fn map(l, r, g, b) { #{ glyph: "*", color: [r, 300, -5] } }"#)
+                .unwrap();
+        assert_eq!(mapper.cell(0, [12, 0, 0]), ('*', Some([12, 255, 0])));
+    }
+
+    #[test]
+    fn map_without_glyph_falls_back_to_blank() {
+        let mapper =
+            ScriptMapper::compile(r#"fn map(l, r, g, b) { #{ color: [1, 2, 3] } }"#).unwrap();
+        assert_eq!(mapper.cell(0, [0, 0, 0]), (' ', None));
+    }
+
+    #[test]
+    fn missing_map_function_is_rejected() {
+        assert!(ScriptMapper::compile("fn other() { 1 }").is_err());
+    }
+}
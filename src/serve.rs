@@ -0,0 +1,626 @@
+use crate::cli::{Args, PlaybackArgs};
+use crate::{App, VideoInput};
+use ascii_gen::async_stream::{AsyncVideoStream, DEFAULT_CAPACITY};
+use ascii_gen::charset::Charset;
+use ascii_gen::color::{self, CellColor, ColorMode};
+use ascii_gen::converter::ImageConverter;
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::output::OutputFormat;
+use ascii_gen::progress::ConversionHandle;
+use ascii_gen::stream::AsciiFrame;
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Router,
+};
+use futures_core::Stream;
+use futures_util::stream;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::io::{self, Cursor, Read};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Wait before fetching the rest of a dropped download, doubled with every try.
+const REFETCH_DELAY: Duration = Duration::from_millis(500);
+/// Most images `/convert` fetches and converts at once; each may hold `--max-memory` of
+/// download and decoded pixels, so further requests wait their turn.
+const MAX_CONVERSIONS: usize = 4;
+/// Most frame stream viewers at once, over every source.
+const MAX_VIEWERS: usize = 64;
+/// Frames kept for a viewer that falls behind, before it skips ahead to the newest.
+const FRAME_BACKLOG: usize = 8;
+
+/// A failed request, answered with its status and message as plain text.
+type Rejection = (StatusCode, String);
+/// A frame handed to every viewer of a source, or why the source failed.
+type SharedFrame = Arc<Result<AsciiFrame, String>>;
+
+/// What the handlers share: the flags `serve` was started with and what it may stream.
+struct Server {
+    args: Args,
+    /// The `--video` files, picked by their file name.
+    videos: Vec<String>,
+    /// The `--camera` devices, picked as `cam0`, `cam1` and so on.
+    cameras: Vec<String>,
+    /// Permits of the `/convert` requests fetching or converting right now.
+    conversions: Arc<Semaphore>,
+    /// The sources converted for the frame streams by file or device, each once however many
+    /// watch it, as a camera takes one reader at a time.
+    sources: Mutex<HashMap<String, broadcast::Sender<SharedFrame>>>,
+    /// Permits of the frame stream viewers connected right now.
+    viewers: Arc<Semaphore>,
+}
+
+/// The parameters of a request. The conversion settings fall back to the flags `serve` was
+/// started with and apply to `/convert`; the frame streams are converted with the flags, as
+/// every viewer of a source shares its frames.
+#[derive(Deserialize)]
+struct RequestQuery {
+    /// The http or https URL of the image `/convert` converts.
+    url: Option<String>,
+    /// What `/ws` and `/stream` play: a `--video` by file name or a `--camera` as `cam0`,
+    /// `cam1`, ..., the first video or else the first camera by default.
+    source: Option<String>,
+    /// How `/ws` and `/stream` send each frame.
+    #[serde(default)]
+    frames: FrameEncoding,
+    width: Option<u32>,
+    height: Option<u32>,
+    gamma: Option<f32>,
+    charset: Option<Charset>,
+    invert: Option<bool>,
+    color: Option<ColorMode>,
+    format: Option<OutputFormat>,
+}
+
+/// How frames are sent, each as one WebSocket text message or server-sent event.
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+enum FrameEncoding {
+    /// The art with ANSI color escapes, ready for a terminal emulator such as xterm.js
+    #[default]
+    Ansi,
+    /// A `FrameMessage` object, for front-ends drawing the cells themselves
+    Json,
+}
+
+/// A frame sent as JSON.
+#[derive(Serialize)]
+struct FrameMessage<'a> {
+    art: &'a str,
+    /// Color of each cell, empty without a color mode.
+    colors: &'a [CellColor],
+    /// Seconds from the start of the video, if the container gives it.
+    timestamp: Option<f64>,
+}
+
+impl RequestQuery {
+    /// The server's settings with the ones given in the query on top.
+    fn args(&self, defaults: &Args) -> Args {
+        let mut args = defaults.clone();
+        let convert = &mut args.convert;
+        convert.width = self.width.unwrap_or(convert.width);
+        convert.height = self.height.unwrap_or(convert.height);
+        convert.gamma = self.gamma.unwrap_or(convert.gamma);
+        convert.charset = self.charset.unwrap_or(convert.charset);
+        convert.invert = self.invert.unwrap_or(convert.invert);
+        convert.color = self.color.unwrap_or(convert.color);
+        convert.format = self.format.unwrap_or(convert.format);
+        // There is no terminal to fit
+        convert.fit = false;
+        args
+    }
+}
+
+impl Server {
+    /// The video or camera a stream asked for, out of the ones given on the command line.
+    fn input(&self, source: Option<&str>) -> Result<VideoInput, Rejection> {
+        let camera = |name: &str| {
+            let index = name.strip_prefix("cam")?.parse::<usize>().ok()?;
+            self.cameras.get(index).cloned().map(VideoInput::Camera)
+        };
+        let video = |name: &str| {
+            self.videos
+                .iter()
+                .find(|file| {
+                    Path::new(file)
+                        .file_name()
+                        .is_some_and(|file_name| file_name == name)
+                })
+                .cloned()
+                .map(VideoInput::File)
+        };
+        let input = match source {
+            Some(name) => camera(name).or_else(|| video(name)),
+            None => self
+                .videos
+                .first()
+                .cloned()
+                .map(VideoInput::File)
+                .or_else(|| camera("cam0")),
+        };
+        input.ok_or_else(|| {
+            let source = source.unwrap_or("a video");
+            (StatusCode::NOT_FOUND, format!("{} is not served", source))
+        })
+    }
+
+    /// A place for one more frame stream viewer, refused once `MAX_VIEWERS` are watching.
+    fn admit(&self) -> Result<OwnedSemaphorePermit, Rejection> {
+        self.viewers.clone().try_acquire_owned().map_err(|_| {
+            let message = format!("{} viewers are watching already", MAX_VIEWERS);
+            (StatusCode::SERVICE_UNAVAILABLE, message)
+        })
+    }
+
+    /// Subscribes to the frames of a source, starting its conversion unless another viewer
+    /// already did.
+    fn watch(self: &Arc<Self>, input: VideoInput) -> broadcast::Receiver<SharedFrame> {
+        let (VideoInput::File(name) | VideoInput::Camera(name)) = &input;
+        let name = name.clone();
+        let mut sources = self.sources.lock().unwrap();
+        if let Some(sender) = sources.get(&name) {
+            return sender.subscribe();
+        }
+        let (sender, receiver) = broadcast::channel(FRAME_BACKLOG);
+        sources.insert(name.clone(), sender.clone());
+        let frames = FrameStream::open(input, self.args.clone());
+        tokio::spawn(broadcast_frames(self.clone(), name, frames, sender));
+        receiver
+    }
+}
+
+/// Serves `GET /convert` and the `/ws` and `/stream` frame streams on `address` until the
+/// process is stopped.
+pub fn run(
+    address: SocketAddr,
+    videos: Vec<String>,
+    cameras: Vec<String>,
+    args: Args,
+) -> Result<(), AsciiGenError> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| AsciiGenError::Config(format!("cannot start the server: {}", e)))?;
+    let server = Server {
+        args,
+        videos,
+        cameras,
+        conversions: Arc::new(Semaphore::new(MAX_CONVERSIONS)),
+        sources: Mutex::default(),
+        viewers: Arc::new(Semaphore::new(MAX_VIEWERS)),
+    };
+    runtime.block_on(async move {
+        let listener = tokio::net::TcpListener::bind(address)
+            .await
+            .map_err(|e| listen_error(address, e))?;
+        tracing::info!(%address, "serving ascii art");
+        eprintln!("Serving on http://{}/convert", address);
+        axum::serve(listener, router(server))
+            .await
+            .map_err(|e| listen_error(address, e))
+    })
+}
+
+fn listen_error(address: SocketAddr, error: std::io::Error) -> AsciiGenError {
+    AsciiGenError::Config(format!("cannot listen on {}: {}", address, error))
+}
+
+fn router(server: Server) -> Router {
+    Router::new()
+        .route("/convert", get(convert))
+        .route("/ws", get(frames))
+        .route("/stream", get(events))
+        .with_state(Arc::new(server))
+}
+
+async fn convert(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<RequestQuery>,
+) -> Result<Response, Rejection> {
+    let args = query.args(&server.args);
+    let url = query
+        .url
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "url is missing".to_string()))?;
+    // The permit goes with the blocking task, which runs on when the client goes away
+    let permit = server
+        .conversions
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+    // Fetching blocks and converting keeps a core busy, neither belongs on the runtime
+    let art = tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        let image = fetch_image(&url, args.convert.max_memory, &args.playback)?;
+        let source = ImageConverter::from_image(image);
+        App::convert_image(&source, &args, &ConversionHandle::default())
+            .map(|art| (art, args.convert.format))
+            .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))??;
+
+    let (art, format) = art;
+    let content_type = match format {
+        OutputFormat::Html => "text/html; charset=utf-8",
+        OutputFormat::Text | OutputFormat::Ansi => "text/plain; charset=utf-8",
+    };
+    Ok(([(header::CONTENT_TYPE, content_type)], art).into_response())
+}
+
+/// Frames of a video or camera, converted on their own thread and handed out when due.
+struct FrameStream {
+    frames: AsyncVideoStream,
+    start_time: tokio::time::Instant,
+}
+
+impl FrameStream {
+    fn open(input: VideoInput, args: Args) -> Self {
+        Self {
+            frames: AsyncVideoStream::spawn(DEFAULT_CAPACITY, move || input.converter(&args)),
+            start_time: tokio::time::Instant::now(),
+        }
+    }
+
+    /// Waits until the next frame is due, or `None` once the source ends.
+    async fn next(&mut self) -> Option<Result<AsciiFrame, AsciiGenError>> {
+        let frame = match self.frames.next_frame().await? {
+            Ok(frame) => frame,
+            Err(e) => {
+                tracing::warn!(error = %e, "streaming frames failed");
+                return Some(Err(e));
+            }
+        };
+        if let Some(timestamp) = frame.timestamp {
+            tokio::time::sleep_until(self.start_time + timestamp).await;
+        }
+        Some(Ok(frame))
+    }
+}
+
+/// Hands each frame of a source to its viewers when due, until the source ends or fails or
+/// the last viewer leaves, dropping the converter with it.
+async fn broadcast_frames(
+    server: Arc<Server>,
+    name: String,
+    mut frames: FrameStream,
+    sender: broadcast::Sender<SharedFrame>,
+) {
+    loop {
+        let frame = frames.next().await;
+        // Under the lock, so no viewer subscribes between the last one leaving and the removal
+        let mut sources = server.sources.lock().unwrap();
+        let more = match frame {
+            Some(frame) => {
+                let failed = frame.is_err();
+                let frame = Arc::new(frame.map_err(|e| e.to_string()));
+                sender.send(frame).is_ok() && !failed
+            }
+            None => false,
+        };
+        if !more {
+            sources.remove(&name);
+            return;
+        }
+    }
+}
+
+/// The next frame for a viewer, skipping the ones it fell too far behind to get, or `None`
+/// once the source ended.
+async fn next_frame(frames: &mut broadcast::Receiver<SharedFrame>) -> Option<SharedFrame> {
+    loop {
+        match frames.recv().await {
+            Ok(frame) => return Some(frame),
+            Err(RecvError::Lagged(skipped)) => tracing::debug!(skipped, "viewer fell behind"),
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+/// A frame as sent to a viewer asking for `encoding`.
+fn encode(frame: &AsciiFrame, encoding: FrameEncoding, color_mode: ColorMode) -> String {
+    match encoding {
+        FrameEncoding::Ansi => color::to_ansi(&frame.art, &frame.colors, color_mode),
+        FrameEncoding::Json => frame_json(frame),
+    }
+}
+
+/// Upgrades to a WebSocket sending one text message per frame.
+async fn frames(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<RequestQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, Rejection> {
+    let input = server.input(query.source.as_deref())?;
+    let viewer = server.admit()?;
+    let encoding = query.frames;
+    // Opened once upgraded, so clients that never finish the handshake start nothing
+    Ok(upgrade.on_upgrade(move |socket| async move {
+        let _viewer = viewer;
+        let frames = server.watch(input);
+        send_frames(socket, frames, encoding, server.args.convert.color).await;
+    }))
+}
+
+/// Sends each frame when it is due, until the video ends or the client goes away.
+async fn send_frames(
+    mut socket: WebSocket,
+    mut frames: broadcast::Receiver<SharedFrame>,
+    encoding: FrameEncoding,
+    color_mode: ColorMode,
+) {
+    while let Some(frame) = next_frame(&mut frames).await {
+        let message = match frame.as_ref() {
+            Ok(frame) => Message::Text(encode(frame, encoding, color_mode)),
+            Err(e) => {
+                let close = CloseFrame {
+                    code: close_code::ERROR,
+                    reason: e.clone().into(),
+                };
+                let _ = socket.send(Message::Close(Some(close))).await;
+                return;
+            }
+        };
+        if socket.send(message).await.is_err() {
+            return;
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// Streams one `frame` event per frame, ending with an `error` event if the source fails.
+async fn events(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<RequestQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, Rejection> {
+    let input = server.input(query.source.as_deref())?;
+    let viewer = server.admit()?;
+    let (encoding, color_mode) = (query.frames, server.args.convert.color);
+    let frames = server.watch(input);
+    // The viewer's place goes with the stream, which is dropped after an error, ending the
+    // response, or once the client goes away
+    let events = stream::unfold(Some((viewer, frames)), move |state| async move {
+        let (viewer, mut frames) = state?;
+        let frame = next_frame(&mut frames).await?;
+        match frame.as_ref() {
+            Ok(frame) => Some((
+                Ok(Event::default()
+                    .event("frame")
+                    .data(encode(frame, encoding, color_mode))),
+                Some((viewer, frames)),
+            )),
+            Err(e) => Some((Ok(Event::default().event("error").data(e)), None)),
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+fn frame_json(frame: &AsciiFrame) -> String {
+    let message = FrameMessage {
+        art: &frame.art,
+        colors: &frame.colors,
+        timestamp: frame.timestamp.map(|timestamp| timestamp.as_secs_f64()),
+    };
+    serde_json::to_string(&message).expect("frames serialize to JSON")
+}
+
+/// Downloads and decodes an image, refusing anything that would take more than `max_memory`.
+/// A download that drops is fetched on from the byte it stopped at, up to `--retries` times.
+fn fetch_image(
+    url: &str,
+    max_memory: u64,
+    playback: &PlaybackArgs,
+) -> Result<DynamicImage, Rejection> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "url must be http or https".to_string(),
+        ));
+    }
+    let cannot_fetch = |e: &dyn std::fmt::Display| {
+        (
+            StatusCode::BAD_GATEWAY,
+            format!("cannot fetch {}: {}", url, e),
+        )
+    };
+    // Slow servers are fine as long as every read gets somewhere. Every host, the ones
+    // redirected to included, is resolved through `resolve_public`
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(playback.timeout)
+        .timeout_read(playback.timeout)
+        .resolver(resolve_public)
+        .build();
+    // Refused up front too, to tell the client why rather than fail as a bad gateway
+    let target = agent
+        .get(url)
+        .request_url()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let port = target
+        .port()
+        .unwrap_or(if target.scheme() == "https" { 443 } else { 80 });
+    if let Err(e) = resolve_public(&format!("{}:{}", target.host(), port)) {
+        if e.kind() == io::ErrorKind::PermissionDenied {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("cannot fetch {}: {}", url, e),
+            ));
+        }
+    }
+
+    let mut bytes = Vec::new();
+    let mut tries = 0;
+    loop {
+        let mut request = agent.get(url);
+        if !bytes.is_empty() {
+            request = request.set("Range", &format!("bytes={}-", bytes.len()));
+        }
+        let response = match request.call() {
+            Ok(response) => response,
+            // Hosts that do not resolve, or resolve to refused addresses, stay that way
+            Err(e @ ureq::Error::Transport(_))
+                if e.kind() != ureq::ErrorKind::Dns && tries < playback.retries =>
+            {
+                tries += 1;
+                tracing::debug!(url, error = %e, tries, "fetching again");
+                std::thread::sleep(REFETCH_DELAY * (1 << tries.min(6)));
+                continue;
+            }
+            Err(e) => return Err(cannot_fetch(&e)),
+        };
+        // A server that ignores the range sends the whole image again
+        if response.status() != 206 {
+            bytes.clear();
+        }
+        let limit = (max_memory + 1).saturating_sub(bytes.len() as u64);
+        // What was read before an error stays in `bytes`
+        match response.into_reader().take(limit).read_to_end(&mut bytes) {
+            Ok(_) => break,
+            Err(e) if tries < playback.retries => {
+                tries += 1;
+                tracing::debug!(url, error = %e, received = bytes.len(), tries, "resuming");
+                std::thread::sleep(REFETCH_DELAY * (1 << tries.min(6)));
+            }
+            Err(e) => return Err(cannot_fetch(&e)),
+        }
+    }
+    if bytes.len() as u64 > max_memory {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("{} is larger than {} bytes", url, max_memory),
+        ));
+    }
+
+    let undecodable = |e: image::ImageError| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("cannot decode {}: {}", url, e),
+        )
+    };
+    let mut reader = image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| undecodable(e.into()))?;
+    let mut limits = image::io::Limits::default();
+    limits.max_alloc = Some(max_memory);
+    reader.limits(limits);
+    let image = reader.decode().map_err(undecodable)?;
+
+    if image.width() == 0 || image.height() == 0 {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("{} has no pixels", url),
+        ));
+    }
+    Ok(image)
+}
+
+/// Resolves `host:port` like the system does, refusing hosts with an address on the
+/// server's own network, so `/convert` cannot be pointed at loopback services, the LAN or
+/// cloud metadata endpoints.
+fn resolve_public(netloc: &str) -> io::Result<Vec<SocketAddr>> {
+    let addresses: Vec<SocketAddr> = netloc.to_socket_addrs()?.collect();
+    if let Some(address) = addresses.iter().find(|address| is_internal(address.ip())) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is on a private network", address.ip()),
+        ));
+    }
+    Ok(addresses)
+}
+
+/// Whether an address is loopback, private, link-local, unspecified or otherwise not on
+/// the public internet.
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && b & 0xc0 == 64)
+                // "This network", 0.0.0.0/8
+                || a == 0
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_internal(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local, fc00::/7
+                || first & 0xfe00 == 0xfc00
+                // Link-local, fe80::/10
+                || first & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn internal(ip: &str) -> bool {
+        is_internal(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn internal_addresses_are_refused() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "0.1.2.3",
+            "255.255.255.255",
+            "100.64.0.1",
+            "100.127.255.255",
+            "192.0.2.1",
+            "::1",
+            "::",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(internal(ip), "{} should be internal", ip);
+        }
+    }
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        for ip in [
+            "1.1.1.1",
+            "8.8.8.8",
+            "100.63.255.255",
+            "100.128.0.1",
+            "172.32.0.1",
+            "2606:4700:4700::1111",
+            "::ffff:8.8.8.8",
+        ] {
+            assert!(!internal(ip), "{} should be public", ip);
+        }
+    }
+}
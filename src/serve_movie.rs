@@ -0,0 +1,145 @@
+use crate::cli::{Args, OnEnd};
+use crate::VideoInput;
+use ascii_gen::color;
+use ascii_gen::error::AsciiGenError;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+/// Hides the cursor and clears the screen once a client connects.
+const START: &str = "\x1b[?25l\x1b[2J";
+/// Moves the cursor back to the top left before each frame.
+const HOME: &str = "\x1b[H";
+/// Resets the colors and shows the cursor again before disconnecting.
+const END: &str = "\x1b[0m\x1b[?25h\r\n";
+/// How long a write to a client, or a read from it, may stall before the client is dropped,
+/// so viewers that stop reading give their place back.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A client's place among the `--max-clients`, given back when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Plays `file` to every client connecting to `address` over raw TCP, each from the start and
+/// on its own clock, so `nc` or `telnet` is all it takes to watch.
+pub fn run(
+    file: PathBuf,
+    address: SocketAddr,
+    max_clients: usize,
+    mut args: Args,
+) -> Result<(), AsciiGenError> {
+    let input = VideoInput::File(file.to_string_lossy().into_owned());
+    // Fail now rather than on the first client
+    input.open(&args.playback)?;
+    // Clients' terminals are unknown, so their size is too
+    args.convert.fit = false;
+
+    let listener = TcpListener::bind(address)
+        .map_err(|e| AsciiGenError::Config(format!("cannot listen on {}: {}", address, e)))?;
+    tracing::info!(%address, file = %file.display(), "serving movie");
+    eprintln!("Serving {} on {}", file.display(), address);
+
+    let input = Arc::new(input);
+    let args = Arc::new(args);
+    let clients = Arc::new(AtomicUsize::new(0));
+    for client in listener.incoming() {
+        let mut client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(error = %e, "accepting a client failed");
+                continue;
+            }
+        };
+        let _ = client.set_write_timeout(Some(CLIENT_TIMEOUT));
+        let _ = client.set_read_timeout(Some(CLIENT_TIMEOUT));
+        let full = clients.fetch_add(1, Ordering::SeqCst) >= max_clients;
+        let slot = Slot(Arc::clone(&clients));
+        if full {
+            drop(slot);
+            let _ = client.write_all(b"Too many viewers, try again later.\r\n");
+            continue;
+        }
+
+        let (input, args) = (Arc::clone(&input), Arc::clone(&args));
+        std::thread::spawn(move || {
+            let _slot = slot;
+            let peer = client.peer_addr().ok();
+            tracing::info!(?peer, "client connected");
+            match play_to(&mut client, &input, &args) {
+                Ok(true) => {
+                    let _ = client.write_all(END.as_bytes());
+                }
+                Ok(false) => {}
+                Err(e) => tracing::warn!(?peer, error = %e, "playing to a client failed"),
+            }
+            tracing::info!(?peer, "client disconnected");
+        });
+    }
+    Ok(())
+}
+
+/// Plays the movie to one client until it ends, returning true, or the client goes away or
+/// stops reading, returning false.
+fn play_to(client: &mut TcpStream, input: &VideoInput, args: &Args) -> Result<bool, AsciiGenError> {
+    let color_mode = args.convert.color;
+    let _ = client.set_nodelay(true);
+    if !send(client, START)? {
+        return Ok(false);
+    }
+
+    loop {
+        let mut converter = input.converter(args)?;
+        let start_time = Instant::now();
+        converter.set_start_time(Some(start_time));
+
+        while let Some(frame) = converter.next() {
+            let frame = frame?;
+            if let Some(timestamp) = frame.timestamp {
+                std::thread::sleep(timestamp.saturating_sub(start_time.elapsed()));
+            }
+
+            // Terminals on the other end of a raw socket need both a carriage return and a
+            // line feed
+            let art = color::to_ansi(&frame.art, &frame.colors, color_mode);
+            let output = format!("{}{}", HOME, art.replace('\n', "\r\n"));
+            if !send(client, &output)? {
+                return Ok(false);
+            }
+            converter.recycle(frame);
+        }
+
+        if args.playback.on_end != OnEnd::Loop {
+            return Ok(true);
+        }
+    }
+}
+
+/// Writes to the client, returning false once it has disconnected or a write timed out.
+fn send(client: &mut TcpStream, output: &str) -> Result<bool, AsciiGenError> {
+    match client.write_all(output.as_bytes()) {
+        Ok(()) => Ok(true),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::WouldBlock
+            ) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(AsciiGenError::Terminal(e)),
+    }
+}
@@ -0,0 +1,132 @@
+use crate::art::ArtBuffer;
+use crate::color::{CellColor, ColorMode};
+use crate::converter::AsciiOptions;
+use crate::error::AsciiGenError;
+use crate::stream::{self, VideoAsciiStream};
+use crate::temporal::TemporalFilter;
+
+/// Spaces between thumbnails side by side.
+pub const GAP: u32 = 2;
+
+/// Color of the labels and the gaps between thumbnails.
+const PLAIN: CellColor = CellColor {
+    fg: [255; 3],
+    bg: None,
+};
+
+/// Frames of a video laid out in a grid, each under its timestamp: a quick storyboard.
+///
+/// ```no_run
+/// use ascii_gen::{color::ColorMode, converter::AsciiOptions, sheet::ContactSheet};
+/// use ascii_gen::{deinterlace::Deinterlace, stream::VideoAsciiStream};
+///
+/// let mut stream = VideoAsciiStream::open("movie.mp4", Deinterlace::Auto, true)?;
+/// let options = AsciiOptions::new(38, 11, 1.0);
+/// let sheet = ContactSheet::new(&mut stream, 4, 4, &options, ColorMode::None)?;
+/// println!("{}", sheet.art);
+/// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+/// ```
+pub struct ContactSheet {
+    pub art: String,
+    /// Color of each cell, empty without a color mode.
+    pub colors: Vec<CellColor>,
+}
+
+impl ContactSheet {
+    /// Converts `columns` x `rows` frames spread evenly over the video with `options`, the
+    /// size of one thumbnail. Fewer come out when the video ends before the last ones.
+    pub fn new(
+        stream: &mut VideoAsciiStream,
+        columns: u32,
+        rows: u32,
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+    ) -> Result<Self, AsciiGenError> {
+        let duration = stream.duration().ok_or_else(|| {
+            AsciiGenError::Config("the video's length is unknown to spread frames over".into())
+        })?;
+        let count = columns.max(1) * rows.max(1);
+
+        let mut thumbnails = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            // The middle of each part, as the very first and last frames are often black
+            let time = duration * (index as f64 + 0.5) / count as f64;
+            if !stream.seek(time)? {
+                break;
+            }
+            let mut art = ArtBuffer::default();
+            stream.convert(&mut art, options, color_mode, &mut TemporalFilter::new(0))?;
+            let label = stream::format_timestamp(stream.timestamp().unwrap_or(time));
+            thumbnails.push((label, art));
+        }
+
+        let mut sheet = Self::layout(&thumbnails, columns.max(1), options.width());
+        if !color_mode.is_enabled() {
+            sheet.colors.clear();
+        }
+        Ok(sheet)
+    }
+
+    /// Lays the thumbnails out `columns` to a row, with a blank line between rows.
+    fn layout(thumbnails: &[(String, ArtBuffer)], columns: u32, width: u32) -> Self {
+        let width = width as usize;
+        let mut sheet = Self {
+            art: String::new(),
+            colors: Vec::new(),
+        };
+
+        for (row, thumbnails) in thumbnails.chunks(columns as usize).enumerate() {
+            if row > 0 {
+                sheet.art.push('\n');
+            }
+
+            for (column, (label, _)) in thumbnails.iter().enumerate() {
+                if column > 0 {
+                    sheet.pad(GAP as usize);
+                }
+                let label: Vec<char> = label.chars().take(width).collect();
+                for &c in &label {
+                    sheet.push(c, PLAIN);
+                }
+                sheet.pad(width - label.len());
+            }
+            sheet.art.push('\n');
+
+            let height = thumbnails
+                .iter()
+                .map(|(_, art)| art.art.lines().count())
+                .max()
+                .unwrap_or(0);
+            let mut cells: Vec<_> = thumbnails
+                .iter()
+                .map(|(_, art)| (art.art.lines(), art.colors.iter()))
+                .collect();
+            for _ in 0..height {
+                for (column, (lines, colors)) in cells.iter_mut().enumerate() {
+                    if column > 0 {
+                        sheet.pad(GAP as usize);
+                    }
+                    let mut drawn = 0;
+                    for c in lines.next().unwrap_or_default().chars() {
+                        sheet.push(c, colors.next().copied().unwrap_or(PLAIN));
+                        drawn += 1;
+                    }
+                    sheet.pad(width.saturating_sub(drawn));
+                }
+                sheet.art.push('\n');
+            }
+        }
+        sheet
+    }
+
+    fn push(&mut self, c: char, color: CellColor) {
+        self.art.push(c);
+        self.colors.push(color);
+    }
+
+    fn pad(&mut self, spaces: usize) {
+        for _ in 0..spaces {
+            self.push(' ', PLAIN);
+        }
+    }
+}
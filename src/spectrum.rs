@@ -0,0 +1,142 @@
+use std::f32::consts::PI;
+
+/// Samples analysed at once, about 46 ms at 44.1 kHz.
+pub const FFT_SIZE: usize = 2048;
+/// Lowest and highest frequencies shown, in Hz.
+const FREQUENCY_RANGE: (f32, f32) = (40.0, 16_000.0);
+/// Quietest level shown, in decibels below a full-scale sine.
+const FLOOR_DB: f32 = 60.0;
+/// How much of a band's level is left after each frame once the sound fades.
+const DECAY: f32 = 0.85;
+
+/// The frequency content of the latest audio, as bars rising and falling with the music.
+/// Audio goes in with `push` as it is decoded, bars come out with `bands` as they are drawn.
+///
+/// ```
+/// use ascii_gen::spectrum::Spectrum;
+///
+/// let rate = 44_100.0;
+/// let tone: Vec<f32> = (0..4096)
+///     .map(|i| (i as f32 * 2.0 * std::f32::consts::PI * 1000.0 / rate).sin())
+///     .collect();
+/// let mut spectrum = Spectrum::default();
+/// spectrum.push(&tone, 1, rate as u32);
+/// let bands = spectrum.bands(16);
+/// // The band around 1 kHz is at full level, the lowest one silent
+/// assert!(bands[8] > 0.9 && bands[0] < 0.1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Spectrum {
+    /// The latest mono samples, oldest first once rotated to `next`.
+    samples: Vec<f32>,
+    next: usize,
+    sample_rate: u32,
+    /// Sum and count of the samples of a frame split between two pushes.
+    partial: (f32, u16),
+    levels: Vec<f32>,
+}
+
+impl Default for Spectrum {
+    fn default() -> Self {
+        Self {
+            samples: vec![0.0; FFT_SIZE],
+            next: 0,
+            sample_rate: 44_100,
+            partial: (0.0, 0),
+            levels: Vec::new(),
+        }
+    }
+}
+
+impl Spectrum {
+    /// Adds interleaved samples from -1.0 to 1.0, mixing the channels down to mono.
+    pub fn push(&mut self, samples: &[f32], channels: u16, sample_rate: u32) {
+        let channels = channels.max(1);
+        self.sample_rate = sample_rate.max(1);
+        for &sample in samples {
+            let (sum, count) = &mut self.partial;
+            *sum += sample;
+            *count += 1;
+            if *count >= channels {
+                self.samples[self.next] = *sum / f32::from(channels);
+                self.next = (self.next + 1) % FFT_SIZE;
+                self.partial = (0.0, 0);
+            }
+        }
+    }
+
+    /// Levels from 0.0 to 1.0 of `count` bands spaced evenly in pitch, lowest first. Levels
+    /// jump up at once and fall back slowly, so each call should come with a drawn frame.
+    pub fn bands(&mut self, count: usize) -> &[f32] {
+        let mut real: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| {
+                // A Hann window, so frequencies between bins do not smear over the others
+                let window = 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos();
+                self.samples[(self.next + i) % FFT_SIZE] * window
+            })
+            .collect();
+        let mut imaginary = vec![0.0; FFT_SIZE];
+        fft(&mut real, &mut imaginary);
+
+        if self.levels.len() != count {
+            self.levels = vec![0.0; count];
+        }
+        let bin_width = self.sample_rate as f32 / FFT_SIZE as f32;
+        let (low, high) = (
+            FREQUENCY_RANGE.0,
+            FREQUENCY_RANGE.1.min(self.sample_rate as f32 / 2.0),
+        );
+        // A full-scale sine peaks at a quarter of the window through the Hann window
+        let full_scale = FFT_SIZE as f32 / 4.0;
+        for (band, level) in self.levels.iter_mut().enumerate() {
+            let edge = |band: usize| low * (high / low).powf(band as f32 / count as f32);
+            let first = ((edge(band) / bin_width) as usize).clamp(1, FFT_SIZE / 2 - 1);
+            let last = ((edge(band + 1) / bin_width) as usize).clamp(first, FFT_SIZE / 2 - 1);
+            let magnitude = (first..=last)
+                .map(|bin| real[bin].hypot(imaginary[bin]))
+                .fold(0.0, f32::max);
+
+            let decibels = 20.0 * (magnitude / full_scale).max(1e-9).log10();
+            let target = ((decibels + FLOOR_DB) / FLOOR_DB).clamp(0.0, 1.0);
+            *level = target.max(*level * DECAY);
+        }
+        &self.levels
+    }
+}
+
+/// Transforms the samples into their frequencies in place, with an iterative radix-2
+/// Cooley-Tukey FFT. Both slices are as long as each other, a power of two.
+fn fft(real: &mut [f32], imaginary: &mut [f32]) {
+    let n = real.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+        if i < j {
+            real.swap(i, j);
+            imaginary.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= n {
+        let angle = -2.0 * PI / length as f32;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + length / 2);
+                let twiddled_real = real[b] * cos - imaginary[b] * sin;
+                let twiddled_imaginary = real[b] * sin + imaginary[b] * cos;
+                real[b] = real[a] - twiddled_real;
+                imaginary[b] = imaginary[a] - twiddled_imaginary;
+                real[a] += twiddled_real;
+                imaginary[a] += twiddled_imaginary;
+            }
+        }
+        length <<= 1;
+    }
+}
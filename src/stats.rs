@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+/// Timings collected while playing a video, shown in the stats overlay.
+#[derive(Default)]
+pub struct PlaybackStats {
+    pub decode: Duration,
+    pub convert: Duration,
+    pub draw: Duration,
+    pub dropped_frames: u64,
+    /// How far the displayed frame lags behind the playback clock, in seconds.
+    pub late: f64,
+    /// How far the displayed frame runs ahead of the audio, in seconds, or `None` when no
+    /// audio plays, as for silent videos and timelapses.
+    pub desync: Option<f64>,
+}
+
+impl PlaybackStats {
+    /// Formats the stats as lines for the overlay.
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Decode:  {:>7.2} ms", millis(self.decode)),
+            format!("Convert: {:>7.2} ms", millis(self.convert)),
+            format!("Draw:    {:>7.2} ms", millis(self.draw)),
+            format!("Dropped: {:>7}", self.dropped_frames),
+            match self.desync {
+                Some(desync) => format!("A/V:     {:>+7.2} ms", desync * 1000.0),
+                None => format!("A/V:     {:>7}", "n/a"),
+            },
+            format!("Late:    {:>+7.2} ms", self.late * 1000.0),
+        ]
+    }
+}
+
+pub fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desync_is_shown_only_with_audio() {
+        let mut stats = PlaybackStats::default();
+        assert!(stats.lines().contains(&"A/V:         n/a".to_string()));
+        stats.desync = Some(-0.0125);
+        assert!(stats.lines().contains(&"A/V:      -12.50 ms".to_string()));
+    }
+}
@@ -0,0 +1,811 @@
+use crate::art::ArtBuffer;
+use crate::charset::SharedMapper;
+use crate::color::{CellColor, ColorMode};
+use crate::converter::{self, AsciiOptions, FrameConverter};
+use crate::deinterlace::{Deinterlace, Deinterlacer};
+use crate::error::AsciiGenError;
+use crate::exposure::AutoExposure;
+use crate::rotation::Rotation;
+use crate::temporal::TemporalFilter;
+use ffmpeg_next as ffmpeg;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Capture device opened by `VideoAsciiStream::open_camera` when none is given.
+#[cfg(target_os = "linux")]
+pub const DEFAULT_CAMERA: &str = "/dev/video0";
+#[cfg(target_os = "macos")]
+pub const DEFAULT_CAMERA: &str = "0";
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub const DEFAULT_CAMERA: &str = "video=Integrated Camera";
+
+/// The capture device of the `index`th camera, counting from 0. DirectShow names its
+/// devices rather than numbering them, so on Windows this is always the default camera.
+pub fn camera_device(index: u32) -> String {
+    if cfg!(target_os = "linux") {
+        format!("/dev/video{}", index)
+    } else if cfg!(target_os = "macos") {
+        index.to_string()
+    } else {
+        DEFAULT_CAMERA.to_string()
+    }
+}
+
+/// Whether a video is a network address such as `https://host/clip.mp4` or
+/// `rtsp://camera/live` rather than a file.
+///
+/// ```
+/// use ascii_gen::stream::is_url;
+///
+/// assert!(is_url("rtsp://192.168.1.20/live"));
+/// assert!(!is_url("file:///home/me/clip.mp4"));
+/// assert!(!is_url("C:\\Videos\\clip.mp4"));
+/// ```
+pub fn is_url(path: &str) -> bool {
+    path.split_once("://").is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme != "file"
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Whether a file has a sound track, so players know not to look for an audio device.
+pub fn has_audio(path: impl AsRef<Path>) -> Result<bool, ffmpeg::Error> {
+    ffmpeg::init()?;
+    let input = ffmpeg::format::input(&path.as_ref())?;
+    let has_audio = input.streams().best(ffmpeg::media::Type::Audio).is_some();
+    Ok(has_audio)
+}
+
+/// The ffmpeg protocol options of the network input at `url`. Every protocol takes
+/// `rw_timeout`, but `timeout` means a socket timeout to some and how long to wait for a
+/// client to others, so it is only set where it is the former.
+fn network_options(url: &str, timeout: Duration) -> ffmpeg::Dictionary<'static> {
+    let micros = timeout.as_micros().to_string();
+    let mut options = ffmpeg::Dictionary::new();
+    options.set("rw_timeout", &micros);
+    let scheme = url.split_once("://").map_or("", |(scheme, _)| scheme);
+    match scheme.to_ascii_lowercase().as_str() {
+        "http" | "https" => {
+            options.set("timeout", &micros);
+            // Reconnect after errors, asking for the rest of the file from the byte it stopped at
+            options.set("reconnect", "1");
+            options.set("reconnect_on_network_error", "1");
+            options.set("reconnect_streamed", "1");
+            options.set("reconnect_delay_max", &timeout.as_secs().max(1).to_string());
+        }
+        "tcp" => options.set("timeout", &micros),
+        // libavformat 59 (ffmpeg 5) renamed the socket timeout from `stimeout`, and before it
+        // `timeout` made rtsp wait for incoming connections instead
+        "rtsp" | "rtsps" if ffmpeg::format::version() >> 16 >= 59 => {
+            options.set("timeout", &micros)
+        }
+        "rtsp" | "rtsps" => options.set("stimeout", &micros),
+        // rtmp's `timeout` is how long to listen for a publisher
+        _ => {}
+    }
+    options
+}
+
+/// Seconds as a duration, with negative ones, which `Duration` cannot hold, as zero.
+fn non_negative_seconds(seconds: f64) -> Duration {
+    Duration::try_from_secs_f64(seconds.max(0.0)).unwrap_or(Duration::MAX)
+}
+
+/// The ffmpeg input device cameras are read through.
+#[cfg(target_os = "linux")]
+const CAMERA_FORMAT: &str = "v4l2";
+#[cfg(target_os = "macos")]
+const CAMERA_FORMAT: &str = "avfoundation";
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const CAMERA_FORMAT: &str = "dshow";
+
+/// Whether the ffmpeg libraries were built with the input device cameras are read through.
+pub fn camera_supported() -> bool {
+    ffmpeg::init().is_ok()
+        && ffmpeg::device::input::video().any(|format| format.name() == CAMERA_FORMAT)
+}
+
+/// Decodes a video file frame by frame and converts each frame to ASCII art.
+///
+/// ```no_run
+/// use ascii_gen::{art::ArtBuffer, color::ColorMode, converter::AsciiOptions};
+/// use ascii_gen::{deinterlace::Deinterlace, stream::VideoAsciiStream, temporal::TemporalFilter};
+///
+/// let mut stream = VideoAsciiStream::open("movie.mp4", Deinterlace::Auto, true)?;
+/// let options = AsciiOptions::builder().size(80, 24).build()?;
+/// let mut art = ArtBuffer::default();
+/// while stream.advance()? {
+///     stream.convert(&mut art, &options, ColorMode::None, &mut TemporalFilter::new(0))?;
+///     println!("{}", art.art);
+/// }
+/// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+/// ```
+pub struct VideoAsciiStream {
+    input: ffmpeg::format::context::Input,
+    stream_index: usize,
+    decoder: ffmpeg::decoder::Video,
+    /// Converts frames to RGB, made once the first frame shows its pixel format and made
+    /// again whenever the format or size changes.
+    scaler: Option<ffmpeg::software::scaling::Context>,
+    deinterlace: Deinterlace,
+    deinterlacer: Option<Deinterlacer>,
+    rotation: Rotation,
+    time_base: f64,
+    /// Presentation time of the first decoded frame in seconds, which timestamps count
+    /// from. Cameras stamp frames with the wall clock or the time since boot, and some
+    /// containers start well after zero.
+    origin: Option<f64>,
+    frame_interval: f64,
+    /// Most frames shown per second, with the ones in between skipped.
+    frame_rate: Option<f64>,
+    /// How many times faster than recorded the video plays.
+    speed: f64,
+    /// Timestamp the next shown frame has to reach, in seconds.
+    next_shown: Option<f64>,
+    /// Whether the frame rate skips every other frame or more, and whether the decoder
+    /// currently drops the frames nothing else refers to.
+    skip_for_rate: bool,
+    skipping_nonreference: bool,
+    /// Frames reused across the whole video
+    decoded: ffmpeg::frame::Video,
+    deinterlaced: ffmpeg::frame::Video,
+    rgb_frame: ffmpeg::frame::Video,
+    /// Whether the current frame came out of the deinterlacer.
+    use_deinterlaced: bool,
+    /// Whether `rgb_frame` holds the current frame yet.
+    scaled: bool,
+    end_of_file: bool,
+}
+
+impl VideoAsciiStream {
+    /// Opens the best video stream of a file, honoring its rotation metadata when `autorotate`.
+    pub fn open(
+        path: impl AsRef<Path>,
+        deinterlace: Deinterlace,
+        autorotate: bool,
+    ) -> Result<Self, ffmpeg::Error> {
+        ffmpeg::init()?;
+        let input = ffmpeg::format::input(&path.as_ref())?;
+        Self::from_input(input, deinterlace, autorotate)
+    }
+
+    /// Opens a video over the network, e.g. over http, hls or rtsp. Connecting and every read
+    /// give up after `timeout`, and http downloads that drop pick up where they stopped with
+    /// range requests.
+    pub fn open_url(
+        url: &str,
+        timeout: Duration,
+        deinterlace: Deinterlace,
+        autorotate: bool,
+    ) -> Result<Self, ffmpeg::Error> {
+        ffmpeg::init()?;
+        let input =
+            ffmpeg::format::input_with_dictionary(&Path::new(url), network_options(url, timeout))?;
+        Self::from_input(input, deinterlace, autorotate)
+    }
+
+    /// Opens a camera through the platform's capture API, e.g. `/dev/video0` with v4l2.
+    pub fn open_camera(device: &str) -> Result<Self, ffmpeg::Error> {
+        ffmpeg::init()?;
+        let format = ffmpeg::device::input::video()
+            .find(|format| format.name() == CAMERA_FORMAT)
+            .ok_or(ffmpeg::Error::DemuxerNotFound)?;
+        let input = ffmpeg::format::open_with(
+            &Path::new(device),
+            &ffmpeg::format::Format::Input(format),
+            ffmpeg::Dictionary::new(),
+        )?
+        .input();
+        Self::from_input(input, Deinterlace::Off, false)
+    }
+
+    fn from_input(
+        input: ffmpeg::format::context::Input,
+        deinterlace: Deinterlace,
+        autorotate: bool,
+    ) -> Result<Self, ffmpeg::Error> {
+        let video_stream = input
+            .streams()
+            .best(ffmpeg::media::Type::Video)
+            .ok_or(ffmpeg::Error::StreamNotFound)?;
+        let stream_index = video_stream.index();
+        let rotation = if autorotate {
+            Rotation::from_stream(&video_stream)
+        } else {
+            Rotation::None
+        };
+        let decoder = ffmpeg::codec::context::Context::from_parameters(video_stream.parameters())?
+            .decoder()
+            .video()?;
+
+        let time_base = video_stream.time_base();
+        let frame_rate = f64::from(video_stream.avg_frame_rate());
+        let frame_interval = if frame_rate > 0.0 {
+            1.0 / frame_rate
+        } else {
+            0.0
+        };
+        tracing::info!(
+            codec = ?decoder.id(),
+            pixel_format = pixel_format_name(decoder.format()),
+            width = decoder.width(),
+            height = decoder.height(),
+            frame_rate,
+            ?rotation,
+            "opened video stream"
+        );
+
+        // Set up a yadif filter for interlaced sources
+        let deinterlacer = match deinterlace {
+            Deinterlace::Off => None,
+            Deinterlace::Auto | Deinterlace::On => Some(Deinterlacer::new(&decoder, time_base)?),
+        };
+
+        Ok(Self {
+            input,
+            stream_index,
+            decoder,
+            scaler: None,
+            deinterlace,
+            deinterlacer,
+            rotation,
+            time_base: time_base.into(),
+            origin: None,
+            frame_interval,
+            frame_rate: None,
+            speed: 1.0,
+            next_shown: None,
+            decoded: ffmpeg::frame::Video::empty(),
+            deinterlaced: ffmpeg::frame::Video::empty(),
+            rgb_frame: ffmpeg::frame::Video::empty(),
+            use_deinterlaced: false,
+            scaled: false,
+            end_of_file: false,
+            skip_for_rate: false,
+            skipping_nonreference: false,
+        })
+    }
+
+    /// Size of the frames once rotated upright, in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.rotation
+            .dimensions(self.decoder.width(), self.decoder.height())
+    }
+
+    /// Length of the video in seconds, if the container gives one.
+    pub fn duration(&self) -> Option<f64> {
+        let duration = self.input.duration();
+        (duration > 0).then(|| duration as f64 / f64::from(ffmpeg::ffi::AV_TIME_BASE))
+    }
+
+    /// Jumps to the first frame at or after `seconds` into the video as recorded, decoding on
+    /// from the keyframe before it, and returns false when the video ends first.
+    pub fn seek(&mut self, seconds: f64) -> Result<bool, ffmpeg::Error> {
+        // ffmpeg seeks to presentation times as stamped, not counted from the first frame
+        let absolute = seconds + self.origin.unwrap_or(0.0);
+        let target = (absolute * f64::from(ffmpeg::ffi::AV_TIME_BASE)) as i64;
+        self.input.seek(target, ..target)?;
+        self.decoder.flush();
+        // yadif still holds frames from before the jump
+        if self.deinterlacer.is_some() {
+            let time_base = self
+                .input
+                .stream(self.stream_index)
+                .ok_or(ffmpeg::Error::StreamNotFound)?
+                .time_base();
+            self.deinterlacer = Some(Deinterlacer::new(&self.decoder, time_base)?);
+        }
+        self.end_of_file = false;
+        self.next_shown = None;
+
+        while self.advance()? {
+            // Allow for timestamps rounded to the time base
+            if self
+                .source_timestamp()
+                .is_none_or(|timestamp| timestamp + self.time_base >= seconds)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Seconds between shown frames, or 0.0 when the frame rate is unknown.
+    pub fn frame_interval(&self) -> f64 {
+        let interval = self.frame_interval / self.speed;
+        match self.frame_rate {
+            Some(frame_rate) => interval.max(1.0 / frame_rate),
+            None => interval,
+        }
+    }
+
+    /// Shows at most `frame_rate` frames per second, picking them by timestamp. The frames
+    /// in between are never scaled or converted, and when at most every other frame is shown
+    /// the decoder also drops the frames that nothing else refers to.
+    pub fn set_frame_rate(&mut self, frame_rate: Option<f64>) {
+        self.frame_rate = frame_rate.filter(|&frame_rate| frame_rate > 0.0);
+        self.next_shown = None;
+        let interval = self.frame_interval / self.speed;
+        self.skip_for_rate = self
+            .frame_rate
+            .is_some_and(|frame_rate| interval > 0.0 && frame_rate * 2.0 * interval <= 1.0);
+        self.set_behind(false);
+    }
+
+    /// Plays the video `speed` times faster than recorded, as a timelapse: timestamps are
+    /// divided by it, so the frame rate keeps the frames shown to the same pace and skips
+    /// the rest. Without a frame rate the video's own is kept.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = if speed > 0.0 { speed } else { 1.0 };
+        let frame_rate = match self.frame_rate {
+            None if self.speed > 1.0 && self.frame_interval > 0.0 => {
+                Some(1.0 / self.frame_interval)
+            }
+            frame_rate => frame_rate,
+        };
+        self.set_frame_rate(frame_rate);
+    }
+
+    /// Lets the decoder drop frames that no other frame is decoded from while playback is
+    /// `behind`, or all along when the frame rate skips most of them anyway.
+    fn set_behind(&mut self, behind: bool) {
+        let skip = behind || self.skip_for_rate;
+        if skip != self.skipping_nonreference {
+            self.decoder.skip_frame(if skip {
+                ffmpeg::Discard::NonReference
+            } else {
+                ffmpeg::Discard::Default
+            });
+            self.skipping_nonreference = skip;
+        }
+    }
+
+    /// Whether the current frame comes too soon after the last shown one for the frame rate.
+    fn skip_current(&mut self) -> bool {
+        let (Some(frame_rate), Some(timestamp)) = (self.frame_rate, self.timestamp()) else {
+            return false;
+        };
+        // Allow for timestamps rounded to the time base
+        let time_base = self.time_base / self.speed;
+        if self
+            .next_shown
+            .is_some_and(|next_shown| timestamp + time_base < next_shown)
+        {
+            return true;
+        }
+        let interval = 1.0 / frame_rate;
+        // Keep to the schedule rather than drifting by the gaps between source frames
+        self.next_shown = Some(match self.next_shown {
+            Some(next_shown) if timestamp - next_shown < interval => next_shown + interval,
+            _ => timestamp + interval,
+        });
+        false
+    }
+
+    /// Decodes the next frame, returning false once the video has ended.
+    pub fn advance(&mut self) -> Result<bool, ffmpeg::Error> {
+        let _span = tracing::trace_span!("decode").entered();
+        loop {
+            if self.decoder.receive_frame(&mut self.decoded).is_ok() {
+                self.use_deinterlaced = false;
+                if let Some(deinterlacer) = self.deinterlacer.as_mut() {
+                    if self.deinterlace == Deinterlace::On || self.decoded.is_interlaced() {
+                        // yadif holds frames back, so there may be nothing to show yet
+                        if !deinterlacer.filter(&self.decoded, &mut self.deinterlaced)? {
+                            continue;
+                        }
+                        self.use_deinterlaced = true;
+                    }
+                }
+                if self.origin.is_none() {
+                    self.origin = self.frame().pts().map(|pts| pts as f64 * self.time_base);
+                }
+                if self.skip_current() {
+                    continue;
+                }
+                self.scaled = false;
+                return Ok(true);
+            }
+
+            if self.end_of_file {
+                return Ok(false);
+            }
+
+            let mut packet = ffmpeg::Packet::empty();
+            match packet.read(&mut self.input) {
+                Ok(()) if packet.stream() == self.stream_index => {
+                    self.decoder.send_packet(&packet)?
+                }
+                Ok(()) => {}
+                Err(ffmpeg::Error::Eof) => {
+                    // Flush the frames still buffered in the decoder
+                    self.decoder.send_eof()?;
+                    self.end_of_file = true;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn frame(&self) -> &ffmpeg::frame::Video {
+        if self.use_deinterlaced {
+            &self.deinterlaced
+        } else {
+            &self.decoded
+        }
+    }
+
+    /// Presentation time of the current frame in seconds, if the container gives one, sped
+    /// up along with the video.
+    pub fn timestamp(&self) -> Option<f64> {
+        self.source_timestamp()
+            .map(|timestamp| timestamp / self.speed)
+    }
+
+    /// Presentation time of the current frame in seconds as recorded, from the start of
+    /// the video.
+    fn source_timestamp(&self) -> Option<f64> {
+        let origin = self.origin.unwrap_or(0.0);
+        self.frame()
+            .pts()
+            .map(|pts| pts as f64 * self.time_base - origin)
+    }
+
+    /// The current frame as packed RGB24, as decoded before rotation.
+    pub fn rgb_frame(&mut self) -> Result<&ffmpeg::frame::Video, AsciiGenError> {
+        if !self.scaled {
+            let _span = tracing::trace_span!("scale").entered();
+            let frame = if self.use_deinterlaced {
+                &self.deinterlaced
+            } else {
+                &self.decoded
+            };
+            // Decoders may only tell the format from the first frame, and streams may change
+            // their format or size midway
+            let input = (frame.format(), frame.width(), frame.height());
+            let scaler = match self.scaler.take() {
+                Some(scaler) if scaler_input(&scaler) == input => scaler,
+                _ => rgb_scaler(input)?,
+            };
+            self.scaler.insert(scaler).run(frame, &mut self.rgb_frame)?;
+            if self.rgb_frame.format() != ffmpeg::format::Pixel::RGB24 {
+                return Err(AsciiGenError::PixelFormat(pixel_format_name(
+                    self.rgb_frame.format(),
+                )));
+            }
+            self.scaled = true;
+        }
+        Ok(&self.rgb_frame)
+    }
+
+    /// Converts the current frame into `art`, stabilizing against the previous frame.
+    pub fn convert(
+        &mut self,
+        art: &mut ArtBuffer,
+        options: &AsciiOptions,
+        color_mode: ColorMode,
+        temporal_filter: &mut TemporalFilter,
+    ) -> Result<(), AsciiGenError> {
+        let _span = tracing::trace_span!("convert").entered();
+        let frame_converter = self.frame_converter()?;
+        art.convert(
+            &converter::crop(&frame_converter, options)?,
+            options,
+            color_mode,
+            temporal_filter,
+        );
+        Ok(())
+    }
+
+    /// Moves `exposure` towards the exposure of the current frame, cropped like `options`
+    /// and shown for `elapsed`.
+    pub fn measure_exposure(
+        &mut self,
+        exposure: &mut AutoExposure,
+        options: &AsciiOptions,
+        elapsed: Duration,
+    ) -> Result<(), AsciiGenError> {
+        let _span = tracing::trace_span!("measure_exposure").entered();
+        let frame_converter = self.frame_converter()?;
+        exposure.measure(&converter::crop(&frame_converter, options)?, elapsed);
+        Ok(())
+    }
+
+    /// The current frame as a pixel source, rotated upright.
+    fn frame_converter(&mut self) -> Result<FrameConverter<'_>, AsciiGenError> {
+        let rotation = self.rotation;
+        let rgb_frame = self.rgb_frame()?;
+        Ok(FrameConverter::from_rgb_plane(
+            rgb_frame.data(0),
+            rgb_frame.width(),
+            rgb_frame.height(),
+            rgb_frame.stride(0),
+            rotation,
+        )?)
+    }
+}
+
+/// A scaler converting frames of this format and size to RGB24 at the same size, or an
+/// error naming the format when swscale cannot read it.
+fn rgb_scaler(
+    (format, width, height): (ffmpeg::format::Pixel, u32, u32),
+) -> Result<ffmpeg::software::scaling::Context, AsciiGenError> {
+    if width == 0 || height == 0 {
+        return Err(AsciiGenError::EmptySource);
+    }
+    // Hardware formats and unknown ones would make swscale fail without saying why
+    let supported = format != ffmpeg::format::Pixel::None
+        // SAFETY: the call only looks the format up in a table
+        && unsafe { ffmpeg::ffi::sws_isSupportedInput(format.into()) } > 0;
+    if !supported {
+        return Err(AsciiGenError::PixelFormat(pixel_format_name(format)));
+    }
+    tracing::debug!(
+        pixel_format = pixel_format_name(format),
+        width,
+        height,
+        "converting frames to RGB"
+    );
+    Ok(ffmpeg::software::scaling::Context::get(
+        format,
+        width,
+        height,
+        ffmpeg::format::Pixel::RGB24,
+        width,
+        height,
+        ffmpeg::software::scaling::flag::Flags::BILINEAR,
+    )?)
+}
+
+/// The format and size of the frames a scaler takes.
+fn scaler_input(scaler: &ffmpeg::software::scaling::Context) -> (ffmpeg::format::Pixel, u32, u32) {
+    let input = scaler.input();
+    (input.format, input.width, input.height)
+}
+
+/// The name ffmpeg gives a pixel format, such as `yuv420p10le`.
+fn pixel_format_name(format: ffmpeg::format::Pixel) -> String {
+    format.descriptor().map_or_else(
+        || format!("{:?}", format),
+        |descriptor| descriptor.name().to_string(),
+    )
+}
+
+/// Formats seconds as `m:ss`, or `h:mm:ss` from an hour on.
+pub fn format_timestamp(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// One converted video frame.
+#[derive(Clone, Debug, Default)]
+pub struct AsciiFrame {
+    pub art: String,
+    /// Color of each cell, empty without a color mode.
+    pub colors: Vec<CellColor>,
+    /// When to show the frame, from the start of the video, if the container gives one.
+    pub timestamp: Option<Duration>,
+    /// How long the frame stays on screen, or zero when the frame rate is unknown.
+    pub duration: Duration,
+    pub decode_time: Duration,
+    pub convert_time: Duration,
+}
+
+/// Converts a video into an iterator of `AsciiFrame`s, leaving the display to the caller.
+///
+/// ```no_run
+/// use ascii_gen::{converter::AsciiOptions, stream::VideoConverter};
+///
+/// let options = AsciiOptions::builder().size(80, 24).build()?;
+/// for frame in VideoConverter::open("movie.mp4", options)? {
+///     println!("{}", frame?.art);
+/// }
+/// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+/// ```
+pub struct VideoConverter {
+    stream: VideoAsciiStream,
+    options: AsciiOptions,
+    color_mode: ColorMode,
+    temporal_filter: TemporalFilter,
+    exposure: Option<AutoExposure>,
+    buffer: ArtBuffer,
+    mapper: Option<SharedMapper>,
+    /// When playback started, for dropping frames that are already late.
+    start_time: Option<Instant>,
+    dropped_frames: u64,
+    pool: FramePool,
+}
+
+/// Time between frames assumed for auto-exposure when the stream does not tell.
+const UNKNOWN_FRAME_INTERVAL: Duration = Duration::from_millis(40);
+
+/// Most frames kept for reuse, enough for one on screen and a few in flight.
+const POOL_SIZE: usize = 4;
+
+/// Buffers of frames handed back with `VideoConverter::recycle`, filled again for the next
+/// frames instead of allocating new ones. The ffmpeg frames are reused by the stream itself.
+#[derive(Default)]
+struct FramePool {
+    arts: Vec<String>,
+    colors: Vec<Vec<CellColor>>,
+}
+
+impl VideoConverter {
+    /// Opens a video, deinterlacing it when needed and honoring its rotation metadata.
+    pub fn open(path: impl AsRef<Path>, options: AsciiOptions) -> Result<Self, AsciiGenError> {
+        let stream = VideoAsciiStream::open(path, Deinterlace::Auto, true)?;
+        Ok(Self::new(stream, options))
+    }
+
+    /// Converts an already opened stream, for other deinterlacing or rotation settings.
+    pub fn new(stream: VideoAsciiStream, options: AsciiOptions) -> Self {
+        Self {
+            stream,
+            options,
+            color_mode: ColorMode::None,
+            temporal_filter: TemporalFilter::new(0),
+            exposure: None,
+            buffer: ArtBuffer::default(),
+            mapper: None,
+            start_time: None,
+            dropped_frames: 0,
+            pool: FramePool::default(),
+        }
+    }
+
+    pub fn with_color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self
+    }
+
+    /// Blends each frame with the previous `strength` frames to reduce flicker.
+    pub fn with_temporal_smooth(mut self, strength: u32) -> Self {
+        self.temporal_filter = TemporalFilter::new(strength);
+        self
+    }
+
+    /// Adjusts the gamma and contrast of each frame to its own brightness, easing the
+    /// changes in over time.
+    pub fn with_auto_expose(mut self, auto_expose: bool) -> Self {
+        self.exposure = auto_expose.then(AutoExposure::default);
+        self
+    }
+
+    /// Picks each cell's character, and optionally its color, with `mapper` instead of the
+    /// options' charset.
+    pub fn with_mapper(mut self, mapper: SharedMapper) -> Self {
+        self.mapper = Some(mapper);
+        self
+    }
+
+    /// Changes the options from the next converted frame on.
+    pub fn set_options(&mut self, options: AsciiOptions, color_mode: ColorMode) {
+        self.options = options;
+        self.color_mode = color_mode;
+    }
+
+    /// Drops frames more than a frame interval behind playback started at `start_time`
+    /// instead of converting them, so a slow display catches up with the video.
+    pub fn set_start_time(&mut self, start_time: Option<Instant>) {
+        self.start_time = start_time;
+    }
+
+    /// Number of frames dropped for being late.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
+    pub fn stream(&mut self) -> &mut VideoAsciiStream {
+        &mut self.stream
+    }
+
+    /// Hands a frame that is no longer needed back, so the next frame reuses its buffers.
+    pub fn recycle(&mut self, frame: AsciiFrame) {
+        if self.pool.arts.len() < POOL_SIZE {
+            self.pool.arts.push(frame.art);
+        }
+        if self.pool.colors.len() < POOL_SIZE {
+            self.pool.colors.push(frame.colors);
+        }
+    }
+
+    /// Converts the current frame again, e.g. to show new options while paused.
+    pub fn reconvert(&mut self) -> Result<AsciiFrame, AsciiGenError> {
+        self.convert(Duration::ZERO)
+    }
+
+    fn convert(&mut self, decode_time: Duration) -> Result<AsciiFrame, AsciiGenError> {
+        let convert_start = Instant::now();
+        let exposed;
+        let options = match &self.exposure {
+            Some(exposure) => {
+                exposed = exposure.apply(&self.options);
+                &exposed
+            }
+            None => &self.options,
+        };
+        self.stream.convert(
+            &mut self.buffer,
+            options,
+            self.color_mode,
+            &mut self.temporal_filter,
+        )?;
+        if let Some(mapper) = &self.mapper {
+            self.buffer.remap(mapper.as_ref(), options, self.color_mode);
+        }
+
+        let mut art = self.pool.arts.pop().unwrap_or_default();
+        art.clone_from(&self.buffer.art);
+        let mut colors = self.pool.colors.pop().unwrap_or_default();
+        colors.clone_from(&self.buffer.colors);
+
+        Ok(AsciiFrame {
+            art,
+            colors,
+            // Frames decoded ahead of the first one, such as B-frames, may come before it
+            timestamp: self.stream.timestamp().map(non_negative_seconds),
+            duration: non_negative_seconds(self.stream.frame_interval()),
+            decode_time,
+            convert_time: convert_start.elapsed(),
+        })
+    }
+
+    /// Whether the current frame is too late to be shown.
+    fn is_late(&self) -> bool {
+        let (Some(start_time), Some(timestamp)) = (self.start_time, self.stream.timestamp()) else {
+            return false;
+        };
+        let frame_interval = self.stream.frame_interval();
+        frame_interval > 0.0 && start_time.elapsed().as_secs_f64() - timestamp > frame_interval
+    }
+}
+
+impl Iterator for VideoConverter {
+    type Item = Result<AsciiFrame, AsciiGenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let decode_start = Instant::now();
+            match self.stream.advance() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => {
+                    tracing::error!(error = %e, "decoding failed");
+                    return Some(Err(e.into()));
+                }
+            }
+            let decode_time = decode_start.elapsed();
+
+            let late = self.is_late();
+            // Frames no other frame needs are not worth decoding while behind
+            self.stream.set_behind(late);
+            if late {
+                self.dropped_frames += 1;
+                tracing::debug!(dropped = self.dropped_frames, "dropped a late frame");
+                continue;
+            }
+            // Only new frames move the exposure, not converting a paused frame again
+            if let Some(exposure) = &mut self.exposure {
+                let elapsed = match self.stream.frame_interval() {
+                    interval if interval > 0.0 => Duration::from_secs_f64(interval),
+                    _ => UNKNOWN_FRAME_INTERVAL,
+                };
+                let measured = self
+                    .stream
+                    .measure_exposure(exposure, &self.options, elapsed);
+                if let Err(e) = measured {
+                    return Some(Err(e));
+                }
+            }
+            return Some(self.convert(decode_time));
+        }
+    }
+}
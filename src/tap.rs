@@ -0,0 +1,71 @@
+use ascii_gen::spectrum::Spectrum;
+use rodio::source::SeekError;
+use rodio::Source;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Samples gathered before they are handed to the spectrum, to lock it less often.
+const CHUNK: usize = 1024;
+
+/// Plays a source unchanged while feeding the samples it decodes to a spectrum.
+pub struct Tap<S> {
+    source: S,
+    spectrum: Arc<Mutex<Spectrum>>,
+    chunk: Vec<f32>,
+}
+
+impl<S: Source<Item = i16>> Tap<S> {
+    pub fn new(source: S, spectrum: Arc<Mutex<Spectrum>>) -> Self {
+        Self {
+            source,
+            spectrum,
+            chunk: Vec::with_capacity(CHUNK),
+        }
+    }
+
+    fn flush(&mut self) {
+        let (channels, sample_rate) = (self.source.channels(), self.source.sample_rate());
+        self.spectrum
+            .lock()
+            .unwrap()
+            .push(&self.chunk, channels, sample_rate);
+        self.chunk.clear();
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Tap<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.source.next()?;
+        self.chunk.push(f32::from(sample) / f32::from(i16::MAX));
+        if self.chunk.len() >= CHUNK {
+            self.flush();
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Tap<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        // Samples from before the jump are no longer playing
+        self.chunk.clear();
+        self.source.try_seek(position)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::charset::Charset;
+
+/// How far (in luminance) a cell may drift past a threshold before it changes character.
+const HYSTERESIS: f32 = 6.0;
+
+/// The dead band around a level, at most half its width so a steady cell settles on its own glyph.
+fn hysteresis(lower: u8, upper: u8) -> f32 {
+    HYSTERESIS.min((upper - lower + 1) as f32 / 2.0)
+}
+
+/// Stabilizes luminance grids across video frames to reduce character flicker.
+pub struct TemporalFilter {
+    strength: u32,
+    /// The running average of each cell, never pulled toward a threshold.
+    average: Vec<f32>,
+    /// The luminance last written out for each cell, whose level hysteresis holds on to.
+    shown: Vec<u8>,
+}
+
+impl TemporalFilter {
+    /// Creates a filter blending each frame with the previous `strength` frames.
+    /// A strength of 0 disables the filter.
+    pub fn new(strength: u32) -> Self {
+        Self {
+            strength,
+            average: Vec::new(),
+            shown: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.strength > 0
+    }
+
+    /// Blends the grid with the previous frame in place and applies hysteresis
+    /// around the charset thresholds.
+    pub fn apply(&mut self, grid: &mut [u8], charset: Charset) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        // Start over whenever the grid dimensions change
+        if self.average.len() != grid.len() {
+            self.average = grid.iter().map(|&l| l as f32).collect();
+            self.shown = grid.to_vec();
+            return;
+        }
+
+        let weight = 1.0 / (self.strength as f32 + 1.0);
+
+        let cells = grid
+            .iter_mut()
+            .zip(self.average.iter_mut())
+            .zip(self.shown.iter_mut());
+        for ((cell, average), shown) in cells {
+            *average += (*cell as f32 - *average) * weight;
+            let blended = average.round();
+
+            // Keep the shown character unless the average clearly left its range
+            let level = charset.level(*shown);
+            let (lower, upper) = charset.bounds(level);
+            let band = hysteresis(lower, upper);
+            let (lower, upper) = (lower as f32, upper as f32);
+
+            let stabilized = if blended < lower && blended > lower - band {
+                lower
+            } else if blended > upper && blended < upper + band {
+                upper
+            } else {
+                blended
+            };
+
+            *shown = stabilized.clamp(0.0, 255.0) as u8;
+            *cell = *shown;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle(filter: &mut TemporalFilter, start: u8, target: u8, charset: Charset) -> u8 {
+        filter.apply(&mut [start], charset);
+        let mut cell = [target];
+        for _ in 0..500 {
+            cell = [target];
+            filter.apply(&mut cell, charset);
+        }
+        cell[0]
+    }
+
+    #[test]
+    fn steady_input_converges_to_its_own_glyph() {
+        for charset in Charset::all() {
+            for level in 0..charset.chars().len() {
+                let (lower, upper) = charset.bounds(level);
+                let target = ((lower as u16 + upper as u16) / 2) as u8;
+                for strength in [1, 4, 10] {
+                    for start in [0, 255] {
+                        let settled =
+                            settle(&mut TemporalFilter::new(strength), start, target, charset);
+                        assert_eq!(
+                            charset.level(settled),
+                            level,
+                            "{:?} strength {} from {} settled at {} for {}",
+                            charset,
+                            strength,
+                            start,
+                            settled,
+                            target
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn steady_input_past_the_dead_band_is_not_held_back() {
+        let charset = Charset::Standard;
+        let (_, upper) = charset.bounds(charset.level(0));
+        let target = upper + HYSTERESIS as u8 + 1;
+        let settled = settle(&mut TemporalFilter::new(10), 0, target, charset);
+        assert_eq!(settled, target);
+    }
+
+    #[test]
+    fn small_flicker_keeps_the_character() {
+        let charset = Charset::Standard;
+        let (_, upper) = charset.bounds(0);
+        let mut filter = TemporalFilter::new(2);
+        filter.apply(&mut [upper - 1], charset);
+        for step in 0..20 {
+            let mut cell = [if step % 2 == 0 { upper + 3 } else { upper - 1 }];
+            filter.apply(&mut cell, charset);
+            assert_eq!(charset.level(cell[0]), 0);
+        }
+    }
+
+    #[test]
+    fn disabled_filter_leaves_the_grid_alone() {
+        let mut filter = TemporalFilter::new(0);
+        let mut grid = [10, 200, 30];
+        filter.apply(&mut grid, Charset::Standard);
+        filter.apply(&mut grid, Charset::Standard);
+        assert_eq!(grid, [10, 200, 30]);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::charset::Charset;
+use crate::converter::{self, AsciiOptions};
+
+/// The tone and character of every luminance for one set of options, so converting a cell
+/// takes a weighted sum and two lookups instead of a `powf`.
+pub struct ToneTable {
+    tones: [u8; 256],
+    glyphs: [char; 256],
+    charset: Charset,
+    /// The tone of every luminance in the low byte and the charset index of that tone in the
+    /// next, widened so a row kernel gathers both with one lookup.
+    cells: [i32; 256],
+}
+
+impl ToneTable {
+    pub fn new(options: &AsciiOptions) -> Self {
+        let tones: [u8; 256] = std::array::from_fn(|base| {
+            let corrected = converter::gamma_correct(base as u8, options.gamma());
+            let tone = options.curve().apply(corrected);
+            if options.invert() {
+                255 - tone
+            } else {
+                tone
+            }
+        });
+        let charset = options.charset();
+        let glyphs = std::array::from_fn(|tone| charset.char_for(tone as u8));
+        let cells = std::array::from_fn(|base| {
+            let tone = tones[base];
+            i32::from(tone) | (charset.level(tone) as i32) << 8
+        });
+        Self {
+            tones,
+            glyphs,
+            charset,
+            cells,
+        }
+    }
+
+    /// Same as `AsciiOptions::tone`.
+    pub fn tone(&self, rgb: [u8; 3]) -> u8 {
+        self.tones[converter::base_luminance(rgb) as usize]
+    }
+
+    /// The character of the options' charset for a tone.
+    pub fn glyph(&self, tone: u8) -> char {
+        self.glyphs[tone as usize]
+    }
+
+    /// Writes the tone of every pixel into `tones`, reusing its allocation.
+    pub fn tones_into(&self, pixels: &[[u8; 3]], tones: &mut Vec<u8>) {
+        tones.clear();
+        tones.reserve(pixels.len());
+        self.cells_row(pixels, tones, None);
+    }
+
+    /// Writes the tone of every pixel into `tones` and its index into the charset into
+    /// `levels`, eight pixels at a time where the CPU supports AVX2.
+    pub fn cells_into(&self, pixels: &[[u8; 3]], tones: &mut Vec<u8>, levels: &mut Vec<u8>) {
+        tones.clear();
+        tones.reserve(pixels.len());
+        levels.clear();
+        levels.reserve(pixels.len());
+        self.cells_row(pixels, tones, Some(levels));
+    }
+
+    /// Draws the characters of `levels` into `art`, a line per `width` cells.
+    pub fn art_into(&self, levels: &[u8], width: u32, art: &mut String) {
+        let chars = self.charset.chars();
+        art.clear();
+        for row in levels.chunks(width.max(1) as usize) {
+            art.extend(row.iter().map(|&level| chars[level as usize]));
+            art.push('\n');
+        }
+    }
+
+    /// Same as `art_into`, drawing the characters of tones, e.g. ones a temporal filter
+    /// blended after `tones_into`.
+    pub fn tone_art_into(&self, tones: &[u8], width: u32, art: &mut String) {
+        art.clear();
+        for row in tones.chunks(width.max(1) as usize) {
+            art.extend(row.iter().map(|&tone| self.glyphs[tone as usize]));
+            art.push('\n');
+        }
+    }
+
+    fn cells_row(&self, pixels: &[[u8; 3]], tones: &mut Vec<u8>, levels: Option<&mut Vec<u8>>) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU was just checked to support AVX2
+            return unsafe { avx2::cells_row(&self.cells, pixels, tones, levels) };
+        }
+        self.cells_row_scalar(pixels, tones, levels)
+    }
+
+    fn cells_row_scalar(
+        &self,
+        pixels: &[[u8; 3]],
+        tones: &mut Vec<u8>,
+        levels: Option<&mut Vec<u8>>,
+    ) {
+        let cells = pixels
+            .iter()
+            .map(|&rgb| self.cells[converter::base_luminance(rgb) as usize]);
+        match levels {
+            Some(levels) => {
+                for cell in cells {
+                    tones.push(cell as u8);
+                    levels.push((cell >> 8) as u8);
+                }
+            }
+            None => tones.extend(cells.map(|cell| cell as u8)),
+        }
+    }
+}
+
+/// The row kernel: luminance, tone and charset index of eight pixels per iteration.
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 8;
+
+    /// Pixels and lanes run through this in the same order with the same float operations
+    /// as `converter::base_luminance`, without fused multiply-adds, so every luminance
+    /// matches the scalar path exactly.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn cells_row(
+        cells: &[i32; 256],
+        pixels: &[[u8; 3]],
+        tones: &mut Vec<u8>,
+        mut levels: Option<&mut Vec<u8>>,
+    ) {
+        // Bytes of the red, green and blue of eight packed pixels, from the first 16 bytes
+        // and from the 16 bytes after the first 8, with -1 leaving a lane empty
+        #[rustfmt::skip]
+        let masks = [
+            (_mm_setr_epi8(0, 3, 6, 9, 12, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1),
+             _mm_setr_epi8(-1, -1, -1, -1, -1, 7, 10, 13, -1, -1, -1, -1, -1, -1, -1, -1)),
+            (_mm_setr_epi8(1, 4, 7, 10, 13, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1),
+             _mm_setr_epi8(-1, -1, -1, -1, -1, 8, 11, 14, -1, -1, -1, -1, -1, -1, -1, -1)),
+            (_mm_setr_epi8(2, 5, 8, 11, 14, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1),
+             _mm_setr_epi8(-1, -1, -1, -1, -1, 9, 12, 15, -1, -1, -1, -1, -1, -1, -1, -1)),
+        ];
+        let weights = [
+            _mm256_set1_ps(0.2126),
+            _mm256_set1_ps(0.7152),
+            _mm256_set1_ps(0.0722),
+        ];
+        let max = _mm256_set1_epi32(255);
+        let low_byte = _mm256_set1_epi32(0xff);
+
+        let mut chunks = pixels.chunks_exact(LANES);
+        for chunk in chunks.by_ref() {
+            let bytes = chunk.as_ptr().cast::<u8>();
+            // SAFETY: a chunk is 24 bytes, and both loads stay within them
+            let (front, back) = (
+                _mm_loadu_si128(bytes.cast()),
+                _mm_loadu_si128(bytes.add(8).cast()),
+            );
+            let channel = |(front_mask, back_mask): (__m128i, __m128i)| {
+                let packed = _mm_or_si128(
+                    _mm_shuffle_epi8(front, front_mask),
+                    _mm_shuffle_epi8(back, back_mask),
+                );
+                _mm256_cvtepi32_ps(_mm256_cvtepu8_epi32(packed))
+            };
+            let (r, g, b) = (channel(masks[0]), channel(masks[1]), channel(masks[2]));
+
+            let luminance = _mm256_add_ps(
+                _mm256_add_ps(_mm256_mul_ps(weights[0], r), _mm256_mul_ps(weights[1], g)),
+                _mm256_mul_ps(weights[2], b),
+            );
+            // Truncated like `as u8`, and kept within the table whatever the rounding
+            let index = _mm256_min_epi32(_mm256_cvttps_epi32(luminance), max);
+            let cell = _mm256_i32gather_epi32::<4>(cells.as_ptr(), index);
+
+            tones.extend_from_slice(&pack(_mm256_and_si256(cell, low_byte)));
+            if let Some(levels) = levels.as_deref_mut() {
+                levels.extend_from_slice(&pack(_mm256_srli_epi32::<8>(cell)));
+            }
+        }
+
+        for &rgb in chunks.remainder() {
+            let cell = cells[crate::converter::base_luminance(rgb) as usize];
+            tones.push(cell as u8);
+            if let Some(levels) = levels.as_deref_mut() {
+                levels.push((cell >> 8) as u8);
+            }
+        }
+    }
+
+    /// The eight lanes, each from 0 to 255, as bytes.
+    #[target_feature(enable = "avx2")]
+    unsafe fn pack(lanes: __m256i) -> [u8; LANES] {
+        let words = _mm_packus_epi32(
+            _mm256_castsi256_si128(lanes),
+            _mm256_extracti128_si256::<1>(lanes),
+        );
+        let bytes = _mm_packus_epi16(words, words);
+        (_mm_cvtsi128_si64(bytes) as u64).to_le_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every channel level on its own, grays, and a spread of mixed colors, in a count that
+    /// leaves a remainder after the eight-pixel chunks.
+    fn pixels() -> Vec<[u8; 3]> {
+        let mut pixels = Vec::new();
+        for value in 0..=255u8 {
+            pixels.extend([[value, 0, 0], [0, value, 0], [0, 0, value], [value; 3]]);
+        }
+        for r in (0..=255u8).step_by(15) {
+            for g in (0..=255u8).step_by(17) {
+                for b in (0..=255u8).step_by(51) {
+                    pixels.push([r, g, b]);
+                }
+            }
+        }
+        pixels.push([255, 255, 255]);
+        assert_ne!(pixels.len() % 8, 0);
+        pixels
+    }
+
+    #[test]
+    fn row_kernel_matches_the_scalar_path() {
+        let pixels = pixels();
+        for charset in Charset::all() {
+            for (gamma, invert) in [(1.0, false), (0.45, false), (2.2, true)] {
+                let options = AsciiOptions::new(80, 24, gamma)
+                    .with_charset(charset)
+                    .with_invert(invert);
+                let table = ToneTable::new(&options);
+
+                let (mut tones, mut levels) = (Vec::new(), Vec::new());
+                table.cells_into(&pixels, &mut tones, &mut levels);
+                let (mut scalar_tones, mut scalar_levels) = (Vec::new(), Vec::new());
+                table.cells_row_scalar(&pixels, &mut scalar_tones, Some(&mut scalar_levels));
+                assert_eq!(tones, scalar_tones);
+                assert_eq!(levels, scalar_levels);
+
+                let mut only_tones = Vec::new();
+                table.tones_into(&pixels, &mut only_tones);
+                assert_eq!(only_tones, scalar_tones);
+
+                // And the same as converting one cell at a time
+                for ((&rgb, &tone), &level) in pixels.iter().zip(&tones).zip(&levels) {
+                    assert_eq!(tone, options.tone(rgb));
+                    assert_eq!(level as usize, charset.level(tone));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn art_is_drawn_from_the_levels() {
+        let options = AsciiOptions::new(2, 2, 1.0);
+        let table = ToneTable::new(&options);
+        let (mut tones, mut levels) = (Vec::new(), Vec::new());
+        table.cells_into(
+            &[[0; 3], [255; 3], [255; 3], [0; 3]],
+            &mut tones,
+            &mut levels,
+        );
+        let mut art = String::new();
+        table.art_into(&levels, 2, &mut art);
+        let (dark, bright) = (table.glyph(0), table.glyph(255));
+        assert_eq!(art, format!("{dark}{bright}\n{bright}{dark}\n"));
+
+        let mut tone_art = String::new();
+        table.tone_art_into(&tones, 2, &mut tone_art);
+        assert_eq!(tone_art, art);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::AsciiGenError;
+use ffmpeg_next as ffmpeg;
+use image::DynamicImage;
+use std::path::Path;
+
+/// The tags and cover art of an audio file, for a "now playing" screen.
+///
+/// ```no_run
+/// use ascii_gen::track::Track;
+///
+/// let track = Track::open("song.flac")?;
+/// println!("{}", track.title.as_deref().unwrap_or("Unknown title"));
+/// # Ok::<(), ascii_gen::error::AsciiGenError>(())
+/// ```
+pub struct Track {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// Length in seconds, if the container gives one.
+    pub duration: Option<f64>,
+    /// The embedded picture, an ID3 `APIC` frame or a Vorbis `METADATA_BLOCK_PICTURE`.
+    pub cover: Option<DynamicImage>,
+}
+
+impl Track {
+    /// Reads the tags and decodes the cover of the file. A cover that does not decode is
+    /// left out rather than failing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AsciiGenError> {
+        ffmpeg::init()?;
+        let mut input = ffmpeg::format::input(&path.as_ref())?;
+
+        // Ogg and Opus keep their tags on the audio stream rather than the container
+        let tag = |key: &str| {
+            std::iter::once(input.metadata())
+                .chain(input.streams().map(|stream| stream.metadata()))
+                .find_map(|tags| tags.get(key).map(str::to_string))
+                .filter(|value| !value.trim().is_empty())
+        };
+        let title = tag("title");
+        let artist = tag("artist").or_else(|| tag("album_artist"));
+        let album = tag("album");
+        let duration = (input.duration() > 0)
+            .then(|| input.duration() as f64 / f64::from(ffmpeg::ffi::AV_TIME_BASE));
+
+        let cover_stream = input
+            .streams()
+            .find(|stream| {
+                stream
+                    .disposition()
+                    .contains(ffmpeg::format::stream::Disposition::ATTACHED_PIC)
+            })
+            .map(|stream| stream.index());
+        // The picture is the one and only packet of its stream
+        let cover = cover_stream.and_then(|index| {
+            let (_, packet) = input
+                .packets()
+                .find(|(stream, _)| stream.index() == index)?;
+            match image::load_from_memory(packet.data()?) {
+                Ok(cover) => Some(cover),
+                Err(e) => {
+                    tracing::warn!(error = %e, "cannot decode the cover art");
+                    None
+                }
+            }
+        });
+
+        Ok(Self {
+            title,
+            artist,
+            album,
+            duration,
+            cover,
+        })
+    }
+}
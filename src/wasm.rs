@@ -0,0 +1,42 @@
+use crate::color::ColorMode;
+use crate::converter::{AsciiOptions, ImageConverter, ToAsciiArt};
+use crate::output::HtmlRenderer;
+use wasm_bindgen::prelude::*;
+
+/// Converts an encoded image (PNG, JPEG, ...) to plain ASCII art.
+///
+/// `options` is an object with any of the `AsciiOptions` fields, e.g.
+/// `{ width: 80, height: 40, charset: "blocks" }`; missing fields take their defaults.
+#[wasm_bindgen]
+pub fn convert(bytes: &[u8], options: JsValue) -> Result<String, JsError> {
+    let source = ImageConverter::from_image(image::load_from_memory(bytes)?);
+    Ok(source.to_ascii_art(Some(parse_options(options)?))?)
+}
+
+/// Same as `convert`, but as an HTML page coloring each character like its part of the image.
+#[wasm_bindgen(js_name = convertHtml)]
+pub fn convert_html(bytes: &[u8], options: JsValue) -> Result<String, JsError> {
+    let source = ImageConverter::from_image(image::load_from_memory(bytes)?);
+    let options = parse_options(options)?;
+    let charset = options.charset();
+    Ok(source.to_ascii_art_with(Some(options), &charset, &HtmlRenderer, ColorMode::Truecolor)?)
+}
+
+/// Reads the options object, checking it like `AsciiOptionsBuilder::build`.
+fn parse_options(options: JsValue) -> Result<AsciiOptions, JsError> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(AsciiOptions::default());
+    }
+
+    let options: AsciiOptions = serde_wasm_bindgen::from_value(options)?;
+    Ok(AsciiOptions::builder()
+        .size(options.width(), options.height())
+        .gamma(options.gamma())
+        .charset(options.charset())
+        .invert(options.invert())
+        .crop(options.crop())
+        .curve(options.curve().clone())
+        .palette(options.palette().cloned())
+        .upscale(options.upscale())
+        .build()?)
+}
@@ -0,0 +1,82 @@
+use ascii_gen::error::AsciiGenError;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+/// Reports changes to files on disk. Their directories are watched rather than the files,
+/// so editors that save through a temporary file and a rename are noticed too.
+pub struct FileWatcher {
+    // Stops watching once dropped
+    _watcher: RecommendedWatcher,
+    changes: Receiver<PathBuf>,
+}
+
+impl FileWatcher {
+    /// Watches the directories of `files`.
+    pub fn new(files: &[String]) -> Result<Self, AsciiGenError> {
+        let (sender, changes) = mpsc::channel();
+        let watch_error = |path: &Path, e: notify::Error| AsciiGenError::Read {
+            path: path.to_path_buf(),
+            source: io::Error::other(e),
+        };
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    for path in event.paths {
+                        let _ = sender.send(path);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "watching files failed"),
+            })
+            .map_err(|e| watch_error(Path::new("."), e))?;
+
+        let mut directories: Vec<PathBuf> =
+            files.iter().filter_map(|file| directory(file)).collect();
+        directories.sort();
+        directories.dedup();
+        for directory in &directories {
+            watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .map_err(|e| watch_error(directory, e))?;
+            tracing::debug!(directory = %directory.display(), "watching for changes");
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// The files changed since the last call, each once however many writes saving took.
+    pub fn changed(&self) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = self.changes.try_iter().filter_map(canonical).collect();
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+/// Whether `file` is one of the `changed` paths.
+pub fn contains(changed: &[PathBuf], file: &str) -> bool {
+    canonical(PathBuf::from(file)).is_some_and(|file| changed.contains(&file))
+}
+
+/// The directory a file is in, as an absolute path.
+fn directory(file: &str) -> Option<PathBuf> {
+    let parent = Path::new(file).parent()?;
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+    parent.canonicalize().ok()
+}
+
+/// The path with its directory resolved, which works for files replaced a moment ago.
+fn canonical(path: PathBuf) -> Option<PathBuf> {
+    let name = path.file_name()?.to_owned();
+    Some(directory(path.to_str()?)?.join(name))
+}
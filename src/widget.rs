@@ -0,0 +1,332 @@
+use crate::art::ArtBuffer;
+use crate::color::{CellColor, ColorMode};
+use crate::converter::{self, AsciiOptions, ImageConverter, PixelSource};
+use crate::temporal::TemporalFilter;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::{Block, StatefulWidget, Widget},
+};
+
+/// Renders ASCII art directly into the terminal buffer, clipped to its area.
+pub struct AsciiArtWidget<'a> {
+    art: &'a str,
+    colors: &'a [CellColor],
+    color_mode: ColorMode,
+    block: Option<Block<'a>>,
+    scroll: (u16, u16),
+}
+
+impl<'a> AsciiArtWidget<'a> {
+    pub fn new(art: &'a str) -> Self {
+        Self {
+            art,
+            colors: &[],
+            color_mode: ColorMode::None,
+            block: None,
+            scroll: (0, 0),
+        }
+    }
+
+    /// Colors each character with the matching cell, in row order.
+    pub fn colors(mut self, colors: &'a [CellColor], color_mode: ColorMode) -> Self {
+        self.colors = colors;
+        self.color_mode = color_mode;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Scrolls the art by `(y, x)` cells, like `Paragraph::scroll`.
+    pub fn scroll(mut self, offset: (u16, u16)) -> Self {
+        self.scroll = offset;
+        self
+    }
+}
+
+impl Widget for AsciiArtWidget<'_> {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        let area = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        let (scroll_y, scroll_x) = (self.scroll.0 as usize, self.scroll.1 as usize);
+        let colored = self.color_mode.is_enabled();
+        let mut cell_index = 0;
+
+        for (row, line) in self.art.lines().enumerate() {
+            let line_start = cell_index;
+            cell_index += line.chars().count();
+
+            if row < scroll_y {
+                continue;
+            }
+            let y = area.y + (row - scroll_y) as u16;
+            if y >= area.bottom() {
+                break;
+            }
+
+            for (col, c) in line
+                .chars()
+                .enumerate()
+                .skip(scroll_x)
+                .take(area.width as usize)
+            {
+                let cell = buf.get_mut(area.x + (col - scroll_x) as u16, y);
+                cell.set_char(c);
+
+                if colored {
+                    if let Some(color) = self.colors.get(line_start + col) {
+                        cell.set_style(self.color_mode.style(color));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Converts an image while rendering, so other ratatui apps can embed previews.
+///
+/// As a `StatefulWidget` the art is kept in an [`AsciiImageState`] and only converted again
+/// when the options, color mode or fitted size change.
+pub struct AsciiImageWidget<'a> {
+    source: &'a ImageConverter,
+    options: AsciiOptions,
+    color_mode: ColorMode,
+    fit: bool,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> AsciiImageWidget<'a> {
+    /// Converts `source` at the size fitting the area, keeping its aspect ratio.
+    pub fn new(source: &'a ImageConverter) -> Self {
+        Self {
+            source,
+            options: AsciiOptions::default(),
+            color_mode: ColorMode::None,
+            fit: true,
+            block: None,
+        }
+    }
+
+    pub fn options(mut self, options: AsciiOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self
+    }
+
+    /// Uses the size from the options instead of fitting the area, scrolling larger art.
+    pub fn fit(mut self, fit: bool) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    fn sized_options(&self, area: Rect) -> AsciiOptions {
+        if !self.fit {
+            return self.options.clone();
+        }
+        let Ok(view) = converter::crop(self.source, &self.options).map(|view| view.dimensions())
+        else {
+            return self.options.clone();
+        };
+        let (width, height) =
+            converter::fit_dimensions(view, (area.width as u32, area.height as u32));
+        self.options.clone().with_size(width, height)
+    }
+}
+
+/// The converted art and scroll position of an [`AsciiImageWidget`].
+#[derive(Default)]
+pub struct AsciiImageState {
+    art: ArtBuffer,
+    converted: Option<(AsciiOptions, ColorMode)>,
+    scroll: (u16, u16),
+}
+
+impl AsciiImageState {
+    /// Scrolls the art by `(y, x)` cells, stopping at its edges when rendered.
+    pub fn scroll_by(&mut self, y: i32, x: i32) {
+        let offset = |current: u16, delta: i32| (current as i32 + delta).clamp(0, u16::MAX as i32);
+        self.scroll = (
+            offset(self.scroll.0, y) as u16,
+            offset(self.scroll.1, x) as u16,
+        );
+    }
+
+    /// The current scroll offset as `(y, x)` cells.
+    pub fn scroll(&self) -> (u16, u16) {
+        self.scroll
+    }
+
+    /// The art from the last render.
+    pub fn art(&self) -> &str {
+        &self.art.art
+    }
+}
+
+impl StatefulWidget for AsciiImageWidget<'_> {
+    type State = AsciiImageState;
+
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let area = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        if area.is_empty() {
+            return;
+        }
+
+        let options = self.sized_options(area);
+        let key = (options, self.color_mode);
+        if state.converted.as_ref() != Some(&key) {
+            // A source without pixels has nothing to draw
+            let Ok(source) = converter::crop(self.source, &key.0) else {
+                return;
+            };
+            state
+                .art
+                .convert(&source, &key.0, key.1, &mut TemporalFilter::new(0));
+            state.converted = Some(key);
+        }
+
+        // Keep the scroll within the art so scrolling back takes effect at once
+        let rows = state.art.art.lines().count();
+        let columns = state.art.art.lines().map(|line| line.chars().count()).max();
+        let max_scroll = |len: usize, visible: u16| len.saturating_sub(visible as usize) as u16;
+        state.scroll = (
+            state.scroll.0.min(max_scroll(rows, area.height)),
+            state
+                .scroll
+                .1
+                .min(max_scroll(columns.unwrap_or(0), area.width)),
+        );
+
+        AsciiArtWidget::new(&state.art.art)
+            .colors(&state.art.colors, self.color_mode)
+            .scroll(state.scroll)
+            .render(area, buf);
+    }
+}
+
+impl Widget for AsciiImageWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        StatefulWidget::render(self, area, buf, &mut AsciiImageState::default());
+    }
+}
+
+/// Splits the area into `count` tiles, `columns` to a row and as many rows as it takes, for
+/// showing several sources at once. The last row's tiles are as wide as the others.
+///
+/// ```
+/// use ascii_gen::widget::tile;
+/// use ratatui::layout::Rect;
+///
+/// let tiles = tile(Rect::new(0, 0, 80, 24), 3, 2);
+/// assert_eq!(tiles, vec![
+///     Rect::new(0, 0, 40, 12),
+///     Rect::new(40, 0, 40, 12),
+///     Rect::new(0, 12, 40, 12),
+/// ]);
+/// ```
+pub fn tile(area: Rect, count: usize, columns: usize) -> Vec<Rect> {
+    let columns = columns.clamp(1, count.max(1));
+    let rows = count.div_ceil(columns).max(1);
+    let (column_count, row_count) = (columns as u32, rows as u32);
+
+    // Spread the remainder over the tiles rather than leaving a gap at the edge
+    let edge = |start: u16, length: u16, index: u32, count: u32| {
+        start + (u32::from(length) * index / count) as u16
+    };
+    (0..count as u32)
+        .map(|index| {
+            let (column, row) = (index % column_count, index / column_count);
+            let left = edge(area.x, area.width, column, column_count);
+            let right = edge(area.x, area.width, column + 1, column_count);
+            let top = edge(area.y, area.height, row, row_count);
+            let bottom = edge(area.y, area.height, row + 1, row_count);
+            Rect::new(left, top, right - left, bottom - top)
+        })
+        .collect()
+}
+
+/// Draws levels from 0.0 to 1.0 as bars of eighth blocks, e.g. the bands of a
+/// `Spectrum`, spread across the width and shaded from green to red as they rise.
+pub struct SpectrumWidget<'a> {
+    levels: &'a [f32],
+    block: Option<Block<'a>>,
+}
+
+impl<'a> SpectrumWidget<'a> {
+    pub fn new(levels: &'a [f32]) -> Self {
+        Self {
+            levels,
+            block: None,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl Widget for SpectrumWidget<'_> {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        let area = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        if area.is_empty() || self.levels.is_empty() {
+            return;
+        }
+
+        const EIGHTHS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        for x in 0..area.width {
+            let band = x as usize * self.levels.len() / area.width as usize;
+            let eighths = (self.levels[band].clamp(0.0, 1.0) * area.height as f32 * 8.0) as u16;
+            for row in 0..area.height {
+                let filled = eighths.saturating_sub(row * 8).min(8);
+                if filled == 0 {
+                    break;
+                }
+                let height = (row + 1) as f32 / area.height as f32;
+                let color = if height > 0.85 {
+                    Color::Red
+                } else if height > 0.6 {
+                    Color::Yellow
+                } else {
+                    Color::Green
+                };
+                buf.get_mut(area.x + x, area.bottom() - 1 - row)
+                    .set_char(EIGHTHS[filled as usize])
+                    .set_fg(color);
+            }
+        }
+    }
+}
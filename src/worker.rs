@@ -0,0 +1,195 @@
+use ascii_gen::art::ArtBuffer;
+use ascii_gen::charset::SharedMapper;
+use ascii_gen::color::ColorMode;
+use ascii_gen::converter::{self, AsciiOptions, ImageConverter, Region};
+use ascii_gen::error::AsciiGenError;
+use ascii_gen::plan::Upscale;
+use ascii_gen::progress::ConversionHandle;
+use ascii_gen::temporal::TemporalFilter;
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+/// A request to convert the live editor's image.
+pub struct ConversionJob {
+    pub generation: u64,
+    pub source: Arc<ImageConverter>,
+    pub options: AsciiOptions,
+    pub color_mode: ColorMode,
+    pub mapper: Option<SharedMapper>,
+    /// Part of the cropped source to convert while zoomed, as `(x, y, width, height)`.
+    pub region: Option<(u32, u32, u32, u32)>,
+    /// Size of the half-block rendering of the source, when comparing.
+    pub original_size: Option<(u32, u32)>,
+}
+
+/// The art produced for a job.
+pub struct ConversionResult {
+    pub generation: u64,
+    pub art: ArtBuffer,
+    pub original: Option<ArtBuffer>,
+    /// Time taken to convert the art, not counting the original.
+    pub elapsed: Duration,
+}
+
+/// Everything that decides which colors the sampling pass produces.
+struct SampleKey {
+    source: Arc<ImageConverter>,
+    crop: Option<(u32, u32, u32, u32)>,
+    region: Option<(u32, u32, u32, u32)>,
+    size: (u32, u32),
+    upscale: Upscale,
+    halfblock: bool,
+}
+
+impl SampleKey {
+    fn new(job: &ConversionJob) -> Self {
+        Self {
+            source: Arc::clone(&job.source),
+            crop: job.options.crop(),
+            region: job.region,
+            size: (job.options.width(), job.options.height()),
+            upscale: job.options.upscale(),
+            halfblock: job.color_mode == ColorMode::Halfblock,
+        }
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        // The same image shared by pointer, not compared pixel by pixel
+        Arc::ptr_eq(&self.source, &other.source)
+            && self.crop == other.crop
+            && self.region == other.region
+            && self.size == other.size
+            && self.upscale == other.upscale
+            && self.halfblock == other.halfblock
+    }
+}
+
+/// Colors sampled by the last conversion, so edits that only change the tone mapping
+/// (gamma, curve, charset, inversion) skip the sampling pass.
+#[derive(Default)]
+pub struct SampleCache {
+    key: Option<SampleKey>,
+    pixels: Vec<[u8; 3]>,
+}
+
+/// Converts images on a background thread so the UI keeps responding.
+pub struct ConversionWorker {
+    jobs: Sender<(ConversionJob, ConversionHandle)>,
+    results: Receiver<ConversionResult>,
+    /// Handle of the last submitted job, cancelled once a newer one supersedes it.
+    current: ConversionHandle,
+}
+
+impl ConversionWorker {
+    pub fn spawn() -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<(ConversionJob, ConversionHandle)>();
+        let (result_sender, results) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let mut cache = SampleCache::default();
+            while let Ok(mut job) = job_receiver.recv() {
+                // Skip jobs that were superseded while the previous one was running
+                while let Ok(newer) = job_receiver.try_recv() {
+                    job = newer;
+                }
+
+                let (job, handle) = job;
+                let Ok(result) = convert(job, &handle, &mut cache) else {
+                    continue;
+                };
+                if result_sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            jobs,
+            results,
+            current: ConversionHandle::default(),
+        }
+    }
+
+    /// Queues a job, stopping the one still running as its art would be replaced anyway.
+    pub fn submit(&mut self, job: ConversionJob) {
+        self.current.cancel();
+        self.current = ConversionHandle::new();
+        let _ = self.jobs.send((job, self.current.clone()));
+    }
+
+    /// Returns the most recent finished result, if any.
+    pub fn latest_result(&self) -> Option<ConversionResult> {
+        self.results.try_iter().last()
+    }
+}
+
+/// Runs a job on the current thread, failing if `handle` is cancelled before it finishes.
+pub fn convert(
+    job: ConversionJob,
+    handle: &ConversionHandle,
+    cache: &mut SampleCache,
+) -> Result<ConversionResult, AsciiGenError> {
+    let source = converter::crop(job.source.as_ref(), &job.options)?;
+    let start = Instant::now();
+    let mut art = ArtBuffer::with_histogram();
+    let mut temporal_filter = TemporalFilter::new(0);
+    let key = SampleKey::new(&job);
+
+    if cache
+        .key
+        .as_ref()
+        .is_some_and(|cached| cached.matches(&key))
+    {
+        art.convert_sampled(
+            &cache.pixels,
+            &job.options,
+            job.color_mode,
+            &mut temporal_filter,
+        );
+    } else {
+        match job.region {
+            Some((x, y, width, height)) => {
+                let region = Region::new(&source, x, y, width, height)?;
+                art.convert_tracked(
+                    &region,
+                    &job.options,
+                    job.color_mode,
+                    &mut temporal_filter,
+                    handle,
+                )?;
+            }
+            None => art.convert_tracked(
+                &source,
+                &job.options,
+                job.color_mode,
+                &mut temporal_filter,
+                handle,
+            )?,
+        }
+        cache.pixels.clear();
+        cache.pixels.extend_from_slice(art.sampled());
+        cache.key = Some(key);
+    }
+
+    if let Some(mapper) = &job.mapper {
+        art.remap(mapper.as_ref(), &job.options, job.color_mode);
+    }
+    let elapsed = start.elapsed();
+    tracing::debug!(generation = job.generation, ?elapsed, "converted image");
+
+    let original = job.original_size.map(|(width, height)| {
+        let mut original = ArtBuffer::default();
+        original.convert_original(&source, width, height);
+        original
+    });
+
+    Ok(ConversionResult {
+        generation: job.generation,
+        art,
+        original,
+        elapsed,
+    })
+}